@@ -0,0 +1,176 @@
+//! Command line variable assignments, as provided via `-v name=value` or as `name=value` operands
+
+/// A variable assignment provided on the command line
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assignment {
+    /// the name of the variable to assign to
+    pub name: String,
+    /// the value to assign, with escape sequences already processed
+    pub value: String,
+}
+
+impl Assignment {
+    /// Parses a command line argument of the form `name=value`
+    ///
+    /// Per POSIX, an argument is only treated as an assignment when the characters before the first
+    /// `=` form a valid variable name - an underscore or alphabetic character, followed by any
+    /// number of underscores, digits, or alphabetic characters. Otherwise, the argument is a file
+    /// path (e.g. `./a=b`).
+    ///
+    /// # Arguments
+    /// - `argument` the command line argument to parse
+    ///
+    /// # Return value
+    /// - `Some(Assignment)` if the argument is an assignment
+    /// - `None` otherwise
+    pub fn parse(argument: &str) -> Option<Assignment> {
+        let (name, raw_value) = argument.split_once('=')?;
+
+        let mut name_chars = name.chars();
+        let is_valid_start =
+            matches!(name_chars.next(), Some(ch) if ch.is_alphabetic() || ch == '_');
+        if !is_valid_start || !name_chars.all(|ch| ch.is_alphanumeric() || ch == '_') {
+            return None;
+        }
+
+        Some(Assignment {
+            name: String::from(name),
+            value: process_escape_sequences(raw_value),
+        })
+    }
+}
+
+/// Replaces escape sequences in the value of an assignment with the characters they represent
+///
+/// The supported escape sequences are the ones awk supports in string literals. An unknown escape
+/// sequence is replaced with the character that was escaped (e.g. `\q` becomes `q`), and a trailing
+/// backslash is left in place.
+///
+/// # Arguments
+/// - `raw_value` the value of an assignment, as it was written on the command line
+///
+/// # Return value
+/// the value with its escape sequences processed
+fn process_escape_sequences(raw_value: &str) -> String {
+    let mut processed = String::with_capacity(raw_value.len());
+    let mut chars = raw_value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            processed.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => processed.push('"'),
+            Some('/') => processed.push('/'),
+            Some('\\') => processed.push('\\'),
+            Some('a') => processed.push('\u{07}'),
+            Some('b') => processed.push('\u{08}'),
+            Some('f') => processed.push('\u{0c}'),
+            Some('n') => processed.push('\n'),
+            Some('r') => processed.push('\r'),
+            Some('t') => processed.push('\t'),
+            Some('v') => processed.push('\u{0b}'),
+            Some(digit @ '0'..='7') => {
+                // an octal escape sequence is made up of one to three octal digits
+                let mut code = digit.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match chars.peek().and_then(|next_ch| next_ch.to_digit(8)) {
+                        Some(next_digit) => {
+                            code = code * 8 + next_digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                processed.push(char::from_u32(code).unwrap());
+            }
+            Some(other) => processed.push(other),
+            None => processed.push('\\'),
+        }
+    }
+
+    processed
+}
+
+#[cfg(test)]
+mod assignment_parsing {
+    use super::*;
+
+    #[test]
+    fn it_parses_a_simple_assignment() {
+        assert_eq!(
+            Assignment::parse("foo=bar"),
+            Some(Assignment {
+                name: String::from("foo"),
+                value: String::from("bar"),
+            })
+        );
+    }
+
+    #[test]
+    fn it_parses_an_assignment_with_an_empty_value() {
+        assert_eq!(
+            Assignment::parse("_foo1="),
+            Some(Assignment {
+                name: String::from("_foo1"),
+                value: String::from(""),
+            })
+        );
+    }
+
+    #[test]
+    fn it_splits_on_the_first_equals_sign() {
+        assert_eq!(
+            Assignment::parse("foo=a=b"),
+            Some(Assignment {
+                name: String::from("foo"),
+                value: String::from("a=b"),
+            })
+        );
+    }
+
+    #[test]
+    fn it_does_not_parse_a_file_path() {
+        assert_eq!(Assignment::parse("./tests/data/hours1.dat"), None);
+    }
+
+    #[test]
+    fn it_does_not_parse_a_file_path_containing_an_equals_sign() {
+        assert_eq!(Assignment::parse("./foo=bar"), None);
+    }
+
+    #[test]
+    fn it_does_not_parse_a_name_with_a_leading_digit() {
+        assert_eq!(Assignment::parse("1foo=bar"), None);
+    }
+
+    #[test]
+    fn it_does_not_parse_an_empty_name() {
+        assert_eq!(Assignment::parse("=bar"), None);
+    }
+
+    #[test]
+    fn it_processes_single_character_escape_sequences() {
+        assert_eq!(
+            process_escape_sequences(r#"\"\/\\\a\b\f\n\r\t\v"#),
+            "\"/\\\u{07}\u{08}\u{0c}\n\r\t\u{0b}"
+        );
+    }
+
+    #[test]
+    fn it_processes_octal_escape_sequences() {
+        assert_eq!(process_escape_sequences(r"\101\60\0111"), "A0\t1");
+    }
+
+    #[test]
+    fn it_replaces_unknown_escape_sequences_with_the_escaped_character() {
+        assert_eq!(process_escape_sequences(r"\q"), "q");
+    }
+
+    #[test]
+    fn it_keeps_a_trailing_backslash() {
+        assert_eq!(process_escape_sequences(r"foo\"), r"foo\");
+    }
+}
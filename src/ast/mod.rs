@@ -0,0 +1,238 @@
+//! Abstract syntax tree for our implementation of awk.
+//!
+//! The [Parser](crate::parser::Parser) produces an [Ast] from the tokens of a user's program, which is then walked by
+//! the [CodeGenerator](crate::codegen::CodeGenerator) to emit bytecode. Every statement and expression carries the
+//! [Span] of source it was parsed from.
+
+use crate::builtin::Builtin;
+use crate::token::Token;
+
+/// The region of a user's program that a node of the tree was parsed from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    /// the line on which the node begins
+    pub start_line: i32,
+    /// the column at which the node begins on its first line, counted in characters starting from 1
+    pub start_column: usize,
+    /// the line on which the node ends
+    pub end_line: i32,
+    /// the byte of the program at which the node begins
+    pub start_offset: usize,
+    /// the byte of the program just past the end of the node
+    pub end_offset: usize,
+}
+
+impl Span {
+    /// Constructs a new `Span` covering a run of tokens
+    ///
+    /// # Arguments
+    /// - `first` the token the node begins with
+    /// - `last` the token the node ends with. A node that no token has been consumed for ends where it begins
+    pub fn new(first: &Token, last: &Token) -> Span {
+        Span {
+            start_line: first.line,
+            start_column: first.column,
+            end_line: last.line.max(first.line),
+            start_offset: first.span.start,
+            end_offset: last.span.end.max(first.span.start),
+        }
+    }
+
+    /// Creates a span covering this span through the end of another
+    ///
+    /// # Arguments
+    /// - `other` the span that ends the new span
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end_line: other.end_line,
+            end_offset: other.end_offset,
+            ..self
+        }
+    }
+}
+
+/// The root of the tree, a user's entire program
+#[derive(Debug, PartialEq)]
+pub struct Ast {
+    /// the pattern-actions of the program, in the order in which they were written
+    pub rules: Vec<Rule>,
+}
+
+/// A single pattern-action of a program - e.g. `$1 > 2 { print $2; }`
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    pub pattern: Pattern,
+    /// the statements of the action, or `None` if the action was omitted (implying `print $0;`)
+    pub action: Option<Vec<Stmt>>,
+    pub span: Span,
+}
+
+/// The pattern of a pattern-action, which determines when its action is run
+#[derive(Debug, PartialEq)]
+pub enum Pattern {
+    /// `BEGIN`, run before any input is read
+    Begin,
+    /// `END`, run after all input has been read
+    End,
+    /// no pattern was written, the action is run for every record
+    Always,
+    /// an expression that is evaluated for every record, running the action when it is truthy
+    Expression(Expr),
+}
+
+/// A statement, found in the action of a pattern-action
+#[derive(Debug, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+/// The different kinds of statements
+#[derive(Debug, PartialEq)]
+pub enum StmtKind {
+    /// `print expr, ...;` - printing `$0` when no expressions are given
+    Print(Vec<Expr>),
+    /// an expression whose result is discarded - e.g. `x = 1;`
+    Expression(Expr),
+    /// `if (condition) then_branch else else_branch`
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    /// `while (condition) body`
+    While { condition: Expr, body: Box<Stmt> },
+    /// `for (initializer; condition; increment) body`, where each clause is optional
+    For {
+        initializer: Option<Expr>,
+        condition: Option<Expr>,
+        increment: Option<Expr>,
+        body: Box<Stmt>,
+    },
+    /// `continue;`
+    Continue,
+    /// `break;`
+    Break,
+    /// `delete array[subscript];`, or `delete array;` when no subscript is given
+    Delete {
+        array: String,
+        subscript: Option<Expr>,
+    },
+    /// `{ statement ... }`
+    Block(Vec<Stmt>),
+}
+
+/// An expression, which produces a value
+#[derive(Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+/// The different kinds of expressions
+#[derive(Debug, PartialEq)]
+pub enum ExprKind {
+    /// a numeric literal - e.g. `42`
+    Number(f32),
+    /// a string literal - e.g. `"foo"`
+    String(String),
+    /// a reference to a variable - e.g. `foo`
+    Variable(String),
+    /// a reference to an element of an array - e.g. `foo[1]`
+    ArrayElement { array: String, subscript: Box<Expr> },
+    /// a reference to a field variable - e.g. `$1`, `$(1 + 2)`
+    FieldVariable(Box<Expr>),
+    /// an assignment to a variable - e.g. `foo = 1`, `foo += 1`
+    Assignment {
+        variable: String,
+        operator: AssignmentOperator,
+        value: Box<Expr>,
+    },
+    /// an assignment to an element of an array - e.g. `foo[1] = 2`
+    ArrayElementAssignment {
+        array: String,
+        subscript: Box<Expr>,
+        value: Box<Expr>,
+    },
+    /// a prefix operation on a single operand - e.g. `-foo`
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expr>,
+    },
+    /// an infix operation on two operands - e.g. `foo + 1`, `"foo" "bar"`
+    Binary {
+        operator: BinaryOperator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// a short circuiting logical operation - e.g. `foo && bar`
+    Logical {
+        operator: LogicalOperator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// `condition ? then_expr : else_expr`
+    Conditional {
+        condition: Box<Expr>,
+        then_expr: Box<Expr>,
+        else_expr: Box<Expr>,
+    },
+    /// an expression wrapped in parenthesis - e.g. `(1 + 2)`
+    Grouping(Box<Expr>),
+    /// a call to a function built into awk - e.g. `substr($1, 2)`
+    BuiltinCall {
+        function: Builtin,
+        arguments: Vec<Expr>,
+    },
+    /// a call to a function provided by the host application - e.g. `geoip($1)`
+    NativeCall {
+        /// the index of the function in the program's [NativeFunctions](crate::native::NativeFunctions)
+        function: usize,
+        arguments: Vec<Expr>,
+    },
+}
+
+/// Operators that may be used to assign to a variable
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AssignmentOperator {
+    Assign,         // '='
+    Add,            // '+='
+    Subtract,       // '-='
+    Multiply,       // '*='
+    Divide,         // '/='
+    Modulus,        // '%='
+    Exponentiation, // '^='
+}
+
+/// Operators that take a single operand
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOperator {
+    Plus,       // '+'
+    Minus,      // '-'
+    LogicalNot, // '!'
+}
+
+/// Operators that take two operands, both of which are always evaluated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOperator {
+    GreaterEqual,   // '>='
+    Greater,        // '>'
+    LessEqual,      // '<='
+    Less,           // '<'
+    Equal,          // '=='
+    NotEqual,       // '!='
+    Add,            // '+'
+    Subtract,       // '-'
+    Multiply,       // '*'
+    Divide,         // '/'
+    Modulus,        // '%'
+    Exponentiation, // '^'
+    Concatenate,    // string concatenation
+}
+
+/// Operators that take two operands, where the right operand may not be evaluated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogicalOperator {
+    And, // '&&'
+    Or,  // '||'
+}
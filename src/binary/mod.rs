@@ -0,0 +1,153 @@
+//! Strings that hold arbitrary bytes, so that input which is not valid UTF-8 is written back out as it was read
+//!
+//! Every byte of input that is not a part of a valid UTF-8 encoded character is held by a string as a character of its
+//! own, from a range of private use characters set aside for the purpose. Those characters are written back out as the
+//! bytes they stand for. A private use character from that range that is read from input is held as the characters
+//! that stand for each of its bytes, so that it too is written back out as it was read.
+//!
+//! Strings that come from anywhere other than input are not decoded, so a character from that range in a program's
+//! strings, or in a value assigned on the command line, is written out as the byte it stands for. Output formats write
+//! the bytes as they were read, so comma separated values and JSON written for input that is not valid UTF-8 are not
+//! valid UTF-8 either.
+
+use std::borrow::Cow;
+use std::str;
+
+/// The character that stands for the first byte, followed by the characters that stand for the other 255
+const FIRST_BYTE_CHARACTER: u32 = 0x10ff00;
+
+/// Converts bytes of input into a string, holding any bytes that are not valid UTF-8 as characters that stand for them
+///
+/// # Arguments
+/// - `bytes` the bytes to convert
+pub(crate) fn decode(bytes: Vec<u8>) -> String {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(text) if !text.chars().any(is_byte_character) => return text,
+        Ok(text) => text.into_bytes(),
+        Err(err) => err.into_bytes(),
+    };
+
+    let mut text = String::with_capacity(bytes.len());
+    let mut rest = bytes.as_slice();
+    while !rest.is_empty() {
+        let (valid, invalid) = match str::from_utf8(rest) {
+            Ok(valid) => (valid, rest.len()..rest.len()),
+            Err(err) => {
+                let valid_up_to = err.valid_up_to();
+                // an incomplete character at the end of the bytes is as invalid as any other
+                let invalid_length = err.error_len().unwrap_or(rest.len() - valid_up_to);
+                let valid = str::from_utf8(&rest[..valid_up_to]).expect("the bytes to be valid");
+                (valid, valid_up_to..valid_up_to + invalid_length)
+            }
+        };
+        for ch in valid.chars() {
+            if is_byte_character(ch) {
+                let mut encoded = [0; 4];
+                ch.encode_utf8(&mut encoded)
+                    .bytes()
+                    .for_each(|byte| text.push(byte_character(byte)));
+            } else {
+                text.push(ch);
+            }
+        }
+        rest[invalid.clone()]
+            .iter()
+            .for_each(|byte| text.push(byte_character(*byte)));
+        rest = &rest[invalid.end..];
+    }
+    text
+}
+
+/// Converts a string into the bytes to write as output, writing the characters that stand for bytes as those bytes
+///
+/// # Arguments
+/// - `text` the string to convert
+pub(crate) fn encode(text: &str) -> Cow<'_, [u8]> {
+    if !text.chars().any(is_byte_character) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+    for ch in text.chars() {
+        if is_byte_character(ch) {
+            bytes.push((ch as u32 - FIRST_BYTE_CHARACTER) as u8);
+        } else {
+            let mut encoded = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut encoded).as_bytes());
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+/// Counts the bytes that a character is written out as
+///
+/// # Arguments
+/// - `ch` the character
+///
+/// # Return value
+/// one for a character that stands for a byte, or the length of the character's UTF-8 encoding otherwise
+pub(crate) fn encoded_len(ch: char) -> usize {
+    if is_byte_character(ch) {
+        1
+    } else {
+        ch.len_utf8()
+    }
+}
+
+/// Determines whether a character stands for a byte
+fn is_byte_character(ch: char) -> bool {
+    ch as u32 >= FIRST_BYTE_CHARACTER
+}
+
+/// Retrieves the character that stands for a byte
+pub(crate) fn byte_character(byte: u8) -> char {
+    char::from_u32(FIRST_BYTE_CHARACTER + byte as u32).expect("a valid character")
+}
+
+#[cfg(test)]
+mod byte_strings {
+    use super::*;
+
+    #[test]
+    fn it_decodes_valid_utf8_as_is() {
+        assert_eq!(decode(Vec::from("naïve")), "naïve");
+        assert_eq!(encode("naïve"), "naïve".as_bytes());
+    }
+
+    #[test]
+    fn it_round_trips_invalid_utf8() {
+        let bytes = vec![b'a', 0xff, b' ', 0xc3, 0xa9, 0xe6, 0x97];
+
+        let text = decode(bytes.clone());
+
+        assert_eq!(text.chars().count(), 6);
+        assert!(text.starts_with('a'));
+        assert_eq!(encode(&text), bytes);
+    }
+
+    #[test]
+    fn it_round_trips_the_characters_that_stand_for_bytes() {
+        let bytes = Vec::from("a\u{10ff41}b");
+
+        let text = decode(bytes.clone());
+
+        assert_eq!(text.chars().count(), 6);
+        assert_eq!(encode(&text), bytes);
+    }
+
+    #[test]
+    fn it_counts_the_bytes_a_character_is_written_as() {
+        let text = decode(vec![b'a', 0xff, 0xc3, 0xa9]);
+
+        let lengths: Vec<usize> = text.chars().map(encoded_len).collect();
+
+        assert_eq!(lengths, vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn it_round_trips_every_byte() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        assert_eq!(encode(&decode(bytes.clone())), bytes);
+    }
+}
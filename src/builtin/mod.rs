@@ -0,0 +1,119 @@
+//! Functions that are built into the awk language
+
+use crate::characters::Characters;
+use crate::value::Value;
+use std::ops::RangeInclusive;
+
+/// A function built into the awk language
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum Builtin {
+    /// `index(s, t)` - the position of `t` in `s`
+    Index,
+    /// `length(s)` - the number of characters in `s`, where `length` alone is the length of `$0`
+    Length,
+    /// `substr(s, m[, n])` - the (at most `n`) characters of `s` starting at position `m`
+    Substr,
+}
+
+/// Every [Builtin], in the order of the bytes they're encoded as
+const BUILTINS: [Builtin; 3] = [Builtin::Index, Builtin::Length, Builtin::Substr];
+
+impl Builtin {
+    /// Looks up a built in function by its name
+    ///
+    /// # Arguments
+    /// - `name` the name of the function
+    ///
+    /// # Return value
+    /// the function, or `None` if no function is built in under the name
+    pub fn resolve(name: &str) -> Option<Builtin> {
+        match name {
+            "index" => Some(Builtin::Index),
+            "length" => Some(Builtin::Length),
+            "substr" => Some(Builtin::Substr),
+            _ => None,
+        }
+    }
+
+    /// Decodes a built in function from the byte it was encoded as
+    ///
+    /// # Arguments
+    /// - `byte` the byte to decode
+    ///
+    /// # Panics
+    /// If the byte does not encode a built in function, as a chunk is only ever written by the code generator
+    pub fn from_byte(byte: u8) -> Builtin {
+        *BUILTINS
+            .get(byte as usize)
+            .unwrap_or_else(|| panic!("Unknown built in function {}!", byte))
+    }
+
+    /// The number of arguments the function may be called with
+    pub fn arity(&self) -> RangeInclusive<usize> {
+        match self {
+            Builtin::Index => 2..=2,
+            Builtin::Length => 0..=1,
+            Builtin::Substr => 2..=3,
+        }
+    }
+
+    /// Calls the function
+    ///
+    /// # Arguments
+    /// - `arguments` the values of the function's arguments, whose number was checked when the program was compiled.
+    ///   `length` is always given the value to find the length of, even when it is called without one
+    /// - `characters` what a single character of a string is
+    ///
+    /// # Return value
+    /// the value returned by the function
+    pub fn call(&self, arguments: &[Value], characters: Characters) -> Value {
+        match self {
+            Builtin::Index => Value::Number(
+                characters.index(&arguments[0].str_value(), &arguments[1].str_value()) as f32,
+            ),
+            Builtin::Length => Value::Number(characters.length(&arguments[0].str_value()) as f32),
+            Builtin::Substr => Value::String(characters.substring(
+                &arguments[0].str_value(),
+                arguments[1].num_value(),
+                arguments.get(2).map(Value::num_value),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod builtins {
+    use super::*;
+    use std::slice;
+
+    #[test]
+    fn it_decodes_every_builtin_from_its_byte() {
+        for (byte, builtin) in BUILTINS.iter().enumerate() {
+            assert_eq!(*builtin as u8 as usize, byte);
+            assert_eq!(Builtin::from_byte(byte as u8), *builtin);
+        }
+    }
+
+    #[test]
+    fn it_calls_builtins_with_the_given_characters() {
+        let text = Value::String(String::from("größe"));
+
+        assert_eq!(
+            Builtin::Length.call(slice::from_ref(&text), Characters::Utf8),
+            Value::Number(5.0)
+        );
+        assert_eq!(
+            Builtin::Length.call(slice::from_ref(&text), Characters::Bytes),
+            Value::Number(7.0)
+        );
+        assert_eq!(
+            Builtin::Substr.call(&[text.clone(), Value::Number(3.0)], Characters::Utf8),
+            Value::String(String::from("öße"))
+        );
+        assert_eq!(
+            Builtin::Index.call(&[text, Value::String(String::from("e"))], Characters::Utf8),
+            Value::Number(5.0)
+        );
+    }
+}
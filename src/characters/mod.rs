@@ -0,0 +1,220 @@
+//! The definition of a character, as used by string functions and by splitting records into characters
+
+use crate::binary;
+
+use std::borrow::Cow;
+use std::iter;
+use std::ops::Range;
+
+/// What a single character of a string is
+///
+/// By default, a character is a UTF-8 encoded character, which may be more than one byte long. Treating every byte as a
+/// character instead is faster, at the cost of splitting characters that are more than one byte long.
+///
+/// r-awk does not support `printf` yet. Its `%c` conversion is left for when it does, and should print the first
+/// character of a string as defined here.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Characters {
+    /// every UTF-8 encoded character is a character
+    #[default]
+    Utf8,
+    /// every byte is a character
+    Bytes,
+}
+
+impl Characters {
+    /// Finds where each character of a string begins
+    ///
+    /// # Arguments
+    /// - `text` the string to find the characters of
+    ///
+    /// # Return value
+    /// the byte offset of each character, followed by the length of the string
+    pub fn offsets(&self, text: &str) -> Vec<usize> {
+        match self {
+            Characters::Utf8 => text
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain(iter::once(text.len()))
+                .collect(),
+            // a character that stands for a byte of input is a single byte, however long it is held as
+            Characters::Bytes => text
+                .char_indices()
+                .flat_map(|(offset, ch)| offset..offset + binary::encoded_len(ch))
+                .chain(iter::once(text.len()))
+                .collect(),
+        }
+    }
+
+    /// Retrieves part of a string
+    ///
+    /// # Arguments
+    /// - `text` the string
+    /// - `range` the byte offsets of the part, as found by [Characters::offsets]
+    ///
+    /// # Return value
+    /// the part of the string. A part that splits a UTF-8 encoded character, which may only happen when every byte is
+    /// a character, holds the bytes of the split character that it includes as characters that stand for them (see
+    /// [binary]), so that they are written out as those bytes
+    pub fn slice<'a>(&self, text: &'a str, range: Range<usize>) -> Cow<'a, str> {
+        if let Some(part) = text.get(range.clone()) {
+            return Cow::Borrowed(part);
+        }
+
+        // the part is built from the characters it overlaps, starting from the one that its first byte belongs to
+        let mut start = range.start;
+        while !text.is_char_boundary(start) {
+            start -= 1;
+        }
+        let mut part = String::with_capacity(range.len());
+        for (offset, ch) in text[start..].char_indices() {
+            let offset = start + offset;
+            if offset >= range.end {
+                break;
+            }
+            let end = offset + ch.len_utf8();
+            if range.start <= offset && end <= range.end {
+                part.push(ch);
+            } else {
+                let bytes = &text.as_bytes()[offset.max(range.start)..end.min(range.end)];
+                part.extend(bytes.iter().map(|byte| binary::byte_character(*byte)));
+            }
+        }
+        Cow::Owned(part)
+    }
+
+    /// Counts the characters in a string, as `length` does
+    ///
+    /// When every byte is a character, the bytes are those that the string is written out as, so that a byte of input
+    /// that is not valid UTF-8 is counted as the single byte it was read as
+    ///
+    /// # Arguments
+    /// - `text` the string to count the characters of
+    pub fn length(&self, text: &str) -> usize {
+        match self {
+            Characters::Utf8 => text.chars().count(),
+            Characters::Bytes => text.chars().map(binary::encoded_len).sum(),
+        }
+    }
+
+    /// Retrieves the characters of a string starting at a position, as `substr` does
+    ///
+    /// Positions start from 1, and are rounded to the nearest whole number. The part of the requested characters that
+    /// falls outside of the string is ignored.
+    ///
+    /// # Arguments
+    /// - `text` the string
+    /// - `start` the position of the first character to retrieve
+    /// - `count` the number of characters to retrieve, or `None` for every character through the end of the string
+    pub fn substring(&self, text: &str, start: f32, count: Option<f32>) -> String {
+        let offsets = self.offsets(text);
+        let length = offsets.len() - 1;
+
+        let start = start.round();
+        let end = count.map_or(f32::INFINITY, |count| start + count.round());
+        if start.is_nan() || end.is_nan() || end <= start.max(1.0) || start > length as f32 {
+            return String::new();
+        }
+        let first = start.max(1.0) as usize - 1;
+        let last = end.min(length as f32 + 1.0) as usize - 1;
+        self.slice(text, offsets[first]..offsets[last]).into_owned()
+    }
+
+    /// Finds the position of the first occurrence of a string within another, as `index` does
+    ///
+    /// # Arguments
+    /// - `text` the string to search
+    /// - `target` the string to search for
+    ///
+    /// # Return value
+    /// the position of the first character of the occurrence, starting from 1, or 0 if `target` does not occur or is
+    /// empty
+    pub fn index(&self, text: &str, target: &str) -> usize {
+        if target.is_empty() {
+            return 0;
+        }
+        match text.find(target) {
+            Some(offset) => self.length(&text[..offset]) + 1,
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod character_semantics {
+    use super::*;
+
+    #[test]
+    fn it_counts_utf8_characters_or_bytes() {
+        assert_eq!(Characters::Utf8.length("naïve 日本"), 8);
+        assert_eq!(Characters::Bytes.length("naïve 日本"), 13);
+    }
+
+    #[test]
+    fn it_counts_bytes_of_input_that_is_not_valid_utf8_as_they_were_read() {
+        let text = binary::decode(vec![b'a', 0xff, b'b', 0xc3, 0xa9]);
+
+        assert_eq!(Characters::Bytes.length(&text), 5);
+        assert_eq!(Characters::Bytes.index(&text, "b"), 3);
+        assert_eq!(Characters::Bytes.offsets(&text), vec![0, 1, 5, 6, 7, 8]);
+        assert_eq!(
+            binary::encode(&Characters::Bytes.substring(&text, 2.0, Some(3.0))),
+            vec![0xff, b'b', 0xc3]
+        );
+        assert_eq!(Characters::Utf8.length(&text), 4);
+    }
+
+    #[test]
+    fn it_finds_the_offset_of_each_character() {
+        assert_eq!(Characters::Utf8.offsets("aé"), vec![0, 1, 3]);
+        assert_eq!(Characters::Bytes.offsets("aé"), vec![0, 1, 2, 3]);
+        assert_eq!(Characters::Utf8.offsets(""), vec![0]);
+    }
+
+    #[test]
+    fn it_retrieves_a_substring_of_characters() {
+        assert_eq!(
+            Characters::Utf8.substring("日本語です", 2.0, Some(2.0)),
+            "本語"
+        );
+        assert_eq!(Characters::Utf8.substring("日本語です", 4.0, None), "です");
+        assert_eq!(Characters::Bytes.substring("日本", 4.0, Some(3.0)), "本");
+    }
+
+    #[test]
+    fn it_ignores_the_part_of_a_substring_outside_of_the_string() {
+        let characters = Characters::Utf8;
+
+        assert_eq!(characters.substring("hello", 0.0, Some(2.0)), "h");
+        assert_eq!(characters.substring("hello", -1.0, None), "hello");
+        assert_eq!(characters.substring("hello", 4.0, Some(10.0)), "lo");
+        assert_eq!(characters.substring("hello", 6.0, None), "");
+        assert_eq!(characters.substring("hello", 2.0, Some(-1.0)), "");
+        assert_eq!(characters.substring("hello", f32::NAN, None), "");
+    }
+
+    #[test]
+    fn it_rounds_substring_positions() {
+        assert_eq!(Characters::Utf8.substring("hello", 1.5, Some(1.4)), "e");
+    }
+
+    #[test]
+    fn it_keeps_the_bytes_of_a_split_character_when_every_byte_is_a_character() {
+        let part = Characters::Bytes.substring("aé日", 2.0, Some(3.0));
+
+        assert_eq!(part.chars().count(), 2);
+        assert_eq!(binary::encode(&part), vec![0xc3, 0xa9, 0xe6]);
+        assert_eq!(
+            binary::encode(&Characters::Bytes.substring("日", 2.0, Some(1.0))),
+            vec![0x97]
+        );
+    }
+
+    #[test]
+    fn it_finds_the_position_of_a_string_in_characters() {
+        assert_eq!(Characters::Utf8.index("日本語", "語"), 3);
+        assert_eq!(Characters::Bytes.index("日本語", "語"), 7);
+        assert_eq!(Characters::Utf8.index("abc", "d"), 0);
+        assert_eq!(Characters::Utf8.index("abc", ""), 0);
+    }
+}
@@ -1,14 +1,21 @@
 //! Module describing the various operations the VM can take with various debugging utilities
 
-use crate::value;
+#[cfg(debug_assertions)]
+use crate::builtin::Builtin;
 #[cfg(debug_assertions)]
 use log::debug;
+use std::collections::HashMap;
 
-// TODO: Look further into byte alignment
 /// Enum describing different operations (operation codes)
-#[derive(Clone, Debug)]
+///
+/// Each operation is encoded as a single byte in a [Chunk], followed by its operands (if any). Unless otherwise noted,
+/// operands are two bytes wide, stored in big endian order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
 pub enum OpCode {
-    OpConstant(value::Value),
+    // the operand of a constant instruction is the index of the constant in its pool
+    NumberConstant,
+    StringConstant,
     GreaterEqual,
     Greater,
     LessEqual,
@@ -25,32 +32,150 @@ pub enum OpCode {
     UnaryMinus,
     LogicalNot,
     OpReturn,
+    // the operand of a print instruction is the number of values to print, which are on the stack. no values prints
+    // the record as a whole
     OpPrint,
     Concatenate,
     LogicalAnd,
     LogicalOr,
     Pop,
-    GetGlobal(usize),
-    SetGlobal(usize),
-    DefineGlobal(usize),
-    GetFieldVariable(),
-    JumpIfFalse(usize, usize),
-    Jump(usize, usize),
-    JumpIfTrue(usize, usize),
-    Loop(usize, usize),
+    // the operand of a global variable instruction is the slot of the variable
+    GetGlobal,
+    SetGlobal,
+    DefineGlobal,
+    // the operand of an array instruction is the slot of the array
+    GetArrayElement,
+    SetArrayElement,
+    DeleteArrayElement,
+    DeleteArray,
+    GetFieldVariable,
+    // the operand of a jump instruction is the distance to jump, from the end of the instruction
+    JumpIfFalse,
+    Jump,
+    JumpIfTrue,
+    Loop,
+    // wide jump instructions have a four byte wide operand, for jumps too far for a two byte wide operand
+    JumpIfFalseWide,
+    JumpWide,
+    JumpIfTrueWide,
+    LoopWide,
+    // a comparison fused with a jump when it is false, emitted by the optimizer. its operand is the op code of the
+    // comparison followed by the two byte wide distance to jump
+    CompareJumpIfFalse,
+    // the operand of a native call instruction is the index of the native function, whose arguments are on the stack
+    CallNative,
+    // the operand of a built in call instruction is the built in function, followed by the one byte wide number of its
+    // arguments, which are on the stack
+    CallBuiltin,
+}
+
+/// Every [OpCode], in the order of the bytes they're encoded as
+const OP_CODES: [OpCode; 42] = [
+    OpCode::NumberConstant,
+    OpCode::StringConstant,
+    OpCode::GreaterEqual,
+    OpCode::Greater,
+    OpCode::LessEqual,
+    OpCode::Less,
+    OpCode::DoubleEqual,
+    OpCode::NotEqual,
+    OpCode::Add,
+    OpCode::Subtract,
+    OpCode::Multiply,
+    OpCode::Divide,
+    OpCode::Modulus,
+    OpCode::Exponentiation,
+    OpCode::UnaryPlus,
+    OpCode::UnaryMinus,
+    OpCode::LogicalNot,
+    OpCode::OpReturn,
+    OpCode::OpPrint,
+    OpCode::Concatenate,
+    OpCode::LogicalAnd,
+    OpCode::LogicalOr,
+    OpCode::Pop,
+    OpCode::GetGlobal,
+    OpCode::SetGlobal,
+    OpCode::DefineGlobal,
+    OpCode::GetArrayElement,
+    OpCode::SetArrayElement,
+    OpCode::DeleteArrayElement,
+    OpCode::DeleteArray,
+    OpCode::GetFieldVariable,
+    OpCode::JumpIfFalse,
+    OpCode::Jump,
+    OpCode::JumpIfTrue,
+    OpCode::Loop,
+    OpCode::JumpIfFalseWide,
+    OpCode::JumpWide,
+    OpCode::JumpIfTrueWide,
+    OpCode::LoopWide,
+    OpCode::CompareJumpIfFalse,
+    OpCode::CallNative,
+    OpCode::CallBuiltin,
+];
+
+impl OpCode {
+    /// Decodes an op code from the byte it was encoded as
+    ///
+    /// # Arguments
+    /// - `byte` the byte to decode
+    ///
+    /// # Panics
+    /// If the byte does not encode an op code, as a chunk is only ever written by the code generator
+    pub fn from_byte(byte: u8) -> OpCode {
+        *OP_CODES
+            .get(byte as usize)
+            .unwrap_or_else(|| panic!("Unknown op code {}!", byte))
+    }
+
+    /// The number of bytes of operands that follow the op code
+    pub fn operand_width(&self) -> usize {
+        match self {
+            OpCode::NumberConstant
+            | OpCode::StringConstant
+            | OpCode::GetGlobal
+            | OpCode::SetGlobal
+            | OpCode::DefineGlobal
+            | OpCode::GetArrayElement
+            | OpCode::SetArrayElement
+            | OpCode::DeleteArrayElement
+            | OpCode::DeleteArray
+            | OpCode::CallNative
+            | OpCode::OpPrint
+            | OpCode::CallBuiltin
+            | OpCode::JumpIfFalse
+            | OpCode::Jump
+            | OpCode::JumpIfTrue
+            | OpCode::Loop => 2,
+            OpCode::JumpIfFalseWide
+            | OpCode::JumpWide
+            | OpCode::JumpIfTrueWide
+            | OpCode::LoopWide => 4,
+            OpCode::CompareJumpIfFalse => 3,
+            _ => 0,
+        }
+    }
 }
 
-/// Struct describing an OpCode and the line in the original corpus it appears in
-#[derive(Clone)]
-pub struct CodeLine {
-    pub code: OpCode,
-    pub line: i32,
+/// A run of consecutive bytes in a chunk that were all generated from the same line of the original corpus
+struct LineRun {
+    line: i32,
+    length: usize,
 }
 
 /// Representation of a series of operations
+///
+/// Operations are densely encoded as bytes in `code`. The constants that operations refer to are stored in pools by
+/// their type, deduplicated so that each distinct constant is only stored once.
 pub struct Chunk {
-    pub code: Vec<CodeLine>, // TODO: Make this a pointer to a series of bytes. That way it's dense (cache friendly) and has constant time lookup and appending. Neat.
-    pub constants: Vec<String>,
+    pub code: Vec<u8>,
+    pub numbers: Vec<f32>,
+    pub strings: Vec<String>,
+    // the index of each number in the pool, keyed by the number's bits
+    number_indices: HashMap<u32, usize>,
+    string_indices: HashMap<String, usize>,
+    lines: Vec<LineRun>,
 }
 
 impl Chunk {
@@ -58,34 +183,146 @@ impl Chunk {
     pub fn new() -> Chunk {
         Chunk {
             code: vec![],
-            constants: vec![],
+            numbers: vec![],
+            strings: vec![],
+            number_indices: HashMap::new(),
+            string_indices: HashMap::new(),
+            lines: vec![],
+        }
+    }
+
+    /// Writes a byte and its associated line to the chunk
+    ///
+    /// # Arguments
+    /// - `byte` the byte to write
+    /// - `line` the line number associated with the byte
+    pub fn write_byte(&mut self, byte: u8, line: i32) {
+        self.code.push(byte);
+
+        match self.lines.last_mut() {
+            Some(line_run) if line_run.line == line => line_run.length += 1,
+            _ => self.lines.push(LineRun { line, length: 1 }),
         }
     }
 
     /// Writes an opcode and its associated line to the chunk
     ///
     /// # Arguments
-    /// - `code` the op code to write
+    /// - `op_code` the op code to write
     /// - `line` the line number associated with the op code
-    pub fn write_chunk(&mut self, code: OpCode, line: i32) {
-        self.code.push(CodeLine { code, line });
+    pub fn write_op_code(&mut self, op_code: OpCode, line: i32) {
+        self.write_byte(op_code as u8, line);
+    }
+
+    /// Writes a two byte wide operand and its associated line to the chunk
+    ///
+    /// # Arguments
+    /// - `operand` the operand to write
+    /// - `line` the line number associated with the operand
+    pub fn write_short(&mut self, operand: u16, line: i32) {
+        for byte in operand.to_be_bytes() {
+            self.write_byte(byte, line);
+        }
+    }
+
+    /// Overwrites a two byte wide operand that was previously written to the chunk
+    ///
+    /// # Arguments
+    /// - `offset` the offset of the first byte of the operand
+    /// - `operand` the new value of the operand
+    pub fn patch_short(&mut self, offset: usize, operand: u16) {
+        self.code[offset..offset + 2].copy_from_slice(&operand.to_be_bytes());
     }
 
-    /// Adds a constant to the chunk's constant table
+    /// Reads a two byte wide operand from the chunk
     ///
     /// # Arguments
-    /// - `constant` the value to add to the table
+    /// - `offset` the offset of the first byte of the operand
+    pub fn read_short(&self, offset: usize) -> u16 {
+        u16::from_be_bytes([self.code[offset], self.code[offset + 1]])
+    }
+
+    /// Writes a four byte wide operand and its associated line to the chunk
+    ///
+    /// # Arguments
+    /// - `operand` the operand to write
+    /// - `line` the line number associated with the operand
+    pub fn write_long(&mut self, operand: u32, line: i32) {
+        for byte in operand.to_be_bytes() {
+            self.write_byte(byte, line);
+        }
+    }
+
+    /// Overwrites a four byte wide operand that was previously written to the chunk
+    ///
+    /// # Arguments
+    /// - `offset` the offset of the first byte of the operand
+    /// - `operand` the new value of the operand
+    pub fn patch_long(&mut self, offset: usize, operand: u32) {
+        self.code[offset..offset + 4].copy_from_slice(&operand.to_be_bytes());
+    }
+
+    /// Reads a four byte wide operand from the chunk
+    ///
+    /// # Arguments
+    /// - `offset` the offset of the first byte of the operand
+    pub fn read_long(&self, offset: usize) -> u32 {
+        u32::from_be_bytes([
+            self.code[offset],
+            self.code[offset + 1],
+            self.code[offset + 2],
+            self.code[offset + 3],
+        ])
+    }
+
+    /// Adds a number to the chunk's pool of numbers
+    ///
+    /// # Arguments
+    /// - `number` the number to add to the pool
     ///
     /// # Return value
-    /// the index of the item in the constant table
-    pub fn add_constant(&mut self, constant: String) -> usize {
-        match self.constants.binary_search(&constant) {
-            Ok(index) => index,
-            Err(_) => {
-                self.constants.push(constant);
-                self.constants.len() - 1
+    /// the index of the number in the pool
+    pub fn add_number(&mut self, number: f32) -> usize {
+        if let Some(index) = self.number_indices.get(&number.to_bits()) {
+            return *index;
+        }
+
+        self.numbers.push(number);
+        self.number_indices
+            .insert(number.to_bits(), self.numbers.len() - 1);
+        self.numbers.len() - 1
+    }
+
+    /// Adds a string to the chunk's pool of strings
+    ///
+    /// # Arguments
+    /// - `string` the string to add to the pool
+    ///
+    /// # Return value
+    /// the index of the string in the pool
+    pub fn add_string(&mut self, string: String) -> usize {
+        if let Some(index) = self.string_indices.get(&string) {
+            return *index;
+        }
+
+        self.strings.push(string.clone());
+        self.string_indices.insert(string, self.strings.len() - 1);
+        self.strings.len() - 1
+    }
+
+    /// Retrieves the line of the original corpus that a byte was generated from
+    ///
+    /// # Arguments
+    /// - `offset` the offset of the byte in the chunk
+    pub fn line(&self, offset: usize) -> i32 {
+        let mut run_end = 0;
+        for line_run in &self.lines {
+            run_end += line_run.length;
+            if offset < run_end {
+                return line_run.line;
             }
         }
+        panic!("No line was recorded for offset {}", offset);
     }
 
     /// Disassembles the chunk to debugging purposes
@@ -98,8 +335,9 @@ impl Chunk {
     pub fn disassemble_chunk(&self, name: &str) {
         debug!("== {} ==", name);
 
-        for (idx, _code_line) in self.code.iter().enumerate() {
-            self.disassemble_instruction(idx);
+        let mut offset = 0;
+        while offset < self.code.len() {
+            offset = self.disassemble_instruction(offset);
         }
     }
 
@@ -109,144 +347,214 @@ impl Chunk {
     ///
     /// # Arguments
     /// - `offset` the offset in the chunk to disassemble
+    ///
+    /// # Return value
+    /// A new offset to read the next instruction from
     #[cfg(debug_assertions)]
     fn disassemble_instruction(&self, offset: usize) -> usize {
-        let mut instruction_info: String = String::from("");
-        instruction_info.push_str(format!("{:<04} ", offset).as_str());
+        debug!("{}", self.describe_instruction(offset));
+        offset + 1 + OpCode::from_byte(self.code[offset]).operand_width()
+    }
 
-        if offset > 0 {
+    /// Describes a single instruction in a chunk, in a human readable format
+    ///
+    /// The description consists of the offset of the instruction, the line it was generated from (or a `|` if it's
+    /// the same line as the preceding byte), the name of the instruction, and a description of its operand
+    ///
+    /// # Arguments
+    /// - `offset` the offset in the chunk of the instruction to describe
+    #[cfg(debug_assertions)]
+    fn describe_instruction(&self, offset: usize) -> String {
+        let mut instruction_info = format!("{:04} ", offset);
+        if offset > 0 && self.line(offset) == self.line(offset - 1) {
             instruction_info.push_str("   | ");
         } else {
-            instruction_info
-                .push_str(format!("{:4} ", &&self.code.get(offset).unwrap().line).as_str());
+            instruction_info.push_str(format!("{:4} ", self.line(offset)).as_str());
         }
 
-        let instruction: &OpCode = &self.code.get(offset).unwrap().code;
-        match instruction {
-            OpCode::OpConstant(constant) => {
-                self.constant_instruction(&instruction_info, "OpConstant", offset, constant)
-            }
-            OpCode::GreaterEqual => {
-                Chunk::simple_instruction(&instruction_info, "GreaterEqual", offset)
-            }
-            OpCode::Greater => Chunk::simple_instruction(&instruction_info, "Greater", offset),
-            OpCode::LessEqual => Chunk::simple_instruction(&instruction_info, "LessEqual", offset),
-            OpCode::Less => Chunk::simple_instruction(&instruction_info, "Less", offset),
-            OpCode::DoubleEqual => {
-                Chunk::simple_instruction(&instruction_info, "DoubleEqual", offset)
-            }
-            OpCode::NotEqual => Chunk::simple_instruction(&instruction_info, "NotEqual", offset),
-            OpCode::Add => Chunk::simple_instruction(&instruction_info, "Add", offset),
-            OpCode::Subtract => Chunk::simple_instruction(&instruction_info, "Subtract", offset),
-            OpCode::Multiply => Chunk::simple_instruction(&instruction_info, "Multiply", offset),
-            OpCode::Divide => Chunk::simple_instruction(&instruction_info, "Divide", offset),
-            OpCode::Modulus => Chunk::simple_instruction(&instruction_info, "Modulus", offset),
-            OpCode::Exponentiation => {
-                Chunk::simple_instruction(&instruction_info, "Exponentiation", offset)
-            }
-            OpCode::UnaryPlus => Chunk::simple_instruction(&instruction_info, "UnaryPlus", offset),
-            OpCode::UnaryMinus => {
-                Chunk::simple_instruction(&instruction_info, "UnaryMinus", offset)
-            }
-            OpCode::LogicalNot => {
-                Chunk::simple_instruction(&instruction_info, "LogicalNot", offset)
-            }
-            OpCode::OpReturn => Chunk::simple_instruction(&instruction_info, "OpReturn", offset),
-            OpCode::OpPrint => Chunk::simple_instruction(&instruction_info, "OpPrint", offset),
-            OpCode::Concatenate => {
-                Chunk::simple_instruction(&instruction_info, "Concatenate", offset)
-            }
-            OpCode::LogicalAnd => {
-                Chunk::simple_instruction(&instruction_info, "LogicalAnd", offset)
-            }
-            OpCode::LogicalOr => Chunk::simple_instruction(&instruction_info, "LogicalOr", offset),
-            OpCode::Pop => Chunk::simple_instruction(&instruction_info, "Pop", offset),
-            OpCode::GetGlobal(_chunk_index) => {
-                Chunk::simple_instruction(&instruction_info, "GetGlobal", offset)
+        let instruction = OpCode::from_byte(self.code[offset]);
+        instruction_info.push_str(format!("{:?}", instruction).as_str());
+        if instruction.operand_width() == 0 {
+            return instruction_info;
+        }
+
+        let operand = match instruction {
+            OpCode::CompareJumpIfFalse => self.read_short(offset + 2) as usize,
+            _ if instruction.operand_width() == 4 => self.read_long(offset + 1) as usize,
+            _ => self.read_short(offset + 1) as usize,
+        };
+        let next_offset = offset + 1 + instruction.operand_width();
+        let operand_info = match instruction {
+            OpCode::NumberConstant => format!("{} '{}'", operand, self.numbers[operand]),
+            OpCode::StringConstant => format!("{} '{}'", operand, self.strings[operand]),
+            OpCode::JumpIfFalse
+            | OpCode::Jump
+            | OpCode::JumpIfTrue
+            | OpCode::JumpIfFalseWide
+            | OpCode::JumpWide
+            | OpCode::JumpIfTrueWide => {
+                format!("{} -> {}", operand, next_offset + operand)
             }
-            OpCode::SetGlobal(_chunk_index) => {
-                Chunk::simple_instruction(&instruction_info, "SetGlobal", offset)
+            OpCode::Loop | OpCode::LoopWide => {
+                format!("{} -> {}", operand, next_offset - operand)
             }
-            OpCode::DefineGlobal(_chunk_index) => {
-                Chunk::simple_instruction(&instruction_info, "DefineGlobal", offset)
+            OpCode::CompareJumpIfFalse => {
+                let comparison = OpCode::from_byte(self.code[offset + 1]);
+                format!("{:?} {} -> {}", comparison, operand, next_offset + operand)
             }
-            _ => {
-                debug!("Unknown opcode {:#?}!", instruction);
-                offset + 1
+            OpCode::CallBuiltin => {
+                let function = Builtin::from_byte(self.code[offset + 1]);
+                format!("{:?} {}", function, self.code[offset + 2])
             }
+            // the remaining instructions all operate on a slot, the index of a native function, or a number of values
+            _ => format!("{}", operand),
+        };
+        format!("{:<32} {}", instruction_info, operand_info)
+    }
+}
+
+#[cfg(test)]
+mod byte_encoding {
+    use super::*;
+
+    #[test]
+    fn it_decodes_every_op_code_from_its_byte() {
+        for (byte, op_code) in OP_CODES.iter().enumerate() {
+            assert_eq!(*op_code as u8 as usize, byte);
+            assert_eq!(OpCode::from_byte(byte as u8), *op_code);
         }
     }
 
-    /// Disassembles a simple instruction
-    ///
-    /// A simple instruction is one that has no additional associated data, such as a constant value
-    ///
-    /// # Arguments
-    /// - `prelude` information regarding the chunk to print to the console
-    /// - `name` the name of the instruction
-    /// - `offset` the current offset that was read from
-    ///
-    /// # Return value
-    /// A new offset to read the next instruction from
-    #[cfg(debug_assertions)]
-    fn simple_instruction(prelude: &str, name: &str, offset: usize) -> usize {
-        debug!("{} {}", prelude, name);
-        offset + 1
+    #[test]
+    fn it_reads_back_a_short_operand() {
+        let mut chunk = Chunk::new();
+        chunk.write_op_code(OpCode::Jump, 1);
+        chunk.write_short(0x1234, 1);
+
+        assert_eq!(chunk.code, vec![OpCode::Jump as u8, 0x12, 0x34]);
+        assert_eq!(chunk.read_short(1), 0x1234);
+    }
+
+    #[test]
+    fn it_patches_a_short_operand() {
+        let mut chunk = Chunk::new();
+        chunk.write_op_code(OpCode::Jump, 1);
+        chunk.write_short(u16::MAX, 1);
+        chunk.patch_short(1, 300);
+
+        assert_eq!(chunk.read_short(1), 300);
+    }
+
+    #[test]
+    fn it_reads_back_a_long_operand() {
+        let mut chunk = Chunk::new();
+        chunk.write_op_code(OpCode::JumpWide, 1);
+        chunk.write_long(0x12345678, 1);
+
+        assert_eq!(
+            chunk.code,
+            vec![OpCode::JumpWide as u8, 0x12, 0x34, 0x56, 0x78]
+        );
+        assert_eq!(chunk.read_long(1), 0x12345678);
+
+        chunk.patch_long(1, 70000);
+        assert_eq!(chunk.read_long(1), 70000);
+    }
+
+    #[test]
+    fn it_looks_up_the_line_of_every_byte() {
+        let mut chunk = Chunk::new();
+        chunk.write_op_code(OpCode::Pop, 1);
+        chunk.write_op_code(OpCode::Pop, 1);
+        chunk.write_op_code(OpCode::Jump, 3);
+        chunk.write_short(0, 3);
+        chunk.write_op_code(OpCode::OpReturn, 4);
+
+        let lines: Vec<i32> = (0..chunk.code.len())
+            .map(|offset| chunk.line(offset))
+            .collect();
+        assert_eq!(lines, vec![1, 1, 3, 3, 3, 4]);
+        // consecutive bytes on the same line share a single run
+        assert_eq!(chunk.lines.len(), 3);
     }
 
-    /// Disassembles a constant instruction
-    ///
-    /// A constant instruction is one that has an associated value, such as a constant
-    ///
-    /// # Arguments
-    /// - `prelude` information regarding the chunk to print to the console
-    /// - `name` the name of the instruction
-    /// - `offset` the current offset that was read from
-    /// - `value` the constant value associated with the instruction
-    ///
-    /// # Return value
-    /// A new offset to read the next instruction from
     #[cfg(debug_assertions)]
-    fn constant_instruction(
-        &self,
-        prelude: &str,
-        name: &str,
-        offset: usize,
-        value: &value::Value,
-    ) -> usize {
-        debug!("{} {} {}", prelude, name, value);
-        offset + 1
+    #[test]
+    fn it_describes_instructions_with_operands() {
+        let mut chunk = Chunk::new();
+        let index = chunk.add_string(String::from("foo"));
+        chunk.write_op_code(OpCode::StringConstant, 1);
+        chunk.write_short(index as u16, 1);
+        chunk.write_op_code(OpCode::Jump, 1);
+        chunk.write_short(1, 1);
+        chunk.write_op_code(OpCode::Pop, 2);
+
+        assert_eq!(
+            chunk.describe_instruction(0),
+            "0000    1 StringConstant         0 'foo'"
+        );
+        assert_eq!(
+            chunk.describe_instruction(3),
+            "0003    | Jump                   1 -> 7"
+        );
+        assert_eq!(chunk.describe_instruction(6), "0006    2 Pop");
+
+        chunk.write_op_code(OpCode::LoopWide, 2);
+        chunk.write_long(12, 2);
+        assert_eq!(
+            chunk.describe_instruction(7),
+            "0007    | LoopWide               12 -> 0"
+        );
+
+        chunk.write_op_code(OpCode::CompareJumpIfFalse, 3);
+        chunk.write_byte(OpCode::Less as u8, 3);
+        chunk.write_short(4, 3);
+        assert_eq!(
+            chunk.describe_instruction(12),
+            "0012    3 CompareJumpIfFalse     Less 4 -> 20"
+        );
     }
 }
 
 #[cfg(test)]
-mod chunk {
+mod constant_pool {
     use super::*;
 
     #[test]
     fn it_returns_the_index_of_a_constant_that_exists() {
         let mut chunk = Chunk::new();
-        chunk.add_constant(String::from("foo"));
-        chunk.add_constant(String::from("bar"));
-        chunk.add_constant(String::from("baz"));
-        let bar_index = chunk.add_constant(String::from("bar"));
+        chunk.add_string(String::from("foo"));
+        chunk.add_string(String::from("bar"));
+        chunk.add_string(String::from("baz"));
+        let bar_index = chunk.add_string(String::from("bar"));
 
         assert_eq!(bar_index, 1);
-        assert_ne!(bar_index, chunk.constants.len());
+        assert_eq!(chunk.strings.len(), 3);
     }
 
     #[test]
-    fn it_places_a_never_seen_before_chunk_at_the_end_of_table() {
+    fn it_places_a_never_seen_before_constant_at_the_end_of_its_pool() {
         let mut chunk = Chunk::new();
 
-        chunk.add_constant(String::from("foo"));
-        let foo_index = chunk.add_constant(String::from("foo"));
-        assert_eq!(foo_index, 0);
-        assert_eq!(foo_index, chunk.constants.len() - 1);
+        chunk.add_number(3.0);
+        let three_index = chunk.add_number(3.0);
+        assert_eq!(three_index, 0);
+        assert_eq!(three_index, chunk.numbers.len() - 1);
 
-        chunk.add_constant(String::from("bar"));
-        let bar_index = chunk.add_constant(String::from("bar"));
-        assert_eq!(bar_index, 1);
-        assert_eq!(bar_index, chunk.constants.len() - 1);
+        // numbers are deliberately added out of order
+        chunk.add_number(1.0);
+        let one_index = chunk.add_number(1.0);
+        assert_eq!(one_index, 1);
+        assert_eq!(one_index, chunk.numbers.len() - 1);
+    }
+
+    #[test]
+    fn it_keeps_a_separate_pool_for_each_type() {
+        let mut chunk = Chunk::new();
+
+        assert_eq!(chunk.add_number(1.0), 0);
+        assert_eq!(chunk.add_string(String::from("1")), 0);
+        assert_eq!(chunk.numbers, vec![1.0]);
+        assert_eq!(chunk.strings, vec![String::from("1")]);
     }
 }
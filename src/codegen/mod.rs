@@ -0,0 +1,910 @@
+//! Code generator for our implementation of awk.
+//!
+//! Walks the [Ast] produced by the [Parser](crate::parser::Parser), emitting [OpCode]s for each
+//! pattern-action into the [Chunk] of the [Section] of the program it belongs to.
+
+use crate::ast::{
+    AssignmentOperator, Ast, BinaryOperator, Expr, ExprKind, LogicalOperator, Pattern, Rule, Stmt,
+    StmtKind, UnaryOperator,
+};
+use crate::builtin::Builtin;
+use crate::chunk::{Chunk, OpCode};
+use crate::error::{Error, ErrorKind};
+use crate::program::{Program, Section};
+use std::collections::HashSet;
+
+/// A forward jump whose distance is not yet known, to be patched once its target has been generated
+struct PendingJump {
+    /// the order in which the jump was emitted, which is the same each time a program is generated
+    id: usize,
+    /// the location of the operand of the jump instruction
+    offset: usize,
+    /// whether the jump was emitted with a four byte wide operand
+    wide: bool,
+}
+
+/// Bookkeeping for a loop whose body is currently being generated
+struct LoopContext {
+    /// the location of the instruction that a `continue` statement returns to
+    continue_target: usize,
+    /// the locations of the jumps emitted for `break` statements, to be patched once the loop ends
+    break_jumps: Vec<PendingJump>,
+}
+
+/// A code generator, emitting the bytecode for a tree to a [Program]
+///
+/// The `CodeGenerator` holds some stateful information in addition to the program it emits to:
+/// - `compiling_section` the section of the program that code is currently being emitted to
+/// - `loops` the loops surrounding the statement currently being generated, innermost last
+/// - `wide_jumps` the forward jumps that need a four byte wide operand, found by previous attempts at generating code
+/// - `overflowed_jumps` the forward jumps of the current attempt that did not fit in a two byte wide operand
+/// - `errors` each error reported while generating code
+pub struct CodeGenerator<'a> {
+    compiling_program: &'a mut Program,
+    compiling_section: Section,
+    loops: Vec<LoopContext>,
+    jump_count: usize,
+    wide_jumps: HashSet<usize>,
+    overflowed_jumps: Vec<usize>,
+    errors: Vec<Error>,
+}
+
+impl<'a> CodeGenerator<'a> {
+    /// Constructs a new `CodeGenerator`
+    ///
+    /// # Arguments
+    /// - `compiling_program` the program to emit code to
+    pub fn new(compiling_program: &'a mut Program) -> CodeGenerator<'a> {
+        CodeGenerator {
+            compiling_program,
+            compiling_section: Section::Main,
+            loops: vec![],
+            jump_count: 0,
+            wide_jumps: HashSet::new(),
+            overflowed_jumps: vec![],
+            errors: vec![],
+        }
+    }
+
+    /// Entrypoint for generating code
+    ///
+    /// # Arguments
+    /// - `ast` the tree of the program to generate code for
+    ///
+    /// # Return value
+    /// - `Ok` if code was generated for the entire program
+    /// - each error that was reported if the program exceeds a limit of the bytecode, such as the number of constants
+    ///   it may contain
+    pub fn generate(&mut self, ast: &Ast) -> Result<(), Vec<Error>> {
+        // the distance of a forward jump isn't known when it is emitted. jumps are emitted with a two byte wide operand
+        // where possible, and the program is generated again with wider operands for any jumps that did not fit
+        loop {
+            *self.compiling_program = Program::new();
+            self.jump_count = 0;
+
+            for rule in &ast.rules {
+                self.rule(rule);
+            }
+
+            if !self.errors.is_empty() || self.overflowed_jumps.is_empty() {
+                break;
+            }
+            self.wide_jumps.extend(self.overflowed_jumps.drain(..));
+        }
+
+        self.end_compiler();
+
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        Ok(())
+    }
+
+    /// Generate the code for a single pattern-action
+    ///
+    /// # Arguments
+    /// - `rule` the pattern-action to generate code for
+    fn rule(&mut self, rule: &Rule) {
+        let line = rule.span.start_line;
+
+        // BEGIN and END actions are emitted to their own sections, the pattern of those actions is always true
+        self.compiling_section = match rule.pattern {
+            Pattern::Begin => Section::Begin,
+            Pattern::End => Section::End,
+            Pattern::Always | Pattern::Expression(_) => Section::Main,
+        };
+        match &rule.pattern {
+            Pattern::Expression(pattern) => self.expression(pattern),
+            _ => self.emit_true(line),
+        }
+
+        // emit a jump instruction as a placeholder to skip over the 'action' associated with the action in the
+        // event the pattern condition is false (if one exists). we'll backpatch it soon with the correct offset.
+        let pattern_false_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+
+        // like an if statement, pop the pattern's result off the stack before running the action. this keeps the
+        // shape of a rule the same as that of an if statement, so that the optimizer can remove a pattern that is
+        // always true in the same way
+        self.emit_byte(OpCode::Pop, line);
+
+        match &rule.action {
+            Some(statements) => {
+                for statement in statements {
+                    self.statement(statement);
+                }
+            }
+            None => {
+                // no action - the action implicitly becomes 'print $0'
+                self.print(&[], line);
+            }
+        }
+
+        // jump over popping the pattern's result when it was falsy
+        let end_jump = self.emit_jump(OpCode::Jump, line);
+
+        // we've passed through the action successfully, backpatch the jump that was emitted for the block
+        self.patch_jump(pattern_false_jump);
+        self.emit_byte(OpCode::Pop, line);
+
+        self.patch_jump(end_jump);
+    }
+
+    /// Emits a number one ('1'), which is always truthy
+    ///
+    /// # Arguments
+    /// - `line` the line to associate the constant with
+    fn emit_true(&mut self, line: i32) {
+        self.emit_number(1.0, line);
+    }
+
+    /// Generate the code for a statement
+    ///
+    /// # Arguments
+    /// - `statement` the statement to generate code for
+    fn statement(&mut self, statement: &Stmt) {
+        let line = statement.span.start_line;
+        match &statement.kind {
+            StmtKind::Print(expressions) => self.print(expressions, line),
+            StmtKind::Expression(Expr {
+                kind:
+                    ExprKind::Assignment {
+                        variable,
+                        operator,
+                        value,
+                    },
+                ..
+            }) => {
+                // the result of an assignment statement is never used, define the variable without leaving its value
+                // on the stack
+                let global_slot = self.assignment_value(variable, *operator, value, line);
+                self.emit_slot_instruction(OpCode::DefineGlobal, global_slot, line);
+            }
+            StmtKind::Expression(expression) => {
+                self.expression(expression);
+                // discard the result
+                self.emit_byte(OpCode::Pop, line);
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => self.if_statement(condition, then_branch, else_branch.as_deref(), line),
+            StmtKind::While { condition, body } => self.while_statement(condition, body, line),
+            StmtKind::For {
+                initializer,
+                condition,
+                increment,
+                body,
+            } => self.for_statement(
+                initializer.as_ref(),
+                condition.as_ref(),
+                increment.as_ref(),
+                body,
+                line,
+            ),
+            StmtKind::Continue => {
+                let continue_target = self
+                    .loops
+                    .last()
+                    .expect("'continue' was used outside of a loop")
+                    .continue_target;
+                self.emit_loop(continue_target, line);
+            }
+            StmtKind::Break => {
+                let break_jump = self.emit_jump(OpCode::Jump, line);
+                self.loops
+                    .last_mut()
+                    .expect("'break' was used outside of a loop")
+                    .break_jumps
+                    .push(break_jump);
+            }
+            StmtKind::Delete { array, subscript } => {
+                let array_slot = self.array_slot(array);
+                match subscript {
+                    Some(subscript) => {
+                        self.expression(subscript);
+                        self.emit_slot_instruction(OpCode::DeleteArrayElement, array_slot, line);
+                    }
+                    None => self.emit_slot_instruction(OpCode::DeleteArray, array_slot, line),
+                }
+            }
+            StmtKind::Block(statements) => {
+                for statement in statements {
+                    self.statement(statement);
+                }
+            }
+        }
+    }
+
+    /// Generate the code for a print statement
+    ///
+    /// # Arguments
+    /// - `expressions` the expressions to print, where none means the record as a whole (`$0`) is printed
+    /// - `line` the line of the statement
+    fn print(&mut self, expressions: &[Expr], line: i32) {
+        // the values are pushed in order, so the last value is on the top of the stack. they are kept apart rather than
+        // concatenated, so that the VM may write them in whichever output format is in use
+        for expression in expressions {
+            self.expression(expression);
+        }
+        self.emit_operand_instruction(
+            OpCode::OpPrint,
+            expressions.len(),
+            "Too many values in one print statement.",
+            line,
+        );
+    }
+
+    /// Generate the code for an if statement
+    ///
+    /// # Arguments
+    /// - `condition` the condition of the statement
+    /// - `then_branch` the statement to run when the condition is truthy
+    /// - `else_branch` the statement to run when the condition is falsy, if any
+    /// - `line` the line of the statement
+    fn if_statement(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+        line: i32,
+    ) {
+        self.expression(condition);
+
+        // emit a jump instruction as a placeholder to skip over the 'then' in the event the if condition is false.
+        // we'll backpatch it soon with the correct offset.
+        let if_was_false_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+
+        // when the if statement condition is truthy, pop the result off the stack
+        self.emit_byte(OpCode::Pop, line);
+        self.statement(then_branch);
+
+        // in the event the if statement's condition is truthy, we need to jump over the else block rather than fall
+        // through. put a placeholder in that will be able to skip the else keyword & the statement(s) that follow it.
+        // there is an implicit 'else' here, even if there isn't one in the author's code.
+        let else_jump = self.emit_jump(OpCode::Jump, line);
+
+        // we've passed through the then statement(s) backpatch the jump that was emitted for the if block.
+        self.patch_jump(if_was_false_jump);
+
+        // when the if statement condition is falsy, pop the result off the stack.
+        self.emit_byte(OpCode::Pop, line);
+
+        if let Some(else_branch) = else_branch {
+            self.statement(else_branch);
+        }
+
+        // we've passed through the else block statement(s), backpatch the jump that was emitted for the else block
+        self.patch_jump(else_jump);
+    }
+
+    /// Generate the code for a while statement
+    ///
+    /// # Arguments
+    /// - `condition` the condition of the loop
+    /// - `body` the body of the loop
+    /// - `line` the line of the statement
+    fn while_statement(&mut self, condition: &Expr, body: &Stmt, line: i32) {
+        // mark the location where the loop begins, which is also where a `continue` statement returns to
+        let while_start = self.current_chunk().code.len();
+
+        self.expression(condition);
+
+        // emit a jump that will jump over the body of the loop should it's condition be false
+        let while_condition_false = self.emit_jump(OpCode::JumpIfFalse, line);
+
+        // pop the result of the while condition off the stack if the condition was truthy
+        self.emit_byte(OpCode::Pop, line);
+
+        self.loop_body(body, while_start);
+        // now that the body of the while loop has been generated, emit a jump back to the start of the loop
+        self.emit_loop(while_start, line);
+
+        // backpatch the jump for a falsy condition and pop the result off the stack
+        self.patch_jump(while_condition_false);
+        self.emit_byte(OpCode::Pop, line);
+
+        self.end_loop();
+    }
+
+    /// Generate the code for a for statement
+    ///
+    /// # Arguments
+    /// - `initializer` the expression run once before the loop begins, if any
+    /// - `condition` the condition of the loop, if any
+    /// - `increment` the expression run at the end of every iteration of the loop, if any
+    /// - `body` the body of the loop
+    /// - `line` the line of the statement
+    fn for_statement(
+        &mut self,
+        initializer: Option<&Expr>,
+        condition: Option<&Expr>,
+        increment: Option<&Expr>,
+        body: &Stmt,
+        line: i32,
+    ) {
+        if let Some(initializer) = initializer {
+            self.expression(initializer);
+            self.emit_byte(OpCode::Pop, line);
+        }
+
+        let mut loop_start = self.current_chunk().code.len();
+        let mut for_loop_exit_jump: Option<PendingJump> = None;
+
+        if let Some(condition) = condition {
+            self.expression(condition);
+
+            // if the condition is false, we need to jump out of the loop
+            for_loop_exit_jump = Some(self.emit_jump(OpCode::JumpIfFalse, line));
+            // if the condition is true, we need to pop the result off of the stack
+            self.emit_byte(OpCode::Pop, line);
+        }
+
+        if let Some(increment) = increment {
+            // unconditionally jump over the incrementer, to the body of the loop
+            let body_jump = self.emit_jump(OpCode::Jump, line);
+            let increment_clause_start = self.current_chunk().code.len();
+
+            // generate the incrementer, then throw away the result since it's often assignment
+            self.expression(increment);
+            self.emit_byte(OpCode::Pop, line);
+
+            // this happens right after an increment, since an increment happens at the end of a loop (a little
+            // weird, I know)
+            // 1. take us back to the top of the for loop, right before the condition (which may not exist). this occurs
+            // _after_ the increment
+            self.emit_loop(loop_start, line);
+            // 2. update the loop start to point to the increment clause
+            loop_start = increment_clause_start;
+            // 3. back patch the jump for the entire body
+            self.patch_jump(body_jump);
+        }
+
+        self.loop_body(body, loop_start);
+
+        self.emit_loop(loop_start, line);
+
+        // patch the jump if the condition is false
+        if let Some(value) = for_loop_exit_jump {
+            self.patch_jump(value);
+            // if the condition is false, we still have that value on the stack
+            self.emit_byte(OpCode::Pop, line);
+        }
+
+        self.end_loop();
+    }
+
+    /// Generate the code for the body of a loop, in which `continue` and `break` statements may be used
+    ///
+    /// # Arguments
+    /// - `body` the body of the loop
+    /// - `continue_target` the location of the instruction that a `continue` statement returns to
+    fn loop_body(&mut self, body: &Stmt, continue_target: usize) {
+        self.loops.push(LoopContext {
+            continue_target,
+            break_jumps: vec![],
+        });
+        self.statement(body);
+    }
+
+    /// Finishes generating the code for the innermost loop, patching any `break` statements found in its body to
+    /// jump to the current location
+    fn end_loop(&mut self) {
+        let loop_context = self.loops.pop().expect("No loop is being generated");
+        for break_jump in loop_context.break_jumps {
+            self.patch_jump(break_jump);
+        }
+    }
+
+    /// Generate the code for an expression, leaving its result on the top of the stack
+    ///
+    /// # Arguments
+    /// - `expression` the expression to generate code for
+    fn expression(&mut self, expression: &Expr) {
+        let line = expression.span.start_line;
+        match &expression.kind {
+            ExprKind::Number(number) => self.emit_number(*number, line),
+            ExprKind::String(string) => self.emit_string(string.clone(), line),
+            ExprKind::Variable(variable) => {
+                let global_slot = self.global_slot(variable);
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+            }
+            ExprKind::ArrayElement { array, subscript } => {
+                let array_slot = self.array_slot(array);
+                self.expression(subscript);
+                self.emit_slot_instruction(OpCode::GetArrayElement, array_slot, line);
+            }
+            ExprKind::FieldVariable(index) => {
+                // because the value immediately following the '$' may be the result of an expression,
+                // e.g. $(2+3), we must generate the index first and push it onto the stack
+                self.expression(index);
+                self.emit_byte(OpCode::GetFieldVariable, line);
+            }
+            ExprKind::Assignment {
+                variable,
+                operator,
+                value,
+            } => {
+                let global_slot = self.assignment_value(variable, *operator, value, line);
+                self.emit_slot_instruction(OpCode::SetGlobal, global_slot, line);
+            }
+            ExprKind::ArrayElementAssignment {
+                array,
+                subscript,
+                value,
+            } => {
+                let array_slot = self.array_slot(array);
+                self.expression(subscript);
+                self.expression(value);
+                self.emit_slot_instruction(OpCode::SetArrayElement, array_slot, line);
+            }
+            ExprKind::Unary { operator, operand } => {
+                // generate the operand first, then we'll apply the operator to it
+                self.expression(operand);
+                let op_code = match operator {
+                    UnaryOperator::Plus => OpCode::UnaryPlus,
+                    UnaryOperator::Minus => OpCode::UnaryMinus,
+                    UnaryOperator::LogicalNot => OpCode::LogicalNot,
+                };
+                self.emit_byte(op_code, line);
+            }
+            ExprKind::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                self.expression(left);
+                self.expression(right);
+                self.emit_byte(binary_op_code(*operator), line);
+            }
+            ExprKind::Logical {
+                operator,
+                left,
+                right,
+            } => self.logical(*operator, left, right, line),
+            ExprKind::Conditional {
+                condition,
+                then_expr,
+                else_expr,
+            } => self.conditional_expression(condition, then_expr, else_expr, line),
+            // a grouping only allows a lower precedence expression to be used where a higher one is expected, it
+            // does not emit any code of its own
+            ExprKind::Grouping(expression) => self.expression(expression),
+            ExprKind::BuiltinCall {
+                function,
+                arguments,
+            } => self.builtin_call(*function, arguments, line),
+            ExprKind::NativeCall {
+                function,
+                arguments,
+            } => {
+                // arguments are pushed in order, so the last argument is on the top of the stack
+                for argument in arguments {
+                    self.expression(argument);
+                }
+                self.emit_operand_instruction(
+                    OpCode::CallNative,
+                    *function,
+                    "Too many native functions in one program.",
+                    line,
+                );
+            }
+        }
+    }
+
+    /// Generate the code for a call to a built in function
+    ///
+    /// # Arguments
+    /// - `function` the function to call
+    /// - `arguments` the arguments of the call, whose number was checked when the program was parsed
+    /// - `line` the line of the call
+    fn builtin_call(&mut self, function: Builtin, arguments: &[Expr], line: i32) {
+        // arguments are pushed in order, so the last argument is on the top of the stack
+        for argument in arguments {
+            self.expression(argument);
+        }
+        let mut count = arguments.len();
+        if function == Builtin::Length && arguments.is_empty() {
+            // `length` alone is the length of the record as a whole
+            self.emit_number(0.0, line);
+            self.emit_byte(OpCode::GetFieldVariable, line);
+            count = 1;
+        }
+        self.emit_byte(OpCode::CallBuiltin, line);
+        self.current_chunk().write_byte(function as u8, line);
+        self.current_chunk().write_byte(count as u8, line);
+    }
+
+    /// Generate the code to compute the value being assigned to a variable, leaving it on the top of the stack
+    ///
+    /// # Arguments
+    /// - `variable` the name of the variable being assigned to
+    /// - `operator` the assignment operator
+    /// - `value` the right hand side of the assignment
+    /// - `line` the line of the assignment
+    ///
+    /// # Return value
+    /// the slot of the variable being assigned to
+    fn assignment_value(
+        &mut self,
+        variable: &str,
+        operator: AssignmentOperator,
+        value: &Expr,
+        line: i32,
+    ) -> usize {
+        let global_slot = self.global_slot(variable);
+
+        match operator {
+            AssignmentOperator::Assign => self.expression(value),
+            AssignmentOperator::Add => {
+                self.expression(value);
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+                self.emit_byte(OpCode::Add, line);
+            }
+            AssignmentOperator::Subtract => {
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+                self.expression(value);
+                self.emit_byte(OpCode::Subtract, line);
+            }
+            AssignmentOperator::Multiply => {
+                self.expression(value);
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+                self.emit_byte(OpCode::Multiply, line);
+            }
+            AssignmentOperator::Divide => {
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+                self.expression(value);
+                self.emit_byte(OpCode::Divide, line);
+            }
+            AssignmentOperator::Modulus => {
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+                self.expression(value);
+                self.emit_byte(OpCode::Modulus, line);
+            }
+            AssignmentOperator::Exponentiation => {
+                self.expression(value);
+                self.emit_slot_instruction(OpCode::GetGlobal, global_slot, line);
+                self.emit_byte(OpCode::Exponentiation, line);
+            }
+        }
+
+        global_slot
+    }
+
+    /// Generate the code for a condition expression
+    ///
+    /// A condition expression takes the form `expr1 ? expr2 : expr3` where:
+    /// - if `expr1` is truthy, `expr2` is evaluated and `expr3` is not evaluated
+    /// - if `expr1` is falsy, `expr2` is not evaluated and `expr3` is evaluated
+    fn conditional_expression(
+        &mut self,
+        condition: &Expr,
+        then_expr: &Expr,
+        else_expr: &Expr,
+        line: i32,
+    ) {
+        self.expression(condition);
+
+        // expr1 has been evaluated and is at the top of the stack.
+        // we need to jump over expr2 if that value on the stack if falsy
+        let over_expr_2_jump = self.emit_jump(OpCode::JumpIfFalse, line);
+
+        // if the value from expr1 is truthy, pop it off the stack now. we cannot wait to pop this value,
+        // as that may lead to popping the result from expr2 being popped off the stack
+        self.emit_byte(OpCode::Pop, line);
+
+        self.expression(then_expr);
+
+        // if expr1 (which is on the stack) is truthy, we'll run through expr2. now we need to emit a jump over expr3
+        // to ensure we don't evaluate that as well
+        let over_expr_3_jump = self.emit_jump(OpCode::Jump, line);
+
+        // backpatch in the event expr1 was falsy
+        self.patch_jump(over_expr_2_jump);
+
+        // if the value from expr1 is falsy, pop it off the stack now. we cannot wait to pop this value,
+        // as that may lead to popping the result from expr3 being popped off the stack
+        self.emit_byte(OpCode::Pop, line);
+
+        self.expression(else_expr);
+
+        // now that expr3 has been generated, we can backpatch the jump we'd have taken if expr1 was truthy
+        self.patch_jump(over_expr_3_jump);
+    }
+
+    /// Generate the code for a logical and (&&) or logical or (||), short circuiting the right hand side.
+    ///
+    /// Once the left hand side of the expression has been evaluated, it is on the top of the stack. If that value is
+    /// falsy for '&&' (or truthy for '||'), the right hand side is skipped.
+    fn logical(&mut self, operator: LogicalOperator, left: &Expr, right: &Expr, line: i32) {
+        self.expression(left);
+
+        // create a placeholder to jump to the end of the expression should it short circuit
+        let (short_circuit_jump, op_code) = match operator {
+            LogicalOperator::And => (OpCode::JumpIfFalse, OpCode::LogicalAnd),
+            LogicalOperator::Or => (OpCode::JumpIfTrue, OpCode::LogicalOr),
+        };
+        let end_jump = self.emit_jump(short_circuit_jump, line);
+
+        self.expression(right);
+
+        // the LHS and RHS are now on the stack, we'll need to evaluate them in the VM, since awk's return value for
+        // logical operators is 1 or 0, not the return value of the last sub expression
+        self.emit_byte(op_code, line);
+
+        // we've now passed the expression, backpatch the jump over the right hand side
+        self.patch_jump(end_jump);
+    }
+
+    /// Resolves a variable's name to the slot its value is stored in
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    ///
+    /// # Return value
+    /// the slot of the variable, for lookup without its name at runtime
+    fn global_slot(&mut self, name: &str) -> usize {
+        self.compiling_program.resolve_global(name)
+    }
+
+    /// Resolves an array's name to the slot its elements are stored in
+    ///
+    /// # Arguments
+    /// - `name` the name of the array
+    ///
+    /// # Return value
+    /// the slot of the array, for lookup without its name at runtime
+    fn array_slot(&mut self, name: &str) -> usize {
+        self.compiling_program.resolve_array(name)
+    }
+
+    /// Emits a looping instruction to go backwards in the code
+    ///
+    /// # Arguments
+    /// - `loop_start` the pointer to the the instruction where the loop began
+    /// - `line` the line to associate the instruction with
+    fn emit_loop(&mut self, loop_start: usize, line: i32) {
+        // the distance is measured from the end of the instruction, which includes its operand
+        let short_offset = self.current_chunk().code.len() + 3 - loop_start;
+        if let Ok(operand) = u16::try_from(short_offset) {
+            self.emit_byte(OpCode::Loop, line);
+            self.current_chunk().write_short(operand, line);
+            return;
+        }
+
+        let wide_offset = self.current_chunk().code.len() + 5 - loop_start;
+        let operand = u32::try_from(wide_offset).unwrap_or_else(|_| {
+            self.error("Loop body too large.", line);
+            u32::MAX
+        });
+        self.emit_byte(OpCode::LoopWide, line);
+        self.current_chunk().write_long(operand, line);
+    }
+
+    /// Emit a jump instruction, with a placeholder for the distance to jump
+    ///
+    /// # Arguments
+    /// - `instruction` the [OpCode] to emit
+    /// - `line` the line to associate the instruction with
+    ///
+    /// # Return value
+    /// the emitted jump instruction, to be patched once the location to jump to has been generated
+    fn emit_jump(&mut self, instruction: OpCode, line: i32) -> PendingJump {
+        let id = self.jump_count;
+        self.jump_count += 1;
+
+        let wide = self.wide_jumps.contains(&id);
+        if wide {
+            let wide_instruction = match instruction {
+                OpCode::JumpIfFalse => OpCode::JumpIfFalseWide,
+                OpCode::Jump => OpCode::JumpWide,
+                OpCode::JumpIfTrue => OpCode::JumpIfTrueWide,
+                _ => unreachable!("{:?} is not a forward jump", instruction),
+            };
+            self.emit_byte(wide_instruction, line);
+            self.current_chunk().write_long(u32::MAX, line);
+        } else {
+            self.emit_byte(instruction, line);
+            self.current_chunk().write_short(u16::MAX, line);
+        }
+
+        // record the offset, that is, where the operand we'll later overwrite begins
+        let width = if wide { 4 } else { 2 };
+        PendingJump {
+            id,
+            offset: self.current_chunk().code.len() - width,
+            wide,
+        }
+    }
+
+    /// Patch a jump instruction that was previously emitted to jump to the current location
+    ///
+    /// # Arguments
+    /// - `jump` the jump instruction that was emitted
+    fn patch_jump(&mut self, jump: PendingJump) {
+        let width = if jump.wide { 4 } else { 2 };
+        // calculate the jump distance, from the end of the jump instruction's operand
+        let distance = self.current_chunk().code.len() - jump.offset - width;
+
+        if jump.wide {
+            match u32::try_from(distance) {
+                Ok(operand) => self.current_chunk().patch_long(jump.offset, operand),
+                Err(_) => {
+                    let line = self.current_chunk().line(jump.offset);
+                    self.error("Too much code to jump over.", line);
+                }
+            }
+        } else {
+            match u16::try_from(distance) {
+                Ok(operand) => self.current_chunk().patch_short(jump.offset, operand),
+                // the jump will be emitted with a wider operand when the program is generated again
+                Err(_) => self.overflowed_jumps.push(jump.id),
+            }
+        }
+    }
+
+    /// Helper function to emit the bytes associated with a number constant
+    ///
+    /// # Arguments
+    /// - `number` the constant to emit bytes for
+    /// - `line` the line to associate the constant with
+    fn emit_number(&mut self, number: f32, line: i32) {
+        let index = self.current_chunk().add_number(number);
+        self.emit_operand_instruction(
+            OpCode::NumberConstant,
+            index,
+            "Too many constants in one chunk.",
+            line,
+        );
+    }
+
+    /// Helper function to emit the bytes associated with a string constant
+    ///
+    /// # Arguments
+    /// - `string` the constant to emit bytes for
+    /// - `line` the line to associate the constant with
+    fn emit_string(&mut self, string: String, line: i32) {
+        let index = self.current_chunk().add_string(string);
+        self.emit_operand_instruction(
+            OpCode::StringConstant,
+            index,
+            "Too many constants in one chunk.",
+            line,
+        );
+    }
+
+    /// Helper function to emit the bytes of an instruction that operates on a variable or array
+    ///
+    /// # Arguments
+    /// - `op_code` the instruction to emit
+    /// - `slot` the slot of the variable or array
+    /// - `line` the line to associate the instruction with
+    fn emit_slot_instruction(&mut self, op_code: OpCode, slot: usize, line: i32) {
+        self.emit_operand_instruction(op_code, slot, "Too many variables in one program.", line);
+    }
+
+    /// Helper function to emit the bytes of an instruction followed by its operand
+    ///
+    /// # Arguments
+    /// - `op_code` the instruction to emit
+    /// - `operand` the operand of the instruction
+    /// - `limit_message` the message to report if the operand is too large to be encoded
+    /// - `line` the line to associate the instruction with
+    fn emit_operand_instruction(
+        &mut self,
+        op_code: OpCode,
+        operand: usize,
+        limit_message: &str,
+        line: i32,
+    ) {
+        let operand = self.short_operand(operand, limit_message, line);
+        self.emit_byte(op_code, line);
+        self.current_chunk().write_short(operand, line);
+    }
+
+    /// Converts an operand to one that is two bytes wide, reporting an error if it cannot be encoded
+    ///
+    /// # Arguments
+    /// - `operand` the operand to convert
+    /// - `limit_message` the message to report if the operand is too large to be encoded
+    /// - `line` the line to report the error on
+    ///
+    /// # Return value
+    /// the converted operand, or zero if it could not be converted
+    fn short_operand(&mut self, operand: usize, limit_message: &str, line: i32) -> u16 {
+        u16::try_from(operand).unwrap_or_else(|_| {
+            self.error(limit_message, line);
+            0
+        })
+    }
+
+    /// Helper function to emit a single byte instruction
+    ///
+    /// # Arguments
+    /// - `op_code` the value to emit the bytes for
+    /// - `line` the line to associate the op code with
+    fn emit_byte(&mut self, op_code: OpCode, line: i32) {
+        self.current_chunk().write_op_code(op_code, line);
+    }
+
+    /// Reports an error found while generating code
+    ///
+    /// Records the error in [CodeGenerator#structfield.errors]
+    ///
+    /// # Arguments
+    /// - `message` the message to relay to the user
+    /// - `line` the line that the error was reported to have occurred on
+    fn error(&mut self, message: &str, line: i32) {
+        self.errors
+            .push(Error::new(ErrorKind::Compile, message).with_line(line as usize));
+    }
+
+    fn emit_return(&mut self) {
+        let chunk = self.current_chunk();
+        let line = match chunk.code.len() {
+            0 => 1,
+            length => chunk.line(length - 1),
+        };
+        self.emit_byte(OpCode::OpReturn, line);
+    }
+
+    /// Retrieves the chunk that code is currently being emitted to
+    fn current_chunk(&mut self) -> &mut Chunk {
+        self.compiling_program.chunk_mut(self.compiling_section)
+    }
+
+    fn end_compiler(&mut self) {
+        for section in [Section::Begin, Section::Main, Section::End] {
+            self.compiling_section = section;
+            #[cfg(debug_assertions)]
+            self.current_chunk()
+                .disassemble_chunk(format!("{:?}", section).as_str());
+            self.emit_return();
+        }
+    }
+}
+
+/// Retrieves the op code that performs a binary operation
+///
+/// # Arguments
+/// - `operator` the binary operator
+///
+/// # Return value
+/// the op code for the operator
+fn binary_op_code(operator: BinaryOperator) -> OpCode {
+    match operator {
+        BinaryOperator::GreaterEqual => OpCode::GreaterEqual,
+        BinaryOperator::Greater => OpCode::Greater,
+        BinaryOperator::LessEqual => OpCode::LessEqual,
+        BinaryOperator::Less => OpCode::Less,
+        BinaryOperator::Equal => OpCode::DoubleEqual,
+        BinaryOperator::NotEqual => OpCode::NotEqual,
+        BinaryOperator::Add => OpCode::Add,
+        BinaryOperator::Subtract => OpCode::Subtract,
+        BinaryOperator::Multiply => OpCode::Multiply,
+        BinaryOperator::Divide => OpCode::Divide,
+        BinaryOperator::Modulus => OpCode::Modulus,
+        BinaryOperator::Exponentiation => OpCode::Exponentiation,
+        BinaryOperator::Concatenate => OpCode::Concatenate,
+    }
+}
@@ -0,0 +1,294 @@
+//! Errors that may occur when compiling or running an awk program
+
+use std::error;
+use std::fmt;
+
+/// The name given to a program that was provided on the command line rather than read from a file, when describing
+/// where an error occurred
+const COMMAND_LINE_PROGRAM: &str = "cmd. line";
+
+/// The different kinds of errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    /// the program is not valid awk
+    Syntax,
+    /// the program is valid awk, but could not be compiled to bytecode (e.g. it has too many constants)
+    Compile,
+    /// the program could not run to completion
+    Runtime,
+    /// a file or stream could not be read from or written to
+    Io,
+    /// the output was closed before the program finished writing to it, by a reader that only reads part of it (e.g.
+    /// `head`)
+    BrokenPipe,
+}
+
+/// An error found while compiling or running a program
+///
+/// Errors found in the source of a program carry the line (and column, if known) on which they were found, and the
+/// program file the line belongs to if the program was read from a file. Errors found while running a program also
+/// carry the input file and number of the record that was being processed, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    line: Option<usize>,
+    column: Option<usize>,
+    file: Option<String>,
+    record: Option<(String, usize)>,
+}
+
+impl Error {
+    /// Constructs a new `Error`, without a location
+    ///
+    /// # Arguments
+    /// - `kind` the kind of error
+    /// - `message` a description of the error
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Error {
+        Error {
+            kind,
+            message: message.into(),
+            line: None,
+            column: None,
+            file: None,
+            record: None,
+        }
+    }
+
+    /// Sets the line of the program that the error was found on
+    ///
+    /// # Arguments
+    /// - `line` the line, starting from 1
+    pub fn with_line(mut self, line: usize) -> Error {
+        self.line = Some(line);
+        self
+    }
+
+    /// Sets the column of the program that the error was found on
+    ///
+    /// # Arguments
+    /// - `column` the column, starting from 1
+    pub fn with_column(mut self, column: usize) -> Error {
+        self.column = Some(column);
+        self
+    }
+
+    /// Sets the file that the error was found in
+    ///
+    /// # Arguments
+    /// - `file` the path of the file
+    pub fn with_file(mut self, file: impl Into<String>) -> Error {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Sets the record that was being processed when the error was found
+    ///
+    /// # Arguments
+    /// - `filename` the name of the input the record was read from, as it would be held by `FILENAME`
+    /// - `fnr` the number of the record within that input, starting from 1
+    pub fn with_record(mut self, filename: impl Into<String>, fnr: usize) -> Error {
+        self.record = Some((filename.into(), fnr));
+        self
+    }
+
+    /// Retrieves the kind of error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Retrieves a description of the error
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Retrieves the line of the program that the error was found on, if known
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Retrieves the column of the program that the error was found on, if known
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    /// Retrieves the file that the error was found in, if known
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Retrieves the name of the input that the record being processed when the error was found was read from, if any
+    pub fn filename(&self) -> Option<&str> {
+        self.record.as_ref().map(|(filename, _)| filename.as_str())
+    }
+
+    /// Retrieves the number of the record being processed when the error was found within its input, if any
+    pub fn fnr(&self) -> Option<usize> {
+        self.record.as_ref().map(|(_, fnr)| *fnr)
+    }
+
+    /// Renders the line of the program that the error was found on, with a caret beneath the column it was found at
+    ///
+    /// ```
+    /// use rawk::Program;
+    ///
+    /// let source = "{ print 1 +; }";
+    /// let error = Program::compile(source).err().unwrap();
+    ///
+    /// assert_eq!(
+    ///     error.errors()[0].snippet(source).unwrap(),
+    ///     "{ print 1 +; }\n           ^"
+    /// );
+    /// ```
+    ///
+    /// # Arguments
+    /// - `source` the source of the program that the error was found in
+    ///
+    /// # Return value
+    /// - the offending line, followed by the caret if the column of the error is known
+    /// - `None` if the error was not found on a line of `source`
+    pub fn snippet(&self, source: &str) -> Option<String> {
+        let line = self.line?;
+        let text = source.lines().nth(line.checked_sub(1)?)?;
+        let Some(column) = self.column else {
+            return Some(String::from(text));
+        };
+
+        // tabs are kept as is, so that the caret lines up with the text above it however wide a tab is displayed
+        let indent: String = text
+            .chars()
+            .take(column - 1)
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        Some(format!("{}\n{}^", text, indent))
+    }
+}
+
+impl fmt::Display for Error {
+    /// Formats the error, prefixed by its location if it is known - e.g. `prog.awk:3:7: message`, or
+    /// `prog.awk:3: (FILENAME=data.txt FNR=12) message` for an error found while processing a record
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            let file = self.file.as_deref().unwrap_or(COMMAND_LINE_PROGRAM);
+            write!(f, "{}:{}:", file, line)?;
+            if let Some(column) = self.column {
+                write!(f, "{}:", column)?;
+            }
+            write!(f, " ")?;
+        }
+        if let Some((filename, fnr)) = &self.record {
+            write!(f, "(FILENAME={} FNR={}) ", filename, fnr)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl error::Error for Error {}
+
+/// The errors found while compiling a program, preventing it from being run
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompileError {
+    errors: Vec<Error>,
+}
+
+impl CompileError {
+    /// Constructs a new `CompileError`
+    ///
+    /// # Arguments
+    /// - `errors` each error that was found in the program
+    pub fn new(errors: Vec<Error>) -> CompileError {
+        CompileError { errors }
+    }
+
+    /// Retrieves each error that was found in the program, in the order they were found
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Consumes the `CompileError`, returning each error that was found in the program
+    pub fn into_errors(self) -> Vec<Error> {
+        self.errors
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(Error::to_string).collect();
+        write!(f, "{}", errors.join("\n"))
+    }
+}
+
+impl error::Error for CompileError {}
+
+#[cfg(test)]
+mod error_display {
+    use super::*;
+
+    #[test]
+    fn it_displays_an_error_without_a_location() {
+        let error = Error::new(ErrorKind::Io, "can't open file data.txt");
+
+        assert_eq!(error.to_string(), "can't open file data.txt");
+    }
+
+    #[test]
+    fn it_displays_the_line_of_a_command_line_program() {
+        let error = Error::new(ErrorKind::Syntax, "syntax error at end").with_line(2);
+
+        assert_eq!(error.to_string(), "cmd. line:2: syntax error at end");
+    }
+
+    #[test]
+    fn it_displays_the_file_line_and_column() {
+        let error = Error::new(ErrorKind::Syntax, "syntax error at end")
+            .with_line(2)
+            .with_column(7)
+            .with_file("prog.awk");
+
+        assert_eq!(error.to_string(), "prog.awk:2:7: syntax error at end");
+    }
+
+    #[test]
+    fn it_displays_the_record_being_processed() {
+        let error = Error::new(ErrorKind::Runtime, "trying to access out of range field -1")
+            .with_line(4)
+            .with_record("data.txt", 3000000);
+
+        assert_eq!(
+            error.to_string(),
+            "cmd. line:4: (FILENAME=data.txt FNR=3000000) trying to access out of range field -1"
+        );
+    }
+
+    #[test]
+    fn it_renders_a_snippet_with_a_caret_under_the_column() {
+        let error = Error::new(ErrorKind::Syntax, "syntax error")
+            .with_line(2)
+            .with_column(9);
+
+        assert_eq!(
+            error.snippet("BEGIN {\n\tprint 1 +;\n}").unwrap(),
+            "\tprint 1 +;\n\t       ^"
+        );
+    }
+
+    #[test]
+    fn it_renders_a_snippet_without_a_caret_for_an_unknown_column() {
+        let error = Error::new(ErrorKind::Compile, "too many constants").with_line(1);
+
+        assert_eq!(error.snippet("{ print 1; }").unwrap(), "{ print 1; }");
+    }
+
+    #[test]
+    fn it_renders_no_snippet_without_a_line_of_the_source() {
+        let source = "{ print 1; }";
+
+        assert_eq!(Error::new(ErrorKind::Io, "oops").snippet(source), None);
+        assert_eq!(
+            Error::new(ErrorKind::Syntax, "oops")
+                .with_line(3)
+                .snippet(source),
+            None
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! Data files that records are read from, which may be compressed
+
+use crate::binary;
+
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use ruzstd::frame::ReadFrameHeaderError;
+use ruzstd::frame_decoder::FrameDecoderError;
+use ruzstd::{BlockDecodingStrategy, FrameDecoder};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+use std::iter;
+
+/// The bytes that a file compressed with gzip begins with
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+/// The bytes that a file compressed with zstd begins with
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+/// The bytes that follow the first byte of a skippable zstd frame, whose first byte is any of `0x50` to `0x5f`
+const ZSTD_SKIPPABLE_MAGIC: &[u8] = &[0x2a, 0x4d, 0x18];
+/// The bytes that a file compressed with bzip2 begins with
+const BZIP2_MAGIC: &[u8] = b"BZh";
+
+/// The ways that a data file may be compressed
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Determines how data is compressed from the bytes it begins with
+    ///
+    /// # Arguments
+    /// - `start` the first bytes of the data, which may be fewer than the length of any magic number if the data is
+    ///   that short
+    fn detect(start: &[u8]) -> Compression {
+        if start.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if start.starts_with(ZSTD_MAGIC) || is_skippable_zstd_frame(start) {
+            Compression::Zstd
+        } else if start.starts_with(BZIP2_MAGIC) {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Determines whether data begins with a skippable zstd frame, which may precede the compressed frames of zstd data
+///
+/// # Arguments
+/// - `start` the first bytes of the data
+fn is_skippable_zstd_frame(start: &[u8]) -> bool {
+    matches!(start.split_first(), Some((first, rest)) if first & 0xf0 == 0x50 && rest.starts_with(ZSTD_SKIPPABLE_MAGIC))
+}
+
+/// Opens a data file to be read from, decompressing its contents as they are read if it is compressed with gzip, zstd
+/// or bzip2
+///
+/// Compression is detected by the bytes that the file begins with rather than by the file's name, so that compressed
+/// data is read the same regardless of how it is named, and uncompressed data is read as is.
+///
+/// r-awk does not support `getline` yet. Reading a file with `getline < file` is left for when it does, and should
+/// open the file with this function, so that compressed files are read the same way as data files.
+///
+/// # Arguments
+/// - `path` the path of the data file to open
+///
+/// # Return value
+/// - a reader over the (decompressed) contents of the data file
+/// - an error if the data file could not be opened, or if it is compressed with zstd and its header is not valid
+pub(crate) fn open(path: &str) -> io::Result<Box<dyn BufRead>> {
+    decompress(BufReader::new(File::open(path)?))
+}
+
+/// Wraps a reader in a decoder for the compression that its contents are detected to use, if any
+///
+/// # Arguments
+/// - `reader` the reader over the contents, which have not yet been read from
+///
+/// # Return value
+/// - a reader over the decompressed contents
+/// - an error if the contents could not be read, or if they are compressed with zstd and their header is not valid
+pub(crate) fn decompress<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+    // enough bytes for the longest magic number are read ahead, as a single fill of the buffer may hold fewer than that
+    let mut start = Vec::with_capacity(ZSTD_MAGIC.len());
+    while start.len() < ZSTD_MAGIC.len() {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        let length = buffer.len().min(ZSTD_MAGIC.len() - start.len());
+        start.extend_from_slice(&buffer[..length]);
+        reader.consume(length);
+    }
+    let compression = Compression::detect(&start);
+    // the bytes that were read ahead are put back in front of the rest of the contents
+    let reader = io::Cursor::new(start).chain(reader);
+
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(MultiZstdDecoder::new(reader)?)),
+    })
+}
+
+/// A decoder of zstd compressed contents made up of any number of frames, as written by concatenating compressed files
+///
+/// Each frame is decompressed in turn, as gzip and bzip2 members are by [MultiGzDecoder] and [MultiBzDecoder].
+/// Skippable frames, which hold no compressed data, are skipped over.
+struct MultiZstdDecoder<R: BufRead> {
+    /// the reader over the compressed contents
+    source: R,
+    /// the decoder of the current frame
+    decoder: FrameDecoder,
+}
+
+impl<R: BufRead> MultiZstdDecoder<R> {
+    /// Constructs a new `MultiZstdDecoder`, reading the header of the first frame
+    ///
+    /// # Arguments
+    /// - `source` the reader over the compressed contents
+    ///
+    /// # Return value
+    /// - the decoder
+    /// - an error if the contents could not be read, or the header of the first frame is not valid
+    fn new(source: R) -> io::Result<MultiZstdDecoder<R>> {
+        let mut decoder = MultiZstdDecoder {
+            source,
+            decoder: FrameDecoder::new(),
+        };
+        decoder.next_frame()?;
+        Ok(decoder)
+    }
+
+    /// Begins decoding the next frame of the contents, skipping over any skippable frames before it
+    ///
+    /// # Return value
+    /// - `true` if a frame was begun, or `false` if the contents have been exhausted
+    /// - an error if the contents could not be read, or the header of the frame is not valid
+    fn next_frame(&mut self) -> io::Result<bool> {
+        loop {
+            if self.source.fill_buf()?.is_empty() {
+                return Ok(false);
+            }
+            match self.decoder.init(&mut self.source) {
+                Ok(()) => return Ok(true),
+                Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame {
+                    length,
+                    ..
+                })) => {
+                    let skipped =
+                        io::copy(&mut (&mut self.source).take(length as u64), &mut io::sink())?;
+                    if skipped < length as u64 {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                    }
+                }
+                Err(err) => return Err(invalid_zstd(err)),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for MultiZstdDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            // a frame is decoded a block at a time, which may not be enough to fill the buffer
+            while self.decoder.can_collect() < buf.len() && !self.decoder.is_finished() {
+                let needed = buf.len() - self.decoder.can_collect();
+                self.decoder
+                    .decode_blocks(&mut self.source, BlockDecodingStrategy::UptoBytes(needed))
+                    .map_err(invalid_zstd)?;
+            }
+            let length = self.decoder.read(buf)?;
+            // once a frame has been read in full, the frame that follows it (if any) is read from
+            if length > 0 || !self.next_frame()? {
+                return Ok(length);
+            }
+        }
+    }
+}
+
+/// Creates an error for zstd compressed contents that could not be decompressed
+///
+/// # Arguments
+/// - `err` the reason the contents could not be decompressed
+fn invalid_zstd(err: FrameDecoderError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// What separates the records of an input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RecordSeparator {
+    /// a single byte
+    Byte(u8),
+    /// one or more blank lines, as `RS` is when it is empty
+    Paragraph,
+}
+
+/// Creates an iterator over the records read from an input
+///
+/// Records are read as bytes, so that input that is not valid UTF-8 is held by each record as it was read (see
+/// [binary]). The record separator is not a part of the record it ends, and the last record of the input need not
+/// end with one. When records are separated by blank lines, blank lines before the first record and after the last
+/// are ignored, and the lines of a record are joined by newlines.
+///
+/// # Arguments
+/// - `reader` the reader over the input
+/// - `separator` what separates records
+///
+/// # Return value
+/// an iterator over the text of each record, or the error that prevented a record from being read
+pub(crate) fn records(
+    mut reader: impl BufRead,
+    separator: RecordSeparator,
+) -> impl Iterator<Item = io::Result<String>> {
+    iter::from_fn(move || {
+        let record = match separator {
+            RecordSeparator::Byte(separator) => read_until(&mut reader, separator),
+            RecordSeparator::Paragraph => read_paragraph(&mut reader),
+        };
+        record.map(|record| record.map(binary::decode)).transpose()
+    })
+}
+
+/// Reads the bytes of an input up to the next separator
+///
+/// # Arguments
+/// - `reader` the reader over the input
+/// - `separator` the byte to read up to, which is not a part of the bytes returned
+///
+/// # Return value
+/// - the bytes that were read, or `None` at the end of the input
+/// - an error if the input could not be read
+fn read_until(reader: &mut impl BufRead, separator: u8) -> io::Result<Option<Vec<u8>>> {
+    let mut bytes = vec![];
+    if reader.read_until(separator, &mut bytes)? == 0 {
+        return Ok(None);
+    }
+    if bytes.last() == Some(&separator) {
+        bytes.pop();
+    }
+    Ok(Some(bytes))
+}
+
+/// Reads the lines of an input up to the next blank line, skipping any blank lines that come first
+///
+/// # Arguments
+/// - `reader` the reader over the input
+///
+/// # Return value
+/// - the lines that were read, joined by newlines, or `None` at the end of the input
+/// - an error if the input could not be read
+fn read_paragraph(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut paragraph: Option<Vec<u8>> = None;
+    while let Some(line) = read_until(reader, b'\n')? {
+        match (&mut paragraph, line.is_empty()) {
+            (None, true) => continue,
+            (None, false) => paragraph = Some(line),
+            (Some(_), true) => break,
+            (Some(paragraph), false) => {
+                paragraph.push(b'\n');
+                paragraph.extend(line);
+            }
+        }
+    }
+    Ok(paragraph)
+}
+
+#[cfg(test)]
+mod records {
+    use super::*;
+
+    #[test]
+    fn it_splits_records_by_the_separator() {
+        let records: Vec<String> = records(&b"a b\nc d\n"[..], RecordSeparator::Byte(b'\n'))
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(records, vec!["a b", "c d"]);
+    }
+
+    #[test]
+    fn it_reads_a_last_record_without_a_separator() {
+        let records: Vec<String> = records(&b"a\0b\nc\0d"[..], RecordSeparator::Byte(b'\0'))
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(records, vec!["a", "b\nc", "d"]);
+    }
+
+    #[test]
+    fn it_keeps_the_bytes_of_invalid_utf8() {
+        let record = records(&[0xff, b'\r', b'\n'][..], RecordSeparator::Byte(b'\n'))
+            .next()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(binary::encode(&record), vec![0xff, b'\r']);
+    }
+
+    #[test]
+    fn it_separates_paragraphs_by_blank_lines() {
+        let input = &b"\n\na 1\nb 2\n\n\n\nc 3\n \nd 4\n\n"[..];
+        let records: Vec<String> = records(input, RecordSeparator::Paragraph)
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(records, vec!["a 1\nb 2", "c 3\n \nd 4"]);
+    }
+
+    #[test]
+    fn it_reads_no_paragraphs_from_blank_lines() {
+        assert_eq!(records(&b"\n\n"[..], RecordSeparator::Paragraph).count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod compression {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::fs;
+    use std::io::Write;
+
+    /// Reads the contents of a reader into a string
+    fn read(mut reader: Box<dyn BufRead>) -> String {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    #[test]
+    fn it_detects_compression_by_magic_number() {
+        assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd]),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::detect(&[0x5a, 0x2a, 0x4d, 0x18]),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::detect(b"BZh9"), Compression::Bzip2);
+        assert_eq!(Compression::detect(b"BZ"), Compression::None);
+        assert_eq!(Compression::detect(b""), Compression::None);
+    }
+
+    #[test]
+    fn it_reads_uncompressed_contents_as_is() {
+        assert_eq!(read(decompress(&b"a b\nc d\n"[..]).unwrap()), "a b\nc d\n");
+        assert_eq!(read(decompress(&b"a"[..]).unwrap()), "a");
+    }
+
+    #[test]
+    fn it_decompresses_every_member_of_gzip_contents() {
+        let mut contents = vec![];
+        for member in ["a b\n", "c d\n"] {
+            let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+            encoder.write_all(member.as_bytes()).unwrap();
+            contents.extend(encoder.finish().unwrap());
+        }
+
+        assert_eq!(
+            read(decompress(io::Cursor::new(contents)).unwrap()),
+            "a b\nc d\n"
+        );
+    }
+
+    #[test]
+    fn it_decompresses_every_frame_of_zstd_contents() {
+        let frame = include_bytes!("../../tests/data/hours1.dat.zst");
+        let skippable_frame = [0x50, 0x2a, 0x4d, 0x18, 0x02, 0x00, 0x00, 0x00, 0xff, 0xff];
+        let contents = [&frame[..], &skippable_frame, &frame[..]].concat();
+
+        let decompressed = read(decompress(io::Cursor::new(contents)).unwrap());
+
+        let expected = fs::read_to_string("./tests/data/hours1.dat").unwrap();
+        assert_eq!(decompressed, expected.repeat(2));
+    }
+
+    #[test]
+    fn it_reports_invalid_zstd_contents() {
+        let error = decompress(&[0x28, 0xb5, 0x2f, 0xfd, 0xff][..])
+            .err()
+            .expect("the contents should not be decompressed");
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
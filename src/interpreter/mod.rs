@@ -0,0 +1,520 @@
+//! An interpreter for running a compiled awk program against any input and output
+
+use crate::characters::Characters;
+use crate::error::{Error, ErrorKind};
+use crate::input;
+use crate::input::RecordSeparator;
+use crate::jsonl;
+use crate::output::OutputFormat;
+use crate::program::{Program, Section};
+use crate::record::{csv_records, FieldSplitter, FieldWidth, Header, Record};
+use crate::value::Value;
+use crate::vm::VM;
+
+use regex::Regex;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+/// The exit status of a program that ran to completion
+const EXIT_SUCCESS: i32 = 0;
+/// The name given to input that was not read from a named file, when describing the record an error was found in
+const UNNAMED_INPUT: &str = "-";
+/// The name of the special variable holding the character that separates records
+const RS: &str = "RS";
+/// The name of the special variable holding the field separator
+const FS: &str = "FS";
+/// The name of the special variable holding the name of the current input
+const FILENAME: &str = "FILENAME";
+/// The name of the special variable holding a regular expression that describes the contents of a field
+const FPAT: &str = "FPAT";
+/// The name of the special variable holding the widths of fields found at fixed positions
+const FIELDWIDTHS: &str = "FIELDWIDTHS";
+/// The special variables that choose how records are split into fields, the most recently assigned of which is used
+const SPLITTER_VARIABLES: [&str; 3] = [FS, FPAT, FIELDWIDTHS];
+/// The name of the special array holding information about the running program
+const PROCINFO: &str = "PROCINFO";
+/// The element of [PROCINFO] naming the way records are currently split into fields
+const PROCINFO_FS: &str = "FS";
+/// The name of the special array holding the index of each field named by the header of the current input
+const FIELD: &str = "FIELD";
+/// The name of the special array holding the values of a record of JSON Lines input, by their path
+const J: &str = "J";
+
+/// Runs a compiled awk program, reading records from a host provided input and writing to a host provided output
+///
+/// The state of the program's global variables is kept between runs, and may be preset or read back by the host:
+///
+/// ```
+/// use rawk::{Interpreter, Program};
+///
+/// let program = Program::compile("{ total = total + $2; } END { print prefix total; }").unwrap();
+/// let mut interpreter = Interpreter::new(program);
+/// interpreter.set_global("prefix", "total: ");
+///
+/// let mut output = Vec::new();
+/// let status = interpreter.run("a 1\nb 2\n".as_bytes(), &mut output).unwrap();
+///
+/// assert_eq!(status, 0);
+/// assert_eq!(String::from_utf8(output).unwrap(), "total: 3\n");
+/// assert_eq!(interpreter.get_global("total"), "3");
+/// ```
+pub struct Interpreter {
+    /// the VM the program is run on
+    vm: VM,
+    /// how every record is split into fields
+    splitter: FieldSplitter,
+    /// the value of `FS` when the last record was read
+    field_separator: String,
+    /// the number of the most recent assignment to each of [SPLITTER_VARIABLES] when the last record was read
+    splitter_assignments: [u64; 3],
+    /// how records are split by the field separator when the last record was read
+    separator: FieldSplitter,
+    /// the name of the input that records are currently being read from
+    filename: String,
+    /// the number of records read from the current input
+    fnr: usize,
+    /// whether the first record of each input is a header, naming the fields of the records that follow it
+    has_header: bool,
+    /// the header of the current input, once it has been read
+    header: Option<Arc<Header>>,
+    /// whether each record is a JSON value, whose contents are held by the `J` array
+    is_jsonl: bool,
+    /// what a single character of a record is
+    characters: Characters,
+}
+
+impl Interpreter {
+    /// Constructs a new `Interpreter`
+    ///
+    /// # Arguments
+    /// - `program` the compiled program to run
+    pub fn new(program: Program) -> Interpreter {
+        let mut interpreter = Interpreter {
+            vm: VM::new(program),
+            splitter: FieldSplitter::default(),
+            field_separator: String::from(" "),
+            splitter_assignments: [0; 3],
+            separator: FieldSplitter::default(),
+            filename: String::from(UNNAMED_INPUT),
+            fnr: 0,
+            has_header: false,
+            header: None,
+            is_jsonl: false,
+            characters: Characters::default(),
+        };
+        interpreter.set_splitter(FieldSplitter::default());
+        interpreter.set_field_separator(" ");
+        interpreter.set_record_separator("\n");
+        interpreter
+    }
+
+    /// Sets the field separator that records are split with, a single space by default
+    ///
+    /// The field separator is held by `FS`, which the program may assign to as well. It is read as each record is read,
+    /// and a field separator of more than one character is a regular expression, which is reported as an error if it is
+    /// not valid. The field separator is not used while the program has assigned a regular expression to `FPAT`, which
+    /// describes the contents of each field rather than what separates them, or assigned field widths to `FIELDWIDTHS`
+    ///
+    /// # Arguments
+    /// - `field_separator` the field separator to use
+    pub fn set_field_separator(&mut self, field_separator: &str) {
+        self.vm
+            .set_global(FS, Value::String(String::from(field_separator)));
+    }
+
+    /// Sets the character that separates records, a newline by default
+    ///
+    /// The record separator is held by `RS`, which the program may assign to as well. It is read as each input begins,
+    /// and must be a single byte - e.g. `"\0"` for input whose records are separated by NUL bytes, as written by
+    /// `find -print0`.
+    ///
+    /// # Arguments
+    /// - `record_separator` the record separator to use
+    pub fn set_record_separator(&mut self, record_separator: &str) {
+        self.vm
+            .set_global(RS, Value::String(String::from(record_separator)));
+    }
+
+    /// Reads input as comma separated values, as described by RFC 4180, in place of splitting records with a field
+    /// separator
+    ///
+    /// Quoted fields may contain commas, escaped quotes (`""`), and newlines, in which case a record spans more than
+    /// one line of input. The value of a quoted field does not include its surrounding quotes. Neither the field
+    /// separator, `FPAT` nor `FIELDWIDTHS` are used to split records that are read as comma separated values.
+    pub fn set_csv(&mut self) {
+        self.set_splitter(FieldSplitter::Csv);
+    }
+
+    /// Treats the first record of each input as a header naming its fields, rather than as a record to run the program
+    /// against
+    ///
+    /// The fields of the records that follow a header may be referenced by name (`$"status"`), and the index of each
+    /// named field is held by the `FIELD` array (`FIELD["status"]`). Referencing a name that is not in the header is an
+    /// error.
+    pub fn set_header(&mut self) {
+        self.has_header = true;
+    }
+
+    /// Reads each record as a JSON value, as a line of JSON Lines input
+    ///
+    /// Before the program is run against a record, the `J` array is filled with the values the record holds, named by
+    /// their dotted path (`J["req.method"]`). The record remains available as a whole as `$0`. A record that is not
+    /// valid JSON is an error.
+    pub fn set_jsonl(&mut self) {
+        self.is_jsonl = true;
+    }
+
+    /// Treats every byte of a string as a character of its own, rather than every UTF-8 encoded character, like gawk's
+    /// `-b` option
+    ///
+    /// Characters are counted by `length`, `substr` and `index`, and records are split into characters by an empty
+    /// field separator (`FS = ""`) and into fixed width fields by `FIELDWIDTHS`. Counting bytes is faster, at the cost
+    /// of splitting characters that are more than one byte long. A string that splits a character holds the bytes of it
+    /// that it includes, and they are written out as those bytes.
+    pub fn set_bytes(&mut self) {
+        self.characters = Characters::Bytes;
+        self.vm.set_characters(Characters::Bytes);
+    }
+
+    /// Writes the values given to each `print` statement as a record of comma separated values, quoting any value that
+    /// contains a comma, a double quote or a line break as described by RFC 4180
+    ///
+    /// A `print` statement without any values writes each field of the current record.
+    pub fn set_ocsv(&mut self) {
+        self.vm.set_output_format(OutputFormat::Csv);
+    }
+
+    /// Writes the values given to each `print` statement as a JSON array, one per line
+    ///
+    /// Numbers computed by the program are written as JSON numbers, and every other value as a JSON string. A `print`
+    /// statement without any values writes the fields of the current record, as an object keyed by the names of its
+    /// fields if the input has a header (see [Interpreter::set_header]).
+    pub fn set_ojson(&mut self) {
+        self.vm.set_output_format(OutputFormat::Json);
+    }
+
+    /// Assigns a value to a global variable, as if it were assigned on the command line with `-v`
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    /// - `value` the value to assign, which is treated as a number if it looks like one
+    pub fn set_global(&mut self, name: &str, value: &str) {
+        self.vm
+            .set_global(name, Value::from_user_input(String::from(value)));
+    }
+
+    /// Retrieves the value of a global variable
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    ///
+    /// # Return value
+    /// the string value of the variable, or an empty string if it has never been assigned
+    pub fn get_global(&self, name: &str) -> String {
+        self.vm.get_global(name).str_value()
+    }
+
+    /// Runs the program, reading each record of `input`, separated by `RS`
+    ///
+    /// `BEGIN` actions are run first, followed by the main rules for each record and `END` actions once the input is
+    /// exhausted. A program made up of only `BEGIN` actions does not read any input.
+    ///
+    /// # Arguments
+    /// - `input` the input to read records from
+    /// - `output` where the output of the program is written to
+    ///
+    /// # Return value
+    /// - the exit status of the program
+    /// - an [Error] if the program is unable to run to completion, or if the input could not be read. Errors found
+    ///   while processing a record carry its number within `input`
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> Result<i32, Error> {
+        self.run_section(Section::Begin, &Record::default(), output)?;
+        if !self.reads_input() {
+            return Ok(EXIT_SUCCESS);
+        }
+
+        self.begin_input(UNNAMED_INPUT);
+        let lines = input::records(input, self.record_separator()?).map(|line| {
+            line.map_err(|err| Error::new(ErrorKind::Io, format!("unable to read input: {}", err)))
+        });
+        // the last record read remains available to END actions
+        let last_record = self.run_input(lines, output)?.unwrap_or_default();
+
+        self.run_section(Section::End, &last_record, output)?;
+        Ok(EXIT_SUCCESS)
+    }
+
+    /// Runs the main rules of the program against each record read from the current input
+    ///
+    /// # Arguments
+    /// - `lines` the lines of the input, without their trailing record separators
+    /// - `output` where the output of the program is written to
+    ///
+    /// # Return value
+    /// - the last record read from the input, or `None` if the input has no records
+    /// - an [Error] if the program is unable to run to completion, or if the input could not be read
+    pub(crate) fn run_input<'a>(
+        &mut self,
+        lines: impl Iterator<Item = Result<String, Error>> + 'a,
+        output: &mut dyn Write,
+    ) -> Result<Option<Record>, Error> {
+        let mut last_record = None;
+        for text in self.records(lines) {
+            let Some(record) = self.next_record(text?)? else {
+                continue;
+            };
+            self.run_section(Section::Main, &record, output)?;
+            last_record = Some(record);
+        }
+        Ok(last_record)
+    }
+
+    /// Runs a single section of the program against a record
+    ///
+    /// # Arguments
+    /// - `section` the section of the program to run
+    /// - `record` the record to run the section against
+    /// - `output` where the output of the program is written to
+    ///
+    /// # Return value
+    /// an [Error] if the section is unable to run to completion, carrying the most recently read record if there is one
+    pub(crate) fn run_section(
+        &mut self,
+        section: Section,
+        record: &Record,
+        output: &mut dyn Write,
+    ) -> Result<(), Error> {
+        self.vm
+            .interpret(section, record, output)
+            .map_err(|error| match self.fnr {
+                0 => error,
+                fnr => error.with_record(self.filename.as_str(), fnr),
+            })
+    }
+
+    /// Starts reading records from a new input, restarting the count of records read
+    ///
+    /// # Arguments
+    /// - `filename` the name of the input, which is held by `FILENAME` - the path of a data file as it was given,
+    ///   whether or not the file is compressed
+    pub(crate) fn begin_input(&mut self, filename: &str) {
+        self.filename = String::from(filename);
+        self.vm
+            .set_global(FILENAME, Value::String(String::from(filename)));
+        self.fnr = 0;
+        // every input has a header of its own
+        self.header = None;
+    }
+
+    /// Retrieves what separates the records of the input that is beginning, as held by `RS`
+    ///
+    /// # Return value
+    /// - the record separator, which is blank lines when `RS` is empty
+    /// - an [Error] if the program has assigned a record separator of more than one byte, such as a regular
+    ///   expression, which is not supported
+    pub(crate) fn record_separator(&self) -> Result<RecordSeparator, Error> {
+        let record_separator = self.vm.get_global(RS).str_value();
+        match record_separator.as_bytes() {
+            [] => Ok(RecordSeparator::Paragraph),
+            [separator] => Ok(RecordSeparator::Byte(*separator)),
+            _ => Err(Error::new(
+                ErrorKind::Runtime,
+                format!("invalid RS value: {}", record_separator),
+            )),
+        }
+    }
+
+    /// Groups lines of input into the text of records
+    ///
+    /// Each line is a record of its own, unless input is read as comma separated values, where a record may span more
+    /// than one line
+    ///
+    /// # Arguments
+    /// - `lines` the lines of input, without their trailing newlines
+    ///
+    /// # Return value
+    /// an iterator over the text of each record, or the error that prevented a line from being read
+    fn records<'a>(
+        &self,
+        lines: impl Iterator<Item = Result<String, Error>> + 'a,
+    ) -> Box<dyn Iterator<Item = Result<String, Error>> + 'a> {
+        match self.splitter {
+            FieldSplitter::Csv => Box::new(csv_records(lines)),
+            _ => Box::new(lines),
+        }
+    }
+
+    /// Creates the next record read from the current input, split as the interpreter has been configured to
+    ///
+    /// # Arguments
+    /// - `text` the text of the record
+    ///
+    /// # Return value
+    /// - the record
+    /// - `None` if the record is the header of the current input, which the program is not run against
+    /// - an [Error] if the program has assigned an invalid value to `FPAT` or `FIELDWIDTHS`, or if the record is not
+    ///   valid JSON when reading JSON Lines input
+    fn next_record(&mut self, text: String) -> Result<Option<Record>, Error> {
+        self.fnr += 1;
+        self.update_splitter()?;
+        if self.is_jsonl {
+            self.read_json(&text)?;
+        }
+        let record = Record::new(text, self.splitter.clone()).with_characters(self.characters);
+        if !self.has_header {
+            return Ok(Some(record));
+        }
+
+        match &self.header {
+            Some(header) => Ok(Some(record.with_header(Arc::clone(header)))),
+            None => {
+                self.read_header(&record);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Fills the `J` array with the values held by a record of JSON Lines input
+    ///
+    /// # Arguments
+    /// - `text` the text of the record
+    ///
+    /// # Return value
+    /// an [Error] if the record is not valid JSON
+    fn read_json(&mut self, text: &str) -> Result<(), Error> {
+        let elements = jsonl::flatten(text)
+            .map_err(|err| self.invalid_value(format!("invalid JSON record: {}", err)))?;
+
+        self.vm.delete_array(J);
+        for (path, value) in elements {
+            self.vm.set_array_element(J, path, value);
+        }
+        Ok(())
+    }
+
+    /// Names the fields of the current input after the fields of its header, filling in the `FIELD` array
+    ///
+    /// A name that is given to more than one field refers to the first of them
+    ///
+    /// # Arguments
+    /// - `record` the header
+    fn read_header(&mut self, record: &Record) {
+        self.vm.delete_array(FIELD);
+        let mut header = Header::new();
+        for (index, name) in (1..).map_while(|index| Some((index, record.field(index)?))) {
+            let name = name.str_value();
+            if header.contains_key(&name) {
+                continue;
+            }
+            self.vm
+                .set_array_element(FIELD, name.clone(), Value::Number(index as f32));
+            header.insert(name, index);
+        }
+        self.header = Some(Arc::new(header));
+    }
+
+    /// Switches the way records are split into fields if the program has assigned `FS`, `FPAT` or `FIELDWIDTHS` since
+    /// the last record was read
+    ///
+    /// Assigning any of the variables makes it the way records are split, until another is assigned, even if it is
+    /// assigned the value it already holds. Clearing `FPAT` or `FIELDWIDTHS` while it is in use returns to splitting
+    /// records with the field separator.
+    ///
+    /// # Return value
+    /// an [Error] if the field separator or the value of `FPAT` is not a valid regular expression, or the value of
+    /// `FIELDWIDTHS` is not a valid list of field widths
+    fn update_splitter(&mut self) -> Result<(), Error> {
+        if let FieldSplitter::Csv = self.splitter {
+            return Ok(());
+        }
+
+        let assignments = SPLITTER_VARIABLES.map(|name| self.vm.assignment_of(name));
+        // the variables assigned since the last record was read take effect in the order they were assigned, so that
+        // the most recent assignment wins
+        let mut assigned: Vec<usize> = (0..SPLITTER_VARIABLES.len())
+            .filter(|&index| assignments[index] != self.splitter_assignments[index])
+            .collect();
+        assigned.sort_by_key(|&index| assignments[index]);
+        self.splitter_assignments = assignments;
+
+        for index in assigned {
+            let name = SPLITTER_VARIABLES[index];
+            let value = self.vm.get_global(name).str_value();
+            match name {
+                FS => {
+                    if value != self.field_separator {
+                        self.separator = FieldSplitter::separator(&value).map_err(|err| {
+                            self.invalid_value(format!(
+                                "invalid regular expression for FS: {}",
+                                err
+                            ))
+                        })?;
+                        self.field_separator = value;
+                    }
+                    self.set_splitter(self.separator.clone());
+                }
+                FPAT if value.is_empty() => {
+                    if let FieldSplitter::Pattern(_) = self.splitter {
+                        self.set_splitter(self.separator.clone());
+                    }
+                }
+                FPAT => {
+                    let regex = Regex::new(&value).map_err(|err| {
+                        self.invalid_value(format!("invalid regular expression for FPAT: {}", err))
+                    })?;
+                    self.set_splitter(FieldSplitter::Pattern(Arc::new(regex)));
+                }
+                // FIELDWIDTHS
+                _ if value.trim().is_empty() => {
+                    if let FieldSplitter::Widths(_) = self.splitter {
+                        self.set_splitter(self.separator.clone());
+                    }
+                }
+                _ => {
+                    let widths = FieldWidth::parse_all(&value).ok_or_else(|| {
+                        self.invalid_value(format!("invalid FIELDWIDTHS value: {}", value))
+                    })?;
+                    self.set_splitter(FieldSplitter::Widths(Arc::from(widths)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes the way records are split into fields, recording it in `PROCINFO["FS"]`
+    ///
+    /// # Arguments
+    /// - `splitter` how records are to be split
+    fn set_splitter(&mut self, splitter: FieldSplitter) {
+        self.vm.set_array_element(
+            PROCINFO,
+            String::from(PROCINFO_FS),
+            Value::String(String::from(splitter.name())),
+        );
+        self.splitter = splitter;
+    }
+
+    /// Creates an error for a value that can't be used, such as one assigned to a special variable, found while reading
+    /// the current record
+    ///
+    /// # Arguments
+    /// - `message` a description of the error
+    fn invalid_value(&self, message: String) -> Error {
+        Error::new(ErrorKind::Runtime, message).with_record(self.filename.as_str(), self.fnr)
+    }
+
+    /// Determines whether the program reads any input, which is only the case if it has main rules or `END` actions
+    pub(crate) fn reads_input(&self) -> bool {
+        self.vm.has_rules(Section::Main) || self.vm.has_rules(Section::End)
+    }
+
+    /// Retrieves the VM the program is run on
+    pub(crate) fn vm(&self) -> &VM {
+        &self.vm
+    }
+
+    /// Retrieves the VM the program is run on, for modification
+    pub(crate) fn vm_mut(&mut self) -> &mut VM {
+        &mut self.vm
+    }
+}
@@ -0,0 +1,169 @@
+//! Records of JSON Lines input, whose values are made available to programs by their path
+
+use crate::value::{StrNum, Value};
+use serde_json::Value as Json;
+
+/// Parses a record of JSON Lines input into the values it holds, each named by its path
+///
+/// The path of a value is made up of the keys of the objects (and the indices of the arrays) that lead to it, joined by
+/// dots - e.g. `req.method` for `{"req": {"method": "GET"}}`. Objects and arrays are themselves included under their
+/// own path, as compact JSON text.
+///
+/// Strings are strings, while numbers are numeric strings that keep the digits they were written with (`1.50` stays
+/// `1.50`), so that large integers such as IDs and timestamps are not rounded. Only the exponent of a number is
+/// rewritten, always with its sign (`1e2` becomes `1e+2`). `true` and `false` become `1` and `0` so that they may be
+/// used as conditions. `null` becomes an empty string. A blank record holds nothing.
+///
+/// # Arguments
+/// - `text` the text of the record, a single JSON value
+///
+/// # Return value
+/// - the path and value of everything the record holds
+/// - a description of why the record is not valid JSON
+pub(crate) fn flatten(text: &str) -> Result<Vec<(String, Value)>, String> {
+    if text.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let json: Json = serde_json::from_str(text).map_err(|err| err.to_string())?;
+
+    let mut elements = vec![];
+    flatten_children(None, &json, &mut elements);
+    Ok(elements)
+}
+
+/// Adds the children of an object or array to the flattened values of a record
+///
+/// # Arguments
+/// - `path` the path of the object or array, or `None` if it is the record as a whole
+/// - `json` the object or array
+/// - `elements` the flattened values of the record
+fn flatten_children(path: Option<&str>, json: &Json, elements: &mut Vec<(String, Value)>) {
+    let child_path = |key: &str| match path {
+        Some(path) => format!("{}.{}", path, key),
+        None => String::from(key),
+    };
+
+    match json {
+        Json::Object(object) => {
+            for (key, child) in object {
+                flatten_value(child_path(key), child, elements);
+            }
+        }
+        Json::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                flatten_value(child_path(&index.to_string()), child, elements);
+            }
+        }
+        // a record that is a lone scalar has no paths to name its value by
+        _ => (),
+    }
+}
+
+/// Adds a value, along with any children it has, to the flattened values of a record
+///
+/// # Arguments
+/// - `path` the path of the value
+/// - `json` the value
+/// - `elements` the flattened values of the record
+fn flatten_value(path: String, json: &Json, elements: &mut Vec<(String, Value)>) {
+    let value = match json {
+        Json::Null => Value::String(String::new()),
+        Json::Bool(boolean) => Value::Number(if *boolean { 1.0 } else { 0.0 }),
+        Json::Number(number) => Value::StrNum(StrNum::from(number.to_string())),
+        Json::String(string) => Value::String(string.clone()),
+        Json::Array(_) | Json::Object(_) => Value::String(json.to_string()),
+    };
+    elements.push((path.clone(), value));
+    flatten_children(Some(&path), json, elements);
+}
+
+#[cfg(test)]
+mod flattening {
+    use super::*;
+
+    #[test]
+    fn it_names_nested_values_by_their_dotted_path() {
+        let elements =
+            flatten(r#"{"status": 200, "req": {"method": "GET", "tags": ["a", true]}}"#).unwrap();
+
+        assert_eq!(
+            elements,
+            vec![
+                (
+                    String::from("req"),
+                    Value::String(String::from(r#"{"method":"GET","tags":["a",true]}"#))
+                ),
+                (
+                    String::from("req.method"),
+                    Value::String(String::from("GET"))
+                ),
+                (
+                    String::from("req.tags"),
+                    Value::String(String::from(r#"["a",true]"#))
+                ),
+                (String::from("req.tags.0"), Value::String(String::from("a"))),
+                (String::from("req.tags.1"), Value::Number(1.0)),
+                (String::from("status"), Value::StrNum(StrNum::from("200"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_converts_null_to_an_empty_string() {
+        assert_eq!(
+            flatten(r#"{"missing": null, "no": false}"#).unwrap(),
+            vec![
+                (String::from("missing"), Value::String(String::new())),
+                (String::from("no"), Value::Number(0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_names_the_elements_of_a_top_level_array_by_index() {
+        assert_eq!(
+            flatten(r#"[1, "two"]"#).unwrap(),
+            vec![
+                (String::from("0"), Value::StrNum(StrNum::from("1"))),
+                (String::from("1"), Value::String(String::from("two"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_digits_of_large_numbers() {
+        let elements = flatten(r#"{"id": 123456789, "ts": 1700000000123}"#).unwrap();
+
+        assert_eq!(
+            elements,
+            vec![
+                (String::from("id"), Value::StrNum(StrNum::from("123456789"))),
+                (
+                    String::from("ts"),
+                    Value::StrNum(StrNum::from("1700000000123"))
+                ),
+            ]
+        );
+        assert_eq!(elements[0].1.str_value(), "123456789");
+    }
+
+    #[test]
+    fn it_keeps_numbers_as_they_were_written() {
+        let elements = flatten(r#"{"count": 1e2, "price": 1.50}"#).unwrap();
+
+        // the exponent of a number is always written with its sign
+        assert_eq!(elements[0].1.str_value(), "1e+2");
+        assert_eq!(elements[0].1.num_value(), 100.0);
+        assert_eq!(elements[1].1.str_value(), "1.50");
+    }
+
+    #[test]
+    fn it_holds_nothing_for_a_blank_record() {
+        assert_eq!(flatten(" \t").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn it_rejects_invalid_json() {
+        assert!(flatten(r#"{"unterminated": "#).is_err());
+    }
+}
@@ -1,273 +1,315 @@
 //! Library for invoking the VM with provided awk code.
+//!
+//! Programs may be run from the command line with [run_program], or embedded in another application by compiling
+//! them with [Program::compile] and running them with an [Interpreter].
 
-use log::{debug, error};
+use is_terminal::IsTerminal;
+use log::debug;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use std::fs;
-
+use std::io;
+use std::io::BufRead;
+use std::iter;
+
+pub mod assignment;
+mod ast;
+mod binary;
+mod builtin;
+mod characters;
 mod chunk;
+mod codegen;
+mod error;
+mod input;
+mod interpreter;
+mod jsonl;
+mod native;
+mod optimizer;
+mod output;
 mod parser;
+mod program;
+mod record;
 pub mod runtime_config;
 mod scanner;
 mod token;
 mod value;
 mod vm;
 
+pub use crate::error::{CompileError, Error, ErrorKind};
+pub use crate::interpreter::Interpreter;
+pub use crate::native::{NativeFn, NativeFunctions};
+pub use crate::program::Program;
+pub use crate::value::Value;
+
+use crate::assignment::Assignment;
+use crate::input::RecordSeparator;
+use crate::program::Section;
+use crate::record::Record;
 use crate::runtime_config::RuntimeConfig;
 use crate::scanner::Scanner;
 use crate::token::token_type::TokenType;
 use crate::token::Token;
 use crate::vm::VM;
 
-/// Container for data that has been parsed & split per an Awk field separator (FS)
-pub struct ParsedDataInput {
-    /// The original data, prior to being parsed/split
-    original: String,
-    /// The original data, split based on a field separator
-    parsed: Vec<String>,
-}
+/// The name of the special variable holding the number of elements in [ARGV]
+const ARGC: &str = "ARGC";
+/// The name of the special array holding the command line arguments, starting with the name of the program and
+/// followed by each operand
+const ARGV: &str = "ARGV";
+/// The operand used in place of a data file path to read from STDIN
+const STDIN_PATH: &str = "-";
 
-/// Invokes a REPL for awk code.
+/// Runs a user's program as configured on the command line, writing its output to STDOUT.
 ///
-/// This function runs on a loop, which will be prematurely halted if `is_eval` is `true` (which is
-/// a very fancy way to say that it runs once). Multiple statements/expressions may be placed on a
-/// single line.
+/// Records are read from the data files named by the program's operands, or from STDIN when no data file is named.
+/// When STDIN is a terminal, its records are read one line at a time as they are entered. Only a single line is read
+/// from STDIN if `is_eval` is `true`.
 ///
 /// # Arguments
 /// - `program` the user's program to run
 /// - `runtime_config` the runtime configuration for the lifetime of the awk program
-pub fn run_program(program: &str, runtime_config: RuntimeConfig) {
+///
+/// # Return value
+/// - each error found while compiling the program, in which case no part of it is run
+/// - the error that halted the program, if it could not run to completion
+pub fn run_program(program: &str, runtime_config: RuntimeConfig) -> Result<(), Vec<Error>> {
     let scanner = Scanner::new(String::from(program));
     let tokens: Vec<Token> = scanner.scan();
 
     if tokens.is_empty() || tokens.first().unwrap().token_type == &TokenType::Eof {
-        return;
+        return Ok(());
     }
 
-    let mut vm = VM::new();
-
-    if runtime_config.is_quick {
-        // TODO: Remove this when `BEGIN` is implemented
-        let _result = vm.interpret(
-            &tokens,
-            &[ParsedDataInput {
-                original: "".into(),
-                parsed: vec![],
-            }],
-        );
-    } else if runtime_config.data_file_paths.is_empty() {
-        loop {
-            // TODO(FUTURE): Handle record separator
-            let data_received = read_user_data_from_terminal();
-            let split_data = split_user_data(&runtime_config.field_separator, &data_received);
-            let parsed_data = ParsedDataInput {
-                original: data_received,
-                parsed: split_data,
-            };
-
-            let _result = vm.interpret(&tokens, &[parsed_data]);
-
-            if runtime_config.is_eval {
-                // the eval should only run once
-                break;
-            }
-        }
-    } else {
-        let parsed_data: Vec<ParsedDataInput> = runtime_config
-            .data_file_paths
-            .iter()
-            .flat_map(|single_path| {
-                fs::read_to_string(single_path)
-                    .unwrap_or_else(|_| panic!("rawk: can't open file {single_path}"))
-                    .split_terminator('\n') // TODO(FUTURE): Handle record separator
-                    .map(|record| ParsedDataInput {
-                        original: record.into(),
-                        parsed: split_user_data(&runtime_config.field_separator, record),
-                    })
-                    .collect::<Vec<ParsedDataInput>>()
-            })
-            .collect();
-
-        let _result = vm.interpret(&tokens, &parsed_data);
+    // the whole program is compiled before BEGIN runs, so there is no sense in reading input for it
+    let program = Program::compile_tokens(
+        &tokens,
+        &NativeFunctions::new(),
+        runtime_config.is_optimized,
+    )
+    .map_err(CompileError::into_errors)?;
+
+    let mut interpreter = Interpreter::new(program);
+    interpreter.set_field_separator(&runtime_config.field_separator);
+    if runtime_config.is_csv {
+        interpreter.set_csv();
     }
-}
-
-/// Reads data from STDIN to be processed by a user's program
-///
-/// # Return value:
-/// - the data read from STDIN
-///
-/// # Panics:
-/// If the user inputs an end of file or interrupted character, or if for some reason a `Readline` error is returned by
-/// the call to `readline()`
-fn read_user_data_from_terminal() -> String {
-    let mut editor = DefaultEditor::new().expect("unable to create an editor");
-    let data_input = editor.readline("");
-    match data_input {
-        Ok(data_line) => {
-            debug!("data line to process: {}", data_line);
-            data_line
-        }
-        Err(err) => {
-            match err {
-                // Yes, we know eprintln exists and these could be `error!()`, considering
-                // them to be semantically different
-                ReadlineError::Eof => println!("Eof received, exiting."),
-                ReadlineError::Interrupted => println!("Interrupt received, exiting."),
-                _ => error!("An error occurred: '{:?}'", err),
-            }
-            panic!();
-        }
+    if runtime_config.has_header {
+        interpreter.set_header();
     }
-}
-
-/// Splits data to be used as field variables based on the provided field separator.
-fn split_user_data(field_separator: &str, data_received: &str) -> Vec<String> {
-    let mut split_data;
-    if field_separator.is_empty() {
-        // Case: field separator is an empty string
-        split_data = data_received
-            .split(field_separator)
-            .map(String::from)
-            .collect::<Vec<String>>();
-        if split_data.len() >= 2 {
-            // the default rust `split()` operation will add a leading/trailing empty string, remove it so we don't try
-            // to implement split ourselves
-            split_data.remove(split_data.len() - 1);
-            split_data.remove(0);
-        }
-    } else if field_separator.eq(" ") {
-        // Case: a single empty string, where we must strip all leading, trailing, and in-between whitespace
-        split_data = data_received
-            .split_whitespace()
-            .map(String::from)
-            .collect::<Vec<String>>();
-    } else if field_separator.len() == 1 {
-        // Case: field separator is single character separator that is not (" ")
-        split_data = data_received
-            .split(field_separator)
-            .map(String::from)
-            .collect::<Vec<String>>();
-    } else {
-        // Case: field separator is more than one character, and should be treated as a regex
-        panic!("TODO: Implement Regex Field Separators")
+    if runtime_config.is_jsonl {
+        interpreter.set_jsonl();
     }
-
-    split_data
-}
-
-#[cfg(test)]
-mod lib {
-    use super::*;
-
-    #[test]
-    fn it_splits_data_by_single_char_fs() {
-        let test_data = "  Hello,World  ,I\tam,someone! ";
-
-        let split_data = split_user_data(&",", test_data);
-
-        assert_eq!(split_data, vec!["  Hello", "World  ", "I\tam", "someone! "]);
+    if runtime_config.is_null_separated {
+        interpreter.set_record_separator("\0");
     }
-
-    #[test]
-    fn it_does_not_truncate_whitespace_for_fs() {
-        let test_data = " Alice  ,40 ,25 ";
-
-        let split_data = split_user_data(&",", test_data);
-
-        assert_eq!(split_data, vec![" Alice  ", "40 ", "25 "]);
+    if runtime_config.is_bytes {
+        interpreter.set_bytes();
     }
-
-    #[test]
-    fn it_counts_two_consecutive_fs_as_empty_record() {
-        let test_data = "Hello,,World!";
-
-        let split_data = split_user_data(&",", test_data);
-
-        assert_eq!(split_data, vec!["Hello", "", "World!"]);
+    if runtime_config.is_ocsv {
+        interpreter.set_ocsv();
+    } else if runtime_config.is_ojson {
+        interpreter.set_ojson();
     }
-
-    #[test]
-    fn it_splits_nothing_when_fs_not_found() {
-        let test_data = "  Hello World  I\tam  someone! ";
-
-        let split_data = split_user_data(&",", test_data);
-
-        assert_eq!(split_data, vec![test_data]);
+    initialize_argv(interpreter.vm_mut(), &runtime_config.data_file_paths);
+    for assignment in &runtime_config.variable_assignments {
+        interpreter.vm_mut().assign_variable(assignment);
     }
 
-    #[test]
-    fn it_splits_data_by_whitespace_when_no_fs_provided() {
-        let test_data = "  Hello World  I\tam  someone! ";
-
-        // awk/gawk/r-awk defaults to a single empty character, provide it in the test to satisfy the contract of the fn
-        let split_data = split_user_data(&" ", test_data);
+    run_interpreter(&mut interpreter, &runtime_config).map_err(|err| vec![err])
+}
 
-        assert_eq!(split_data, vec!["Hello", "World", "I", "am", "someone!"]);
+/// Runs each section of the user's program, reading records from the data files named in `ARGV` or from STDIN
+///
+/// # Arguments
+/// - `interpreter` the interpreter to run the user's program with
+/// - `runtime_config` the runtime configuration for the lifetime of the awk program
+///
+/// # Return value
+/// an [Error] if the program is unable to run to completion, or if a data file could not be read
+fn run_interpreter(
+    interpreter: &mut Interpreter,
+    runtime_config: &RuntimeConfig,
+) -> Result<(), Error> {
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    interpreter.run_section(Section::Begin, &Record::default(), &mut output)?;
+
+    // a program made up of only BEGIN actions does not read any input
+    if !interpreter.reads_input() {
+        return Ok(());
     }
 
-    #[test]
-    fn it_splits_data_by_character_when_null_string_fs_provided() {
-        // https://www.gnu.org/software/gawk/manual/html_node/Single-Character-Fields.html
-        // > Traditionally, the behavior of FS equal to "" was not defined. In this case, most versions of Unix awk
-        // > simply treat the entire record as only having one field. (d.c.) In compatibility mode (see section
-        // > Command-Line Options), if FS is the null string, then gawk also behaves this way.
-        // r-awk chooses to respect FS="" as g-awk does
-        let test_data = "Hello World";
+    // the last record read remains available to END actions
+    let mut last_record = Record::default();
 
-        let split_data = split_user_data(&"", test_data);
-
-        assert_eq!(
-            split_data,
-            vec!["H", "e", "l", "l", "o", " ", "W", "o", "r", "l", "d"]
-        );
-    }
+    if runtime_config.is_quick {
+        // main actions are run once against an empty record, without reading any input
+        interpreter.run_section(Section::Main, &Record::default(), &mut output)?;
+    } else {
+        let mut has_read_data_file = false;
+
+        // ARGV and ARGC are consulted as each operand is reached, as BEGIN actions may have modified them
+        let mut argv_index: usize = 1;
+        while (argv_index as f32) < interpreter.vm().get_global(ARGC).num_value() {
+            let operand = interpreter
+                .vm()
+                .get_array_element(ARGV, &argv_index.to_string())
+                .map(|value| value.str_value())
+                .unwrap_or_default();
+            argv_index += 1;
+
+            // deleted and blanked out elements are skipped over
+            if operand.is_empty() {
+                continue;
+            }
 
-    #[test]
-    fn it_splits_empty_data_when_fs_is_null_string() {
-        let test_data = "";
+            // an assignment operand is performed once it is reached, affecting the files that follow it
+            if let Some(assignment) = Assignment::parse(&operand) {
+                interpreter.vm_mut().assign_variable(&assignment);
+                continue;
+            }
 
-        let split_data = split_user_data(&"", test_data);
+            has_read_data_file = true;
+            let data = open_data_file(&operand).map_err(|err| {
+                Error::new(
+                    ErrorKind::Io,
+                    format!("can't open file {}: {}", operand, err),
+                )
+                .with_file(operand.as_str())
+            })?;
+            interpreter.begin_input(&operand);
+            let lines = input::records(data, interpreter.record_separator()?).map(|line| {
+                line.map_err(|err| {
+                    Error::new(
+                        ErrorKind::Io,
+                        format!("can't read file {}: {}", operand, err),
+                    )
+                    .with_file(operand.as_str())
+                })
+            });
+            if let Some(record) = interpreter.run_input(lines, &mut output)? {
+                last_record = record;
+            }
+        }
 
-        assert_eq!(split_data.len(), 0);
+        // with no data files to read, data is read from STDIN instead
+        if !has_read_data_file {
+            interpreter.begin_input(STDIN_PATH);
+            let mut lines = read_records_from_stdin(interpreter.record_separator()?)?;
+            if runtime_config.is_eval {
+                // the eval should only run once, against a single line of data
+                lines = Box::new(lines.take(1));
+            }
+            if let Some(record) = interpreter.run_input(lines, &mut output)? {
+                last_record = record;
+            }
+        }
     }
 
-    #[test]
-    fn it_splits_data_entirely_when_fs_matches_test_data_single_char() {
-        let test_data = "a";
-
-        let split_data = split_user_data(&"a", test_data);
+    interpreter.run_section(Section::End, &last_record, &mut output)
+}
 
-        assert_eq!(split_data, vec!["", ""]);
+/// Opens a data file to read records from, decompressing it as it is read if it is compressed
+///
+/// # Arguments
+/// - `path` the path of the data file to read. `-` is read from STDIN, allowing STDIN to be read in between other
+///   data files
+///
+/// # Return value
+/// - a reader over the (decompressed) contents of the data file
+/// - an error if the data file could not be opened
+fn open_data_file(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path == STDIN_PATH {
+        input::decompress(io::stdin().lock())
+    } else {
+        input::open(path)
     }
+}
 
-    #[test]
-    fn it_splits_data_when_fs_matches_leading_char() {
-        let test_data = "abac";
-
-        let split_data = split_user_data(&"a", test_data);
-
-        assert_eq!(split_data, vec!["", "b", "c"]);
+/// Initializes the ARGC and ARGV special variables
+///
+/// `ARGV[0]` holds the name of the program, and is followed by each operand in the order it was given. `ARGC` holds
+/// the number of elements in `ARGV`.
+///
+/// # Arguments
+/// - `vm` the VM to initialize the variables in
+/// - `operands` the operands that followed the program on the command line
+fn initialize_argv(vm: &mut VM, operands: &[String]) {
+    vm.set_array_element(
+        ARGV,
+        String::from("0"),
+        Value::String(String::from(env!("CARGO_PKG_NAME"))),
+    );
+    for (index, operand) in operands.iter().enumerate() {
+        vm.set_array_element(
+            ARGV,
+            (index + 1).to_string(),
+            Value::from_user_input(operand.clone()),
+        );
     }
+    vm.set_global(ARGC, Value::Number((operands.len() + 1) as f32));
+}
 
-    #[test]
-    fn it_splits_data_when_fs_matches_trailing_char() {
-        let test_data = "baca";
-
-        let split_data = split_user_data(&"a", test_data);
-
-        assert_eq!(split_data, vec!["b", "c", ""]);
+/// Creates an iterator over the records read from STDIN
+///
+/// When STDIN is a terminal, records are read interactively one line at a time. Otherwise, STDIN has been piped or
+/// redirected to r-awk, and is read through a buffer, decompressing it if it is compressed. In either case, the iterator
+/// ends once an end of file is read.
+///
+/// # Arguments
+/// - `separator` what separates records, when STDIN is not a terminal
+///
+/// # Return value
+/// - an iterator over the records read from STDIN, or the error that prevented a record from being read
+/// - an error if a terminal could not be prepared for reading, or if piped data could not be read from
+fn read_records_from_stdin(
+    separator: RecordSeparator,
+) -> Result<Box<dyn Iterator<Item = Result<String, Error>>>, Error> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        let mut editor = DefaultEditor::new().map_err(|err| {
+            Error::new(
+                ErrorKind::Io,
+                format!("unable to create an editor: {}", err),
+            )
+        })?;
+        Ok(Box::new(iter::from_fn(move || {
+            read_user_data_from_terminal(&mut editor).transpose()
+        })))
+    } else {
+        // piped data may be compressed, just as it may be when STDIN is named by '-'
+        let reader = input::decompress(stdin.lock())
+            .map_err(|err| Error::new(ErrorKind::Io, format!("unable to read STDIN: {}", err)))?;
+        Ok(Box::new(input::records(reader, separator).map(|line| {
+            line.map_err(|err| Error::new(ErrorKind::Io, format!("unable to read STDIN: {}", err)))
+        })))
     }
+}
 
-    // TODO: Regex - test the case of FS = "[ \t\n]+" - which should _not_ strip leading spaces and add an integration test
-    // See https://www.gnu.org/software/gawk/manual/html_node/Regexp-Field-Splitting.html
-    #[test]
-    #[ignore]
-    fn it_splits_data_by_whitespace_via_regex() {
-        let test_data = "  Hello World  I\tam  someone! ";
-
-        let split_data = split_user_data(&"[ \t\n]+", test_data);
-
-        assert_eq!(split_data, vec!["  Hello", "World", "I", "am", "someone! "]);
+/// Reads data from a terminal to be processed by a user's program
+///
+/// # Arguments
+/// - `editor` the editor to read a line of data with
+///
+/// # Return value:
+/// - the data read from the terminal
+/// - `None` if the user inputs an end of file character
+/// - an error if the user inputs an interrupt character, or if for some reason a `Readline` error is returned by the
+///   call to `readline()`
+fn read_user_data_from_terminal(editor: &mut DefaultEditor) -> Result<Option<String>, Error> {
+    let data_input = editor.readline("");
+    match data_input {
+        Ok(data_line) => {
+            debug!("data line to process: {}", data_line);
+            Ok(Some(data_line))
+        }
+        Err(ReadlineError::Eof) => Ok(None),
+        Err(ReadlineError::Interrupted) => {
+            Err(Error::new(ErrorKind::Io, "interrupt received, exiting"))
+        }
+        Err(err) => Err(Error::new(
+            ErrorKind::Io,
+            format!("unable to read from the terminal: {:?}", err),
+        )),
     }
 }
@@ -1,15 +1,12 @@
 use clap::{parser::ValueSource, Arg, ArgAction, ArgMatches, Command};
 use env_logger::{Builder, Env};
 use log::LevelFilter;
+use rawk::assignment::Assignment;
 use rawk::runtime_config::RuntimeConfig;
-use std::error::Error;
-use std::fmt::Debug;
+use rawk::{Error, ErrorKind};
 use std::fs;
-
-#[derive(Debug)]
-enum TempAwkReadFileError {
-    FileDoesNotExist,
-}
+use std::io;
+use std::process;
 
 const PROGRAM_KEY: &str = "program";
 const PROGRAM_FILE_KEY: &str = "file";
@@ -17,8 +14,21 @@ const DATA_FILE: &str = "data_file";
 const QUICK_KEY: &str = "quick";
 const EVAL_KEY: &str = "eval";
 const FIELD_SEPARATOR_KEY: &str = "field_separator";
+const CSV_KEY: &str = "csv";
+const HEADER_KEY: &str = "header";
+const JSONL_KEY: &str = "jsonl";
+const NULL_DATA_KEY: &str = "null-data";
+const BYTES_KEY: &str = "bytes";
+const OCSV_KEY: &str = "ocsv";
+const OJSON_KEY: &str = "ojson";
+const ASSIGNMENT_KEY: &str = "assign";
+const NO_OPTIMIZE_KEY: &str = "no-optimize";
+/// The path of a program file that is read from STDIN
+const STDIN_PATH: &str = "-";
+/// The exit status used when a program cannot be compiled or run to completion, as other awk implementations do
+const EXIT_FAILURE: i32 = 2;
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
     Builder::from_env(Env::default().default_filter_or("info"))
         .format_timestamp(None)
         .filter_module("rustyline", LevelFilter::Error)
@@ -41,7 +51,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     if !has_program_literal && !has_program_file {
         // clap will add a newline to the rendered version string for us
         print!("{render_version}");
-        return Ok(());
+        return;
     }
 
     let field_separator = cmd_line_matches
@@ -49,6 +59,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         .map(|separator| separator.to_string())
         .unwrap_or_else(|| panic!("{} not configured for command line", FIELD_SEPARATOR_KEY));
 
+    let variable_assignments: Vec<Assignment> = cmd_line_matches
+        .get_many::<Assignment>(ASSIGNMENT_KEY)
+        .unwrap_or_default()
+        .cloned()
+        .collect();
+
     let is_eval = cmd_line_matches
         .value_source(EVAL_KEY)
         .unwrap_or_else(|| panic!("{} not configured for command line", EVAL_KEY))
@@ -57,6 +73,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         .value_source(QUICK_KEY)
         .unwrap_or_else(|| panic!("{} not configured for command line", QUICK_KEY))
         .eq(&ValueSource::CommandLine);
+    let is_optimized = !cmd_line_matches.get_flag(NO_OPTIMIZE_KEY);
 
     let mut data_file_paths: Vec<String> = if is_program_data_file {
         vec![cmd_line_matches
@@ -75,12 +92,64 @@ fn main() -> Result<(), Box<dyn Error>> {
             data_file_paths.push(data_file_path);
         });
 
-    let config: RuntimeConfig =
-        RuntimeConfig::new(data_file_paths, field_separator, is_eval, is_quick);
+    let mut config: RuntimeConfig = RuntimeConfig::new(
+        data_file_paths,
+        field_separator,
+        variable_assignments,
+        is_eval,
+        is_quick,
+        is_optimized,
+    );
+    config.is_csv = cmd_line_matches.get_flag(CSV_KEY);
+    config.has_header = cmd_line_matches.get_flag(HEADER_KEY);
+    config.is_jsonl = cmd_line_matches.get_flag(JSONL_KEY);
+    config.is_null_separated = cmd_line_matches.get_flag(NULL_DATA_KEY);
+    config.is_bytes = cmd_line_matches.get_flag(BYTES_KEY);
+    config.is_ocsv = cmd_line_matches.get_flag(OCSV_KEY);
+    config.is_ojson = cmd_line_matches.get_flag(OJSON_KEY);
 
-    let program = get_awk_program(&cmd_line_matches);
-    rawk::run_program(&program, config);
-    Ok(())
+    let result = get_awk_program(&cmd_line_matches)
+        .map_err(|errors| errors.iter().map(Error::to_string).collect())
+        .and_then(|program_files| {
+            let program: String = program_files
+                .iter()
+                .map(|program_file| program_file.contents.as_str())
+                .collect();
+            rawk::run_program(&program, config)
+                .or_else(|errors| {
+                    // a reader that stops reading output early, such as `head`, quietly stops the program as it does
+                    // other awks, rather than being reported
+                    if errors
+                        .iter()
+                        .all(|error| error.kind() == ErrorKind::BrokenPipe)
+                    {
+                        Ok(())
+                    } else {
+                        Err(errors)
+                    }
+                })
+                .map_err(|errors| {
+                    errors
+                        .into_iter()
+                        .map(|error| {
+                            // the snippet is taken from the concatenated program, before the error is attributed to a file
+                            let snippet = error.snippet(&program);
+                            let error = locate_in_program_files(error, &program_files);
+                            match snippet {
+                                Some(snippet) => format!("{}\n{}", error, snippet),
+                                None => error.to_string(),
+                            }
+                        })
+                        .collect::<Vec<String>>()
+                })
+        });
+
+    if let Err(diagnostics) = result {
+        for diagnostic in diagnostics {
+            eprintln!("{}: {}", env!("CARGO_PKG_NAME"), diagnostic);
+        }
+        process::exit(EXIT_FAILURE);
+    }
 }
 
 fn build_awk_cli_command() -> Command {
@@ -108,7 +177,6 @@ fn build_awk_cli_command() -> Command {
             Arg::new(DATA_FILE).index(2).num_args(1..),
         )
         .arg(
-            // TODO: Remove this when `BEGIN` is implemented. We could use -w, but this is quicker
             Arg::new(QUICK_KEY)
                 .short('q')
                 .long(QUICK_KEY)
@@ -131,6 +199,89 @@ fn build_awk_cli_command() -> Command {
                 .default_value(" ")
                 .help("Sets the field separator character/regex for parsing data"),
         )
+        .arg(
+            Arg::new(CSV_KEY)
+                .long(CSV_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Reads data as comma separated values, ignoring the field separator"),
+        )
+        .arg(
+            Arg::new(HEADER_KEY)
+                .long(HEADER_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Names fields after the first record of each data file, which is not run against"),
+        )
+        .arg(
+            Arg::new(JSONL_KEY)
+                .long(JSONL_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Reads each line of data as JSON, making its values available through the J array"),
+        )
+        .arg(
+            Arg::new(NULL_DATA_KEY)
+                .short('0')
+                .long(NULL_DATA_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Separates records by NUL bytes rather than newlines, as written by find -print0"),
+        )
+        .arg(
+            Arg::new(BYTES_KEY)
+                .short('b')
+                .long(BYTES_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Treats every byte as a character, rather than every UTF-8 encoded character"),
+        )
+        .arg(
+            Arg::new(OCSV_KEY)
+                .long(OCSV_KEY)
+                .action(ArgAction::SetTrue)
+                .conflicts_with(OJSON_KEY)
+                .help("Prints values as comma separated values, quoting them where needed"),
+        )
+        .arg(
+            Arg::new(OJSON_KEY)
+                .long(OJSON_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Prints values as a JSON array, or the fields of a record with a header as an object"),
+        )
+        .arg(
+            Arg::new(ASSIGNMENT_KEY)
+                .short('v')
+                .long(ASSIGNMENT_KEY)
+                .num_args(1)
+                .required(false)
+                .action(ArgAction::Append)
+                .value_name("var=value")
+                .value_parser(parse_assignment)
+                .help("Assigns a value to a variable before the program is run"),
+        )
+        .arg(
+            Arg::new(NO_OPTIMIZE_KEY)
+                .short('s')
+                .long(NO_OPTIMIZE_KEY)
+                .action(ArgAction::SetTrue)
+                .help("Runs the program without optimizing it, for debugging"),
+        )
+}
+
+/// Parses the value of a `-v var=value` argument
+///
+/// # Arguments
+/// - `argument` the value provided to the `-v` flag
+///
+/// # Return value
+/// - the parsed assignment
+/// - an error message if the argument is not a valid assignment
+fn parse_assignment(argument: &str) -> Result<Assignment, String> {
+    Assignment::parse(argument).ok_or_else(|| format!("invalid -v argument '{argument}'"))
+}
+
+/// A piece of an awk program, and the file it was read from
+struct ProgramFile {
+    /// the path of the file, or `None` if the program was provided on the command line
+    path: Option<String>,
+    /// the source code of the program
+    contents: String,
 }
 
 /// Retrieve an awk program from the command line
@@ -138,32 +289,83 @@ fn build_awk_cli_command() -> Command {
 /// A program can be provided as a single argument from the command line, or through one or more
 /// usages of the `-f progfile` flag, where `progfile` is the path to the awk program to run. if
 /// more than one instance of `-f progfile` is provided, each `progfile` shall be read in the order
-/// they are declared and concatenated to previously read `progfile`s
+/// they are declared and concatenated to previously read `progfile`s. a `progfile` of `-` is read
+/// from STDIN.
 ///
 /// # Arguments
 /// - `cmd_line_matches` the matched command line arguments provided by the user at runtime
 ///
 /// # Return value
-/// - The awk program to run
-fn get_awk_program(cmd_line_matches: &ArgMatches) -> String {
+/// - each piece of the awk program to run, in the order they are to be concatenated
+/// - an error if a `progfile` could not be read
+fn get_awk_program(cmd_line_matches: &ArgMatches) -> Result<Vec<ProgramFile>, Vec<Error>> {
     if let Some(provided_awk_filepaths) = cmd_line_matches.get_many::<String>(PROGRAM_FILE_KEY) {
         provided_awk_filepaths
             .map(|awk_filepath| {
-                // TODO: Support for '-' as a special filename
-                match fs::read_to_string(awk_filepath) {
-                    Ok(contents) => contents,
-                    Err(_) => panic!("{:?}", TempAwkReadFileError::FileDoesNotExist),
+                let program_contents = if awk_filepath == STDIN_PATH {
+                    io::read_to_string(io::stdin())
+                } else {
+                    fs::read_to_string(awk_filepath)
+                };
+                match program_contents {
+                    Ok(contents) => Ok(ProgramFile {
+                        path: Some(awk_filepath.clone()),
+                        contents,
+                    }),
+                    Err(err) => Err(vec![Error::new(
+                        ErrorKind::Io,
+                        format!("can't open file {}: {}", awk_filepath, err),
+                    )
+                    .with_file(awk_filepath.as_str())]),
                 }
             })
             .collect()
     } else {
-        cmd_line_matches
-            .get_one::<String>(PROGRAM_KEY)
-            .cloned()
-            .unwrap()
+        Ok(vec![ProgramFile {
+            path: None,
+            contents: cmd_line_matches
+                .get_one::<String>(PROGRAM_KEY)
+                .cloned()
+                .unwrap(),
+        }])
     }
 }
 
+/// Attributes an error found in the concatenated program to the program file that it was found in
+///
+/// # Arguments
+/// - `error` the error to attribute
+/// - `program_files` each piece of the program, in the order they were concatenated
+///
+/// # Return value
+/// the error, with its file set and its line made relative to the start of that file
+fn locate_in_program_files(error: Error, program_files: &[ProgramFile]) -> Error {
+    let Some(line) = error.line() else {
+        return error;
+    };
+
+    let mut start_line = 1;
+    for (index, program_file) in program_files.iter().enumerate() {
+        // pieces are concatenated as is, so a piece begins on the line that the previous one ended on
+        let end_line = start_line + program_file.contents.matches('\n').count();
+        let last_line = if program_file.contents.ends_with('\n') {
+            end_line - 1
+        } else {
+            end_line
+        };
+        // the end of the program is found after the last line of the last piece
+        if line <= last_line || index == program_files.len() - 1 {
+            let error = error.with_line(line - start_line + 1);
+            return match &program_file.path {
+                Some(path) => error.with_file(path.as_str()),
+                None => error,
+            };
+        }
+        start_line = end_line;
+    }
+    error
+}
+
 #[cfg(test)]
 mod cli {
     use crate::build_awk_cli_command;
@@ -173,3 +375,50 @@ mod cli {
         build_awk_cli_command().debug_assert();
     }
 }
+
+#[cfg(test)]
+mod program_files {
+    use super::*;
+
+    fn program_files() -> Vec<ProgramFile> {
+        vec![
+            ProgramFile {
+                path: Some(String::from("first.awk")),
+                contents: String::from("BEGIN {\nx = 1;\n}\n"),
+            },
+            ProgramFile {
+                path: Some(String::from("second.awk")),
+                contents: String::from("{ print x; }\nEND {"),
+            },
+        ]
+    }
+
+    #[test]
+    fn it_locates_an_error_in_the_first_program_file() {
+        let error = Error::new(ErrorKind::Syntax, "message").with_line(2);
+
+        let error = locate_in_program_files(error, &program_files());
+
+        assert_eq!(error.file(), Some("first.awk"));
+        assert_eq!(error.line(), Some(2));
+    }
+
+    #[test]
+    fn it_locates_an_error_relative_to_the_start_of_a_later_program_file() {
+        let error = Error::new(ErrorKind::Syntax, "message").with_line(5);
+
+        let error = locate_in_program_files(error, &program_files());
+
+        assert_eq!(error.file(), Some("second.awk"));
+        assert_eq!(error.line(), Some(2));
+    }
+
+    #[test]
+    fn it_does_not_locate_an_error_without_a_line() {
+        let error = Error::new(ErrorKind::Io, "message");
+
+        let error = locate_in_program_files(error, &program_files());
+
+        assert_eq!(error.file(), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Functions provided by a host application embedding r-awk, which may be called by awk programs
+
+use crate::builtin::Builtin;
+use crate::error::{Error, ErrorKind};
+use crate::value::Value;
+use std::sync::Arc;
+
+/// The signature of a native function, which receives the values of its arguments and returns a value
+///
+/// Native functions may be shared between threads, so that a program compiled with them may be run on any thread.
+pub type NativeFn = dyn Fn(&[Value]) -> Value + Send + Sync;
+
+/// A single native function, registered under a name
+#[derive(Clone)]
+struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Arc<NativeFn>,
+}
+
+/// A registry of native functions that a program may call
+///
+/// Native functions are registered before a program is compiled with
+/// [Program::compile_with_natives](crate::Program::compile_with_natives), so that calls to them can be recognized by
+/// the parser and the number of arguments given to each call checked at compile time:
+///
+/// ```
+/// use rawk::{Interpreter, NativeFunctions, Program, Value};
+///
+/// let mut natives = NativeFunctions::new();
+/// natives.register("double", 1, |args| Value::Number(args[0].num_value() * 2.0)).unwrap();
+///
+/// let program = Program::compile_with_natives("{ print double($1); }", &natives).unwrap();
+/// let mut output = Vec::new();
+/// Interpreter::new(program).run("21\n".as_bytes(), &mut output).unwrap();
+///
+/// assert_eq!(String::from_utf8(output).unwrap(), "42\n");
+/// ```
+#[derive(Clone, Default)]
+pub struct NativeFunctions {
+    functions: Vec<NativeFunction>,
+}
+
+impl NativeFunctions {
+    /// Constructs a new, empty `NativeFunctions`
+    pub fn new() -> NativeFunctions {
+        NativeFunctions::default()
+    }
+
+    /// Registers a native function, replacing any function previously registered under the same name
+    ///
+    /// A registered name is always treated as a call by the parser, so it may no longer be used as a variable
+    ///
+    /// # Arguments
+    /// - `name` the name the function is called by
+    /// - `arity` the number of arguments the function must be called with
+    /// - `function` the function to run, which receives the values of its arguments in the order they were given
+    ///
+    /// # Return value
+    /// - `Ok` if the function was registered
+    /// - an [Error] if the name is that of a built in function (e.g. `length`), which could never be called
+    pub fn register<F>(&mut self, name: &str, arity: usize, function: F) -> Result<(), Error>
+    where
+        F: Fn(&[Value]) -> Value + Send + Sync + 'static,
+    {
+        if Builtin::resolve(name).is_some() {
+            return Err(Error::new(
+                ErrorKind::Compile,
+                format!(
+                    "cannot register native function {}, as it is a built in function",
+                    name
+                ),
+            ));
+        }
+
+        let native = NativeFunction {
+            name: String::from(name),
+            arity,
+            function: Arc::new(function),
+        };
+        match self.resolve(name) {
+            Some((index, _)) => self.functions[index] = native,
+            None => self.functions.push(native),
+        }
+        Ok(())
+    }
+
+    /// Looks up a native function by its name
+    ///
+    /// # Arguments
+    /// - `name` the name of the function
+    ///
+    /// # Return value
+    /// - the index of the function and the number of arguments it must be called with
+    /// - `None` if no function has been registered under the name
+    pub(crate) fn resolve(&self, name: &str) -> Option<(usize, usize)> {
+        self.functions
+            .iter()
+            .position(|native| native.name == name)
+            .map(|index| (index, self.functions[index].arity))
+    }
+
+    /// Retrieves the number of arguments a native function must be called with
+    ///
+    /// # Arguments
+    /// - `index` the index of the function, as returned by [NativeFunctions::resolve]
+    pub(crate) fn arity(&self, index: usize) -> usize {
+        self.functions[index].arity
+    }
+
+    /// Calls a native function
+    ///
+    /// # Arguments
+    /// - `index` the index of the function, as returned by [NativeFunctions::resolve]
+    /// - `arguments` the values of the function's arguments
+    ///
+    /// # Return value
+    /// the value returned by the function
+    pub(crate) fn call(&self, index: usize, arguments: &[Value]) -> Value {
+        (self.functions[index].function)(arguments)
+    }
+}
+
+#[cfg(test)]
+mod registration {
+    use super::*;
+
+    #[test]
+    fn it_resolves_functions_in_the_order_they_were_registered() {
+        let mut natives = NativeFunctions::new();
+        natives
+            .register("first", 0, |_| Value::Number(1.0))
+            .unwrap();
+        natives
+            .register("second", 2, |_| Value::Number(2.0))
+            .unwrap();
+
+        assert_eq!(natives.resolve("first"), Some((0, 0)));
+        assert_eq!(natives.resolve("second"), Some((1, 2)));
+        assert_eq!(natives.resolve("third"), None);
+    }
+
+    #[test]
+    fn it_replaces_a_function_registered_under_the_same_name() {
+        let mut natives = NativeFunctions::new();
+        natives
+            .register("answer", 0, |_| Value::Number(1.0))
+            .unwrap();
+        natives
+            .register("answer", 1, |args| args[0].clone())
+            .unwrap();
+
+        assert_eq!(natives.resolve("answer"), Some((0, 1)));
+        assert_eq!(
+            natives.call(0, &[Value::String(String::from("42"))]),
+            Value::String(String::from("42"))
+        );
+    }
+
+    #[test]
+    fn it_rejects_the_name_of_a_built_in_function() {
+        let mut natives = NativeFunctions::new();
+        let error = natives
+            .register("length", 1, |_| Value::Number(0.0))
+            .expect_err("a built in function should not be replaced");
+
+        assert_eq!(
+            error.message(),
+            "cannot register native function length, as it is a built in function"
+        );
+        assert_eq!(natives.resolve("length"), None);
+    }
+}
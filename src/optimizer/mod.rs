@@ -0,0 +1,668 @@
+//! Optimizer for our implementation of awk.
+//!
+//! Rewrites the bytecode of a [Chunk] once it has been generated, so that less work is done each time it is run. The
+//! chunk is decoded into a list of instructions whose jumps refer to the instruction they go to, rather than a distance
+//! in bytes. A handful of peephole passes are then run over the list until none of them find anything left to change:
+//! - constant subexpressions are folded into a single constant - e.g. `2 * 3 + 1` becomes `7`
+//! - conditional jumps on a constant are removed or made unconditional, removing always-true pattern checks
+//! - a comparison followed by a conditional jump is fused into a single instruction
+//! - code that can never be reached, such as the code following an unconditional jump, is removed
+//!
+//! None of the passes make the code between a jump and the instruction it goes to any longer, so each jump keeps the
+//! width of its operand when the list is encoded back into a chunk.
+
+use crate::chunk::{Chunk, OpCode};
+use crate::value::Value;
+use crate::vm::operations;
+
+/// The operand of a decoded instruction
+#[derive(Clone, Debug, PartialEq)]
+enum Operand {
+    /// the instruction does not have an operand
+    None,
+    /// a number or string constant
+    Constant(Value),
+    /// the slot of a variable or array
+    Slot(u16),
+    /// the index of the instruction that a jump goes to
+    Target(usize),
+    /// the comparison of a comparison fused with a jump, and the index of the instruction that it jumps to
+    CompareTarget(OpCode, usize),
+}
+
+/// A single instruction of a chunk, decoded from its bytes
+#[derive(Clone, Debug, PartialEq)]
+struct Instruction {
+    op_code: OpCode,
+    operand: Operand,
+    line: i32,
+}
+
+impl Instruction {
+    /// Constructs a new instruction that pushes a constant onto the stack
+    ///
+    /// # Arguments
+    /// - `value` the constant to push
+    /// - `line` the line to associate the instruction with
+    fn constant(value: Value, line: i32) -> Instruction {
+        let op_code = match value {
+            Value::Number(_) => OpCode::NumberConstant,
+            Value::String(_) => OpCode::StringConstant,
+            Value::StrNum(_) => unreachable!("Constants are never strnums"),
+        };
+        Instruction {
+            op_code,
+            operand: Operand::Constant(value),
+            line,
+        }
+    }
+
+    /// Retrieves the constant that an instruction pushes onto the stack, if it pushes one
+    fn constant_value(&self) -> Option<&Value> {
+        match &self.operand {
+            Operand::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the index of the instruction that a jump goes to, if the instruction is a jump
+    fn target(&self) -> Option<usize> {
+        match self.operand {
+            Operand::Target(target) | Operand::CompareTarget(_, target) => Some(target),
+            _ => None,
+        }
+    }
+}
+
+/// Optimizes the code of a chunk
+///
+/// # Arguments
+/// - `chunk` the chunk to optimize
+///
+/// # Return value
+/// - a new chunk, made up of the optimized code
+/// - `None` if the optimized code cannot be encoded, in which case the original chunk should be used as is
+pub fn optimize(chunk: &Chunk) -> Option<Chunk> {
+    let mut instructions = decode(chunk);
+
+    loop {
+        let mut changed = rewrite(&mut instructions, fold_constants);
+        changed |= rewrite(&mut instructions, remove_constant_jumps);
+        changed |= rewrite(&mut instructions, fuse_comparisons);
+        changed |= rewrite(&mut instructions, remove_dead_code);
+        if !changed {
+            break;
+        }
+    }
+
+    encode(&instructions)
+}
+
+/// Decodes the bytes of a chunk into a list of instructions
+///
+/// # Arguments
+/// - `chunk` the chunk to decode
+fn decode(chunk: &Chunk) -> Vec<Instruction> {
+    // the offset of every instruction, and the offset that its jump goes to (if it is a jump)
+    let mut decoded: Vec<(usize, Instruction, Option<usize>)> = vec![];
+    let mut offset = 0;
+    while offset < chunk.code.len() {
+        let op_code = OpCode::from_byte(chunk.code[offset]);
+        let next_offset = offset + 1 + op_code.operand_width();
+        let short = || chunk.read_short(offset + 1) as usize;
+
+        let (operand, jump_offset) = match op_code {
+            OpCode::NumberConstant => (
+                Operand::Constant(Value::Number(chunk.numbers[short()])),
+                None,
+            ),
+            OpCode::StringConstant => (
+                Operand::Constant(Value::String(chunk.strings[short()].clone())),
+                None,
+            ),
+            OpCode::JumpIfFalse | OpCode::Jump | OpCode::JumpIfTrue => {
+                (Operand::None, Some(next_offset + short()))
+            }
+            OpCode::JumpIfFalseWide | OpCode::JumpWide | OpCode::JumpIfTrueWide => (
+                Operand::None,
+                Some(next_offset + chunk.read_long(offset + 1) as usize),
+            ),
+            OpCode::Loop => (Operand::None, Some(next_offset - short())),
+            OpCode::LoopWide => (
+                Operand::None,
+                Some(next_offset - chunk.read_long(offset + 1) as usize),
+            ),
+            OpCode::CompareJumpIfFalse => (
+                Operand::CompareTarget(OpCode::from_byte(chunk.code[offset + 1]), 0),
+                Some(next_offset + chunk.read_short(offset + 2) as usize),
+            ),
+            _ if op_code.operand_width() == 2 => (Operand::Slot(short() as u16), None),
+            _ => (Operand::None, None),
+        };
+
+        let line = chunk.line(offset);
+        decoded.push((
+            offset,
+            Instruction {
+                op_code,
+                operand,
+                line,
+            },
+            jump_offset,
+        ));
+        offset = next_offset;
+    }
+
+    // resolve the offset that each jump goes to into the index of the instruction found there. a jump may go to the
+    // very end of the chunk, which is the index following the last instruction
+    let index_of = |jump_offset: usize| {
+        decoded
+            .binary_search_by_key(&jump_offset, |(offset, _, _)| *offset)
+            .unwrap_or(decoded.len())
+    };
+    let targets: Vec<Option<usize>> = decoded
+        .iter()
+        .map(|(_, _, jump_offset)| jump_offset.map(index_of))
+        .collect();
+
+    decoded
+        .into_iter()
+        .zip(targets)
+        .map(|((_, mut instruction, _), target)| {
+            if let Some(target) = target {
+                instruction.operand = match instruction.operand {
+                    Operand::CompareTarget(comparison, _) => {
+                        Operand::CompareTarget(comparison, target)
+                    }
+                    _ => Operand::Target(target),
+                };
+            }
+            instruction
+        })
+        .collect()
+}
+
+/// Encodes a list of instructions into a new chunk
+///
+/// # Arguments
+/// - `instructions` the instructions to encode
+///
+/// # Return value
+/// - the chunk the instructions were encoded into
+/// - `None` if an operand of an instruction does not fit in the width of its operand
+fn encode(instructions: &[Instruction]) -> Option<Chunk> {
+    let mut offsets = Vec::with_capacity(instructions.len() + 1);
+    let mut offset = 0;
+    for instruction in instructions {
+        offsets.push(offset);
+        offset += 1 + instruction.op_code.operand_width();
+    }
+    offsets.push(offset);
+
+    let mut chunk = Chunk::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        let line = instruction.line;
+        let next_offset = offsets[index + 1];
+        chunk.write_op_code(instruction.op_code, line);
+
+        match &instruction.operand {
+            Operand::None => {}
+            Operand::Constant(value) => {
+                let index = match value {
+                    Value::String(string) => chunk.add_string(string.clone()),
+                    _ => chunk.add_number(value.num_value()),
+                };
+                chunk.write_short(u16::try_from(index).ok()?, line);
+            }
+            Operand::Slot(slot) => chunk.write_short(*slot, line),
+            Operand::Target(target) => {
+                let distance = match instruction.op_code {
+                    OpCode::Loop | OpCode::LoopWide => next_offset.checked_sub(offsets[*target]),
+                    _ => offsets[*target].checked_sub(next_offset),
+                }?;
+                if instruction.op_code.operand_width() == 4 {
+                    chunk.write_long(u32::try_from(distance).ok()?, line);
+                } else {
+                    chunk.write_short(u16::try_from(distance).ok()?, line);
+                }
+            }
+            Operand::CompareTarget(comparison, target) => {
+                let distance = offsets[*target].checked_sub(next_offset)?;
+                chunk.write_byte(*comparison as u8, line);
+                chunk.write_short(u16::try_from(distance).ok()?, line);
+            }
+        }
+    }
+
+    Some(chunk)
+}
+
+/// A pass over a list of instructions
+///
+/// A pass is given every instruction, along with whether each instruction is the target of a jump. It may replace any
+/// instruction, or remove it by setting it to `None`, returning whether it changed anything.
+type Pass = fn(&mut [Option<Instruction>], &[bool]) -> bool;
+
+/// Runs a pass over a list of instructions
+///
+/// Jumps to an instruction that the pass removed are pointed at the next instruction that was kept.
+///
+/// # Arguments
+/// - `instructions` the instructions to run the pass over
+/// - `pass` the pass to run
+///
+/// # Return value
+/// whether the pass changed any instruction
+fn rewrite(instructions: &mut Vec<Instruction>, pass: Pass) -> bool {
+    let mut is_target = vec![false; instructions.len() + 1];
+    for target in instructions.iter().filter_map(Instruction::target) {
+        is_target[target] = true;
+    }
+
+    let mut slots: Vec<Option<Instruction>> = instructions.drain(..).map(Some).collect();
+    let changed = pass(&mut slots, &is_target);
+
+    // the index of each instruction once the removed instructions are gone
+    let mut remap = Vec::with_capacity(slots.len() + 1);
+    let mut kept = 0;
+    for slot in &slots {
+        remap.push(kept);
+        if slot.is_some() {
+            kept += 1;
+        }
+    }
+    remap.push(kept);
+
+    instructions.extend(slots.into_iter().flatten().map(|mut instruction| {
+        instruction.operand = match instruction.operand {
+            Operand::Target(target) => Operand::Target(remap[target]),
+            Operand::CompareTarget(comparison, target) => {
+                Operand::CompareTarget(comparison, remap[target])
+            }
+            operand => operand,
+        };
+        instruction
+    }));
+
+    changed
+}
+
+/// Folds operations on constants into the constant that they produce
+///
+/// An operation is only folded when no jump goes to the middle of it.
+fn fold_constants(slots: &mut [Option<Instruction>], is_target: &[bool]) -> bool {
+    let mut changed = false;
+    let mut index = 0;
+    while index < slots.len() {
+        let (Some(first), second, third) = (
+            &slots[index],
+            slots.get(index + 1).and_then(Option::as_ref),
+            slots.get(index + 2).and_then(Option::as_ref),
+        ) else {
+            index += 1;
+            continue;
+        };
+
+        if let (Some(a), Some(b), Some(operation)) = (
+            first.constant_value(),
+            second.and_then(Instruction::constant_value),
+            third,
+        ) {
+            let folded = match operation.op_code {
+                OpCode::Add
+                | OpCode::Subtract
+                | OpCode::Multiply
+                | OpCode::Divide
+                | OpCode::Modulus
+                | OpCode::Exponentiation => Some(operations::arithmetic(&operation.op_code, a, b)),
+                OpCode::GreaterEqual
+                | OpCode::Greater
+                | OpCode::LessEqual
+                | OpCode::Less
+                | OpCode::DoubleEqual
+                | OpCode::NotEqual => Some(operations::comparison(&operation.op_code, a, b)),
+                OpCode::Concatenate => Some(operations::concatenation(a, b)),
+                _ => None,
+            };
+            let line = operation.line;
+            if let Some(folded) = folded.filter(|_| !is_target[index + 1] && !is_target[index + 2])
+            {
+                slots[index] = Some(Instruction::constant(folded, line));
+                slots[index + 1] = None;
+                slots[index + 2] = None;
+                changed = true;
+                index += 3;
+                continue;
+            }
+        }
+
+        if let (Some(a), Some(operation)) = (first.constant_value(), second) {
+            let is_unary = matches!(
+                operation.op_code,
+                OpCode::UnaryPlus | OpCode::UnaryMinus | OpCode::LogicalNot
+            );
+            if is_unary && !is_target[index + 1] {
+                let folded = operations::unary(&operation.op_code, a);
+                let line = operation.line;
+                slots[index] = Some(Instruction::constant(folded, line));
+                slots[index + 1] = None;
+                changed = true;
+                index += 2;
+                continue;
+            }
+        }
+
+        index += 1;
+    }
+    changed
+}
+
+/// Removes conditional jumps on a constant, which either never jump or always jump, along with constants that are
+/// popped off of the stack as soon as they are pushed and jumps to the instruction that follows them
+fn remove_constant_jumps(slots: &mut [Option<Instruction>], is_target: &[bool]) -> bool {
+    let mut changed = false;
+    let mut index = 0;
+    while index < slots.len() {
+        let Some(first) = &slots[index] else {
+            index += 1;
+            continue;
+        };
+
+        if matches!(first.op_code, OpCode::Jump | OpCode::JumpWide)
+            && first.target() == Some(index + 1)
+        {
+            slots[index] = None;
+            changed = true;
+            index += 1;
+            continue;
+        }
+
+        let (Some(value), Some(Some(second))) = (first.constant_value(), slots.get(index + 1))
+        else {
+            index += 1;
+            continue;
+        };
+        if is_target[index + 1] {
+            index += 1;
+            continue;
+        }
+
+        // the conditional jumps leave their condition on the stack, so the constant is kept
+        let (op_code, line) = (second.op_code, first.line);
+        let jumps = match op_code {
+            OpCode::JumpIfFalse | OpCode::JumpIfFalseWide => Some(!value.truthy_value()),
+            OpCode::JumpIfTrue | OpCode::JumpIfTrueWide => Some(value.num_value() != 0.0),
+            _ => None,
+        };
+        match jumps {
+            Some(false) => {
+                slots[index + 1] = None;
+            }
+            Some(true) => {
+                // jumping when true replaces the condition with a one
+                if matches!(op_code, OpCode::JumpIfTrue | OpCode::JumpIfTrueWide) {
+                    slots[index] = Some(Instruction::constant(Value::Number(1.0), line));
+                }
+                let jump = slots[index + 1].as_mut().unwrap();
+                jump.op_code = match op_code.operand_width() {
+                    4 => OpCode::JumpWide,
+                    _ => OpCode::Jump,
+                };
+            }
+            None if op_code == OpCode::Pop => {
+                slots[index] = None;
+                slots[index + 1] = None;
+            }
+            None => {
+                index += 1;
+                continue;
+            }
+        }
+        changed = true;
+        index += 2;
+    }
+    changed
+}
+
+/// Fuses a comparison followed by a jump when it is false into a single instruction
+///
+/// Both the instruction following the jump and the instruction it goes to must pop the result of the comparison off
+/// the stack, so that the fused instruction may skip them both.
+fn fuse_comparisons(slots: &mut [Option<Instruction>], is_target: &[bool]) -> bool {
+    let mut changed = false;
+    let mut index = 0;
+    while index + 2 < slots.len() {
+        let fusable = match (&slots[index], &slots[index + 1], &slots[index + 2]) {
+            (Some(comparison), Some(jump), Some(pop)) => {
+                matches!(
+                    comparison.op_code,
+                    OpCode::GreaterEqual
+                        | OpCode::Greater
+                        | OpCode::LessEqual
+                        | OpCode::Less
+                        | OpCode::DoubleEqual
+                        | OpCode::NotEqual
+                ) && jump.op_code == OpCode::JumpIfFalse
+                    && pop.op_code == OpCode::Pop
+                    && !is_target[index + 1]
+                    && !is_target[index + 2]
+                    && jump.target().map_or(false, |target| {
+                        matches!(&slots.get(target), Some(Some(popped)) if popped.op_code == OpCode::Pop)
+                    })
+            }
+            _ => false,
+        };
+        if !fusable {
+            index += 1;
+            continue;
+        }
+
+        let comparison = slots[index].take().unwrap();
+        let target = slots[index + 1].take().unwrap().target().unwrap();
+        slots[index + 2] = None;
+        slots[index] = Some(Instruction {
+            op_code: OpCode::CompareJumpIfFalse,
+            operand: Operand::CompareTarget(comparison.op_code, target + 1),
+            line: comparison.line,
+        });
+        changed = true;
+        index += 3;
+    }
+    changed
+}
+
+/// Removes the instructions following an unconditional jump or a return, up until the next instruction that is jumped
+/// to, as they can never be run
+fn remove_dead_code(slots: &mut [Option<Instruction>], is_target: &[bool]) -> bool {
+    let mut changed = false;
+    let mut is_reachable = true;
+    for (index, slot) in slots.iter_mut().enumerate() {
+        if is_target[index] {
+            is_reachable = true;
+        }
+        let Some(instruction) = slot else {
+            continue;
+        };
+
+        if !is_reachable {
+            *slot = None;
+            changed = true;
+            continue;
+        }
+        is_reachable = !matches!(
+            instruction.op_code,
+            OpCode::Jump | OpCode::JumpWide | OpCode::Loop | OpCode::LoopWide | OpCode::OpReturn
+        );
+    }
+    changed
+}
+
+#[cfg(test)]
+mod peephole {
+    use super::*;
+
+    /// Builds a chunk from a list of op codes, each followed by the two byte wide operand given with it (if any)
+    fn chunk_of(instructions: &[(OpCode, Option<u16>)]) -> Chunk {
+        let mut chunk = Chunk::new();
+        for (op_code, operand) in instructions {
+            chunk.write_op_code(*op_code, 1);
+            if let Some(operand) = operand {
+                chunk.write_short(*operand, 1);
+            }
+        }
+        chunk
+    }
+
+    /// Retrieves the op codes of an optimized chunk
+    fn op_codes(chunk: &Chunk) -> Vec<OpCode> {
+        decode(chunk)
+            .into_iter()
+            .map(|instruction| instruction.op_code)
+            .collect()
+    }
+
+    #[test]
+    fn it_folds_nested_arithmetic() {
+        let mut chunk = Chunk::new();
+        for number in [2.0, 3.0] {
+            let index = chunk.add_number(number) as u16;
+            chunk.write_op_code(OpCode::NumberConstant, 1);
+            chunk.write_short(index, 1);
+        }
+        chunk.write_op_code(OpCode::Multiply, 1);
+        let one = chunk.add_number(1.0) as u16;
+        chunk.write_op_code(OpCode::NumberConstant, 1);
+        chunk.write_short(one, 1);
+        chunk.write_op_code(OpCode::Add, 1);
+        chunk.write_op_code(OpCode::OpPrint, 1);
+        chunk.write_short(1, 1);
+        chunk.write_op_code(OpCode::OpReturn, 1);
+
+        let optimized = optimize(&chunk).unwrap();
+
+        assert_eq!(
+            op_codes(&optimized),
+            vec![OpCode::NumberConstant, OpCode::OpPrint, OpCode::OpReturn]
+        );
+        assert_eq!(optimized.numbers, vec![7.0]);
+    }
+
+    #[test]
+    fn it_folds_string_concatenation() {
+        let mut chunk = Chunk::new();
+        for string in ["a", "b"] {
+            let index = chunk.add_string(String::from(string)) as u16;
+            chunk.write_op_code(OpCode::StringConstant, 1);
+            chunk.write_short(index, 1);
+        }
+        chunk.write_op_code(OpCode::Concatenate, 1);
+        chunk.write_op_code(OpCode::OpPrint, 1);
+        chunk.write_short(1, 1);
+        chunk.write_op_code(OpCode::OpReturn, 1);
+
+        let optimized = optimize(&chunk).unwrap();
+
+        assert_eq!(
+            op_codes(&optimized),
+            vec![OpCode::StringConstant, OpCode::OpPrint, OpCode::OpReturn]
+        );
+        assert_eq!(optimized.strings, vec![String::from("ab")]);
+    }
+
+    #[test]
+    fn it_does_not_fold_an_operation_that_is_jumped_into() {
+        let mut chunk = Chunk::new();
+        let index = chunk.add_number(1.0) as u16;
+        chunk.write_op_code(OpCode::NumberConstant, 1);
+        chunk.write_short(index, 1);
+        chunk.write_op_code(OpCode::JumpIfFalse, 1);
+        chunk.write_short(3, 1);
+        chunk.write_op_code(OpCode::NumberConstant, 1);
+        chunk.write_short(index, 1);
+        // the jump goes to the addition, which adds to whatever else is on the stack
+        chunk.write_op_code(OpCode::Add, 1);
+        chunk.write_op_code(OpCode::OpPrint, 1);
+        chunk.write_short(1, 1);
+        chunk.write_op_code(OpCode::OpReturn, 1);
+
+        let instructions = decode(&chunk);
+        let mut folded = instructions.clone();
+        rewrite(&mut folded, fold_constants);
+
+        assert_eq!(folded, instructions);
+    }
+
+    #[test]
+    fn it_removes_an_always_true_pattern_check() {
+        // `{ print; }`, as generated for a rule without a pattern
+        let mut chunk = chunk_of(&[
+            (OpCode::NumberConstant, Some(0)),
+            (OpCode::JumpIfFalse, Some(7)),
+            (OpCode::Pop, None),
+            (OpCode::OpPrint, Some(0)),
+            (OpCode::Jump, Some(1)),
+            (OpCode::Pop, None),
+            (OpCode::OpReturn, None),
+        ]);
+        chunk.add_number(1.0);
+
+        let optimized = optimize(&chunk).unwrap();
+
+        assert_eq!(
+            op_codes(&optimized),
+            vec![OpCode::OpPrint, OpCode::OpReturn]
+        );
+    }
+
+    #[test]
+    fn it_fuses_a_comparison_and_jump() {
+        // `while (x < 3) x = x + 1;`
+        let mut chunk = chunk_of(&[
+            (OpCode::GetGlobal, Some(0)),
+            (OpCode::NumberConstant, Some(0)),
+            (OpCode::Less, None),
+            (OpCode::JumpIfFalse, Some(14)),
+            (OpCode::Pop, None),
+            (OpCode::GetGlobal, Some(0)),
+            (OpCode::NumberConstant, Some(1)),
+            (OpCode::Add, None),
+            (OpCode::DefineGlobal, Some(0)),
+            (OpCode::Loop, Some(24)),
+            (OpCode::Pop, None),
+            (OpCode::OpReturn, None),
+        ]);
+        chunk.add_number(3.0);
+        chunk.add_number(1.0);
+
+        let optimized = optimize(&chunk).unwrap();
+
+        assert_eq!(
+            op_codes(&optimized),
+            vec![
+                OpCode::GetGlobal,
+                OpCode::NumberConstant,
+                OpCode::CompareJumpIfFalse,
+                OpCode::GetGlobal,
+                OpCode::NumberConstant,
+                OpCode::Add,
+                OpCode::DefineGlobal,
+                OpCode::Loop,
+                OpCode::OpReturn
+            ]
+        );
+        // the fused jump goes to the return, and the loop goes back to the start of the chunk
+        assert_eq!(optimized.code[7..10], [OpCode::Less as u8, 0, 13]);
+        assert_eq!(optimized.read_short(21), 23);
+    }
+
+    #[test]
+    fn it_removes_code_after_an_unconditional_jump() {
+        let chunk = chunk_of(&[
+            (OpCode::Jump, Some(1)),
+            (OpCode::Pop, None),
+            (OpCode::OpReturn, None),
+        ]);
+
+        assert_eq!(op_codes(&optimize(&chunk).unwrap()), vec![OpCode::OpReturn]);
+    }
+}
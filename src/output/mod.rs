@@ -0,0 +1,177 @@
+//! Serialization of the values written by `print` statements
+
+use crate::record::Record;
+use crate::value::Value;
+
+/// The ways that the values given to a `print` statement may be written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum OutputFormat {
+    /// values are separated by a space
+    #[default]
+    Text,
+    /// values are separated by commas, and quoted as described by RFC 4180 where needed
+    Csv,
+    /// values are written as a JSON array
+    Json,
+}
+
+impl OutputFormat {
+    /// Formats the values given to a `print` statement as a single line of output, without its trailing newline
+    ///
+    /// # Arguments
+    /// - `values` the values to print, in the order they were given
+    pub(crate) fn format_values(&self, values: &[Value]) -> String {
+        match self {
+            OutputFormat::Text => {
+                let values: Vec<String> = values.iter().map(Value::str_value).collect();
+                values.join(" ")
+            }
+            OutputFormat::Csv => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| csv_quote(&value.str_value()))
+                    .collect();
+                values.join(",")
+            }
+            OutputFormat::Json => {
+                let values: Vec<String> = values.iter().map(json_value).collect();
+                format!("[{}]", values.join(","))
+            }
+        }
+    }
+
+    /// Formats a record as a single line of output, as printed by a `print` statement without any values
+    ///
+    /// As text, the record is written as it was read. Otherwise, each of its fields is written as a value, and as JSON
+    /// a record whose fields are named by a header is written as an object keyed by the names of its fields.
+    ///
+    /// # Arguments
+    /// - `record` the record to format
+    pub(crate) fn format_record(&self, record: &Record) -> String {
+        match (self, record.header()) {
+            (OutputFormat::Text, _) => record
+                .field(0)
+                .map_or_else(String::new, |text| text.str_value()),
+            (OutputFormat::Json, Some(header)) => {
+                let mut names: Vec<(&String, &usize)> = header.iter().collect();
+                names.sort_by_key(|(_, index)| **index);
+                let members: Vec<String> = names
+                    .into_iter()
+                    .map(|(name, index)| {
+                        let value = record
+                            .field(*index)
+                            .unwrap_or_else(|| Value::String(String::from("")));
+                        format!("{}:{}", json_string(name), json_value(&value))
+                    })
+                    .collect();
+                format!("{{{}}}", members.join(","))
+            }
+            _ => self.format_values(&record.fields()),
+        }
+    }
+}
+
+/// Quotes a value as a field of comma separated values, if it contains a comma, a double quote or a line break
+///
+/// # Arguments
+/// - `text` the text of the value
+fn csv_quote(text: &str) -> String {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
+
+/// Formats a value as JSON
+///
+/// Numbers that were computed by the program are written as JSON numbers, while everything else (including fields that
+/// look like numbers, whose text may not be a valid JSON number) is written as a JSON string
+///
+/// # Arguments
+/// - `value` the value to format
+fn json_value(value: &Value) -> String {
+    match value {
+        Value::Number(number) if number.is_finite() => value.str_value(),
+        _ => json_string(&value.str_value()),
+    }
+}
+
+/// Formats text as a JSON string, escaping it as needed
+///
+/// # Arguments
+/// - `text` the text to format
+fn json_string(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+#[cfg(test)]
+mod output_formats {
+    use super::*;
+    use crate::record::FieldSplitter;
+    use crate::value::StrNum;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn values() -> Vec<Value> {
+        vec![
+            Value::String(String::from("Smith, \"J\"")),
+            Value::Number(42.0),
+            Value::StrNum(StrNum::from("007")),
+        ]
+    }
+
+    #[test]
+    fn it_separates_text_with_spaces() {
+        assert_eq!(
+            OutputFormat::Text.format_values(&values()),
+            "Smith, \"J\" 42 007"
+        );
+    }
+
+    #[test]
+    fn it_quotes_csv_values_only_where_needed() {
+        assert_eq!(
+            OutputFormat::Csv.format_values(&values()),
+            "\"Smith, \"\"J\"\"\",42,007"
+        );
+        assert_eq!(
+            OutputFormat::Csv.format_values(&[Value::String(String::from("two\nlines"))]),
+            "\"two\nlines\""
+        );
+    }
+
+    #[test]
+    fn it_writes_json_numbers_only_for_computed_numbers() {
+        assert_eq!(
+            OutputFormat::Json.format_values(&values()),
+            r#"["Smith, \"J\"",42,"007"]"#
+        );
+        assert_eq!(
+            OutputFormat::Json.format_values(&[Value::Number(f32::INFINITY)]),
+            r#"["inf"]"#
+        );
+    }
+
+    #[test]
+    fn it_formats_the_fields_of_a_record() {
+        let record = Record::new(String::from("a  b,c"), FieldSplitter::default());
+
+        assert_eq!(OutputFormat::Text.format_record(&record), "a  b,c");
+        assert_eq!(OutputFormat::Csv.format_record(&record), "a,\"b,c\"");
+        assert_eq!(OutputFormat::Json.format_record(&record), r#"["a","b,c"]"#);
+    }
+
+    #[test]
+    fn it_formats_a_record_with_a_header_as_a_json_object() {
+        let header: HashMap<String, usize> =
+            HashMap::from([(String::from("name"), 2), (String::from("id"), 1)]);
+        let record = Record::new(String::from("7 alice"), FieldSplitter::default())
+            .with_header(Arc::new(header));
+
+        assert_eq!(
+            OutputFormat::Json.format_record(&record),
+            r#"{"id":"7","name":"alice"}"#
+        );
+    }
+}
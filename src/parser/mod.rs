@@ -1,19 +1,24 @@
 //! Parser for our implementation of awk.
 //!
 //! The parser is implemented as a Pratt Parser, and is heavily modeled after the one given in
-//! "Crafting Interpreters" in the second half of the book.
+//! "Crafting Interpreters" in the second half of the book. Rather than emitting bytecode directly,
+//! it produces an [Ast] that is handed off to the [CodeGenerator](crate::codegen::CodeGenerator).
 
 mod associativity;
 mod parse_rules;
 mod precedence;
 
-use crate::chunk::{Chunk, OpCode};
+use crate::ast::{
+    AssignmentOperator, Ast, BinaryOperator, Expr, ExprKind, LogicalOperator, Pattern, Rule, Span,
+    Stmt, StmtKind, UnaryOperator,
+};
+use crate::builtin::Builtin;
+use crate::error::{Error, ErrorKind};
+use crate::native::NativeFunctions;
 use crate::parser::associativity::Associativity;
 use crate::parser::precedence::Precedence;
 use crate::token::token_type::TokenType;
 use crate::token::Token;
-use crate::value::Value;
-use log::error;
 use std::slice::Iter;
 
 /// A Pratt Parser for our awk implementation
@@ -21,15 +26,17 @@ use std::slice::Iter;
 /// The `Parser` holds some stateful information in addition to the tokens it must iterate over:
 /// - `current_token` a reference to the current token being examined
 /// - `previous_token` a reference to the token that preceded the current token being examined
+/// - `loop_depth` the number of loops surrounding the statement currently being parsed
+/// - `errors` each error reported while parsing
+/// - `natives` the native functions that the program may call
 pub struct Parser<'a> {
     current_token: Option<&'a Token>,
     previous_token: Option<&'a Token>,
     tokens_iter: Iter<'a, Token>,
-    compiling_chunk: &'a mut Chunk,
-    had_error: bool,
+    natives: &'a NativeFunctions,
+    errors: Vec<Error>,
     panic_mode: bool,
-    inner_most_loop_start: i32,
-    inner_most_loop_end: i32,
+    loop_depth: usize,
 }
 
 impl<'a> Parser<'a> {
@@ -37,91 +44,89 @@ impl<'a> Parser<'a> {
     ///
     /// # Arguments
     /// - `tokens` a slice iterator of [Token]s
-    pub fn new(tokens: Iter<'a, Token>, compiling_chunk: &'a mut Chunk) -> Parser<'a> {
+    /// - `natives` the native functions that the program may call
+    pub fn new(tokens: Iter<'a, Token>, natives: &'a NativeFunctions) -> Parser<'a> {
         Parser {
             current_token: None,
             previous_token: None,
             tokens_iter: tokens,
-            compiling_chunk,
-            had_error: false,
+            natives,
+            errors: vec![],
             panic_mode: false,
-            inner_most_loop_start: -1,
-            inner_most_loop_end: -1,
+            loop_depth: 0,
         }
     }
 
     /// Entrypoint for parsing tokens.
     ///
     /// # Return value
-    /// - `true` if the program was parsed successfully
-    /// - `false` otherwise
-    pub fn parse(&mut self) -> bool {
+    /// - the tree of the program if it was parsed successfully
+    /// - each error that was reported otherwise
+    pub fn parse(&mut self) -> Result<Ast, Vec<Error>> {
         // prime the pump, so that the `current_token` is defined
         self.advance();
 
+        let mut rules = vec![];
         while !self.match_token(&TokenType::Eof) {
-            self.parse_pattern_action();
+            rules.push(self.parse_pattern_action());
+            if self.panic_mode {
+                self.synchronize_rule();
+            }
         }
 
-        self.end_compiler();
-
-        !self.had_error
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        Ok(Ast { rules })
     }
 
     /// Parse a single pattern-action
-    fn parse_pattern_action(&mut self) {
-        self.parse_pattern();
-        self.parse_action();
+    fn parse_pattern_action(&mut self) -> Rule {
+        let start = self.current();
+        let pattern = self.parse_pattern();
+        let action = self.parse_action();
+
+        Rule {
+            pattern,
+            action,
+            span: self.span_from(start),
+        }
     }
 
     /// Parse a single pattern
-    fn parse_pattern(&mut self) {
+    fn parse_pattern(&mut self) -> Pattern {
         if self.match_token(&TokenType::Begin) {
-            // BEGIN has been parsed, now handle the block
-            self.emit_true();
-            panic!("TODO: Implement BEGIN support");
+            if !self.peek_token(&TokenType::LeftCurly) {
+                self.error_at_current("Expect '{' after BEGIN.");
+            }
+            Pattern::Begin
         } else if self.match_token(&TokenType::End) {
-            // END has been parsed, now handle the block
-            self.emit_true();
-            panic!("TODO: Implement END support");
+            if !self.peek_token(&TokenType::LeftCurly) {
+                self.error_at_current("Expect '{' after END.");
+            }
+            Pattern::End
         } else if self.peek_token(&TokenType::LeftCurly) {
-            // we've run into an action earlier than we thought, emit true so we always run the action that will follow
-            self.emit_true();
+            // we've run into an action earlier than we thought, the action that will follow always runs
+            Pattern::Always
         } else {
             // we have a pattern to parse
-            self.expression();
             // TODO: Support multiple patterns - https://www.gnu.org/software/gawk/manual/html_node/Ranges.html
+            Pattern::Expression(self.expression())
         }
     }
 
-    /// Emits a number one ('1') for the [TokenType::Number] token type
-    fn emit_true(&mut self) {
-        self.emit_constant(Value::Number(1.0));
-    }
-
     /// Parse a single action
-    fn parse_action(&mut self) {
-        // emit a jump instruction as a placeholder to skip over the 'action' associated with the action in the
-        // event the pattern condition is false (if one exists). we'll backpatch it soon with the correct offset.
-        let pattern_false_jump = self.emit_jump(OpCode::JumpIfFalse(0xff, 0xff));
-
+    ///
+    /// # Return value
+    /// - the statements of the action
+    /// - `None` if no action was written, in which case the action implicitly becomes 'print $0'
+    fn parse_action(&mut self) -> Option<Vec<Stmt>> {
         if self.match_token(&TokenType::LeftCurly) {
             // parse the contents of the action
-            self.block();
+            Some(self.block())
         } else {
-            // no pattern - the action implicitly becomes 'print $0'
-            self.emit_constant(Value::Number(0.0));
-            self.emit_byte(OpCode::GetFieldVariable());
-            self.emit_byte(OpCode::OpPrint);
-            self.emit_byte(OpCode::Pop);
+            None
         }
-
-        // we've passed through the action successfully, backpatch the jump that was emitted for the block
-        self.patch_jump(pattern_false_jump);
-
-        // always pop the pattern's result off the stack. this differs from handling an if statement, which has a
-        // then clause that requires conditional popping
-        self.emit_byte(OpCode::Pop);
     }
 
     /// Parses a series of tokens, based on the precedence associated with them.
@@ -136,7 +141,10 @@ impl<'a> Parser<'a> {
     ///
     /// # Arguments
     /// - `precedence` the precedence of the current token
-    fn parse_precedence(&mut self, precedence: Precedence) {
+    ///
+    /// # Return value
+    /// - the parsed expression
+    fn parse_precedence(&mut self, precedence: Precedence) -> Expr {
         self.advance();
         // the first token is always going to belong to some kind of prefix expression, by
         // definition - although it may be nested as an operand in 1+ infix expressions
@@ -145,12 +153,12 @@ impl<'a> Parser<'a> {
                 .prefix_parse_fn;
         if maybe_prefix_rule.is_none() {
             self.error_at_previous("Expect expression.");
-            return;
+            return self.error_expression();
         }
 
         let can_assign: bool = precedence <= Precedence::LogicalAnd;
         let prefix_rule = maybe_prefix_rule.unwrap();
-        prefix_rule(self, can_assign);
+        let mut expression = prefix_rule(self, can_assign);
 
         while precedence
             <= parse_rules::get_rule(self.current_token.expect("Missing token!").token_type)
@@ -162,13 +170,15 @@ impl<'a> Parser<'a> {
                     .infix_parse_fn
                     .unwrap();
 
-            infix_rule(self, can_assign);
+            expression = infix_rule(self, expression, can_assign);
         }
 
         // if '=' is the current token, we should have consumed it somehow...report the error
         if can_assign && self.match_token(&TokenType::Equals) {
             self.error_at_current("Invalid assignment target.");
         }
+
+        expression
     }
 
     /// Advances the pointers the parser has to the current and the previous token
@@ -190,7 +200,14 @@ impl<'a> Parser<'a> {
             if !self.peek_token(&TokenType::Error) {
                 break;
             }
-            self.error_at_current("An error token was discovered.");
+            // the lexeme of an error token is the message the scanner reported
+            let message = self
+                .current_token
+                .unwrap()
+                .lexeme
+                .clone()
+                .unwrap_or_default();
+            self.error_at_current(&message);
         }
     }
 
@@ -242,43 +259,18 @@ impl<'a> Parser<'a> {
         false
     }
 
-    /// Parse a declaration production
-    fn declaration(&mut self) {
-        if self.match_token(&TokenType::Identifier) {
-            self.variable_declaration();
-        } else {
-            self.statement();
-        }
-    }
-
     /// Parse a field variable reference
-    fn field_variable(&mut self) {
+    fn field_variable(&mut self) -> Expr {
+        let start = self.previous();
         // a field variable is one that is prefixed with a dollar sign/sigil ('$')
         // because the value immediately following the '$' may be the result of an expression,
-        // e.g. $(2+3), we must parse the right hand side first and push it onto the stack
-        self.parse_precedence(Precedence::FieldVariable);
-        // now that the result of the expression is on the stack, push the '$'
-        self.emit_byte(OpCode::GetFieldVariable());
-    }
+        // e.g. $(2+3), the right hand side becomes the index of the field
+        let index = self.parse_precedence(Precedence::FieldVariable);
 
-    /// Parse a variable production
-    fn variable_declaration(&mut self) {
-        // store the variable name so it can be looked up later
-        let global_variable_index = self.parse_variable();
-
-        if self.match_token(&TokenType::Equals) {
-            self.expression();
-        } else if self.op_assign_match() {
-            self.op_assign(global_variable_index);
-        } else {
-            self.emit_constant(Value::String("".into()));
+        Expr {
+            kind: ExprKind::FieldVariable(Box::new(index)),
+            span: self.span_from(start),
         }
-        self.consume(
-            &TokenType::Semicolon,
-            "Expect ';' after a variable declaration.",
-        );
-
-        self.define_variable(global_variable_index);
     }
 
     /// Evaluates whether or not the current token is an operator assignment
@@ -294,78 +286,83 @@ impl<'a> Parser<'a> {
             || self.match_token(&TokenType::PowAssign)
     }
 
-    /// Parses a variable's name and places it in the current chunk's constant table
+    /// Parses a variable's name
     ///
-    /// # Return value
-    /// the index of the variable name in the constant table for easy lookup
-    fn parse_variable(&mut self) -> usize {
-        self.compiling_chunk.add_constant(
-            self.previous_token
-                .expect("No token was parsed")
-                .lexeme
-                .clone()
-                .expect("Variable name was empty"),
-        )
-    }
-
-    /// Output the bytecode instructions for a new variable definition
+    /// Assumes that the identifier of the variable is currently pointed to in the
+    /// [Parser#structfield.previous_token]
     ///
-    /// # Arguments
-    /// - `global_var_index` the index of the variable name in a chunk's constants table
-    fn define_variable(&mut self, global_var_index: usize) {
-        self.emit_byte(OpCode::DefineGlobal(global_var_index));
+    /// # Return value
+    /// the name of the variable
+    fn parse_variable(&mut self) -> String {
+        self.previous_token
+            .expect("No token was parsed")
+            .lexeme
+            .clone()
+            .expect("Variable name was empty")
     }
 
     /// Function for parsing a statement
-    fn statement(&mut self) {
-        if self.match_token(&TokenType::Print) {
-            self.print_statement();
+    fn statement(&mut self) -> Stmt {
+        let start = self.current();
+        let kind = if self.match_token(&TokenType::Print) {
+            self.print_statement()
         } else if self.match_token(&TokenType::If) {
-            self.if_statement();
+            self.if_statement()
         } else if self.match_token(&TokenType::While) {
-            self.while_statement();
+            self.while_statement()
         } else if self.match_token(&TokenType::For) {
-            self.for_statement();
+            self.for_statement()
         } else if self.match_token(&TokenType::Continue) {
-            self.continue_statement();
+            self.continue_statement()
         } else if self.match_token(&TokenType::Break) {
-            self.break_statement();
+            self.break_statement()
+        } else if self.match_token(&TokenType::Delete) {
+            self.delete_statement()
         } else if self.match_token(&TokenType::LeftCurly) {
-            self.block();
+            StmtKind::Block(self.block())
         } else {
             // we're looking at an expression statement (as the name of the next LoC implies)
-            self.expression();
+            let expression = self.expression();
             self.consume(
                 &TokenType::Semicolon,
                 "Expect ';' at the end of a statement.",
             );
-            // discard the result
-            self.emit_byte(OpCode::Pop);
+            StmtKind::Expression(expression)
+        };
+
+        Stmt {
+            kind,
+            span: self.span_from(start),
         }
     }
 
     /// Function for parsing a block of code contained by curly braces
-    fn block(&mut self) {
+    fn block(&mut self) -> Vec<Stmt> {
+        let mut statements = vec![];
         while !self.peek_token(&TokenType::RightCurly) && !self.peek_token(&TokenType::Eof) {
-            self.declaration();
+            statements.push(self.statement());
+            if self.panic_mode {
+                self.synchronize_statement();
+            }
         }
         self.consume(&TokenType::RightCurly, "Expect '}' after block.");
+        statements
     }
 
     /// Function for parsing a print statement
     ///
     /// TODO: output_redirection support
-    fn print_statement(&mut self) {
-        self.simple_print_statement();
+    fn print_statement(&mut self) -> StmtKind {
+        let expressions = self.simple_print_statement();
         self.consume(
             &TokenType::Semicolon,
             "Expect ';' at the end of a statement.",
         );
-        self.emit_byte(OpCode::OpPrint);
+        StmtKind::Print(expressions)
     }
 
-    fn simple_print_statement(&mut self) {
-        self.print_expr_list_opt();
+    fn simple_print_statement(&mut self) -> Vec<Expr> {
+        self.print_expr_list_opt()
         // TODO: Support additional arms of this part of the grammar
         // | Print  '(' multiple_expr_list ')'
         // | Printf print_expr_list
@@ -373,431 +370,446 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse an if statement
-    fn if_statement(&mut self) {
+    fn if_statement(&mut self) -> StmtKind {
         self.consume(&TokenType::LeftParenthesis, "Expect '(' after IF.");
-        self.expression();
+        let condition = self.expression();
         self.consume(
             &TokenType::RightParenthesis,
             "Expect ')' at the end of IF statement.",
         );
 
-        // emit a jump instruction as a placeholder to skip over the 'then' in the event the if condition is false.
-        // we'll backpatch it soon with the correct offset.
-        let if_was_false_jump = self.emit_jump(OpCode::JumpIfFalse(0xff, 0xff));
-        self.statement();
-
-        // when the if statement condition is truthy, pop the result off the stack
-        self.emit_byte(OpCode::Pop);
-
-        // in the event the if statement's condition is truthy, we need to jump over the else block rather than fall
-        // through. put a placeholder in that will be able to skip the else keyword & the statement(s) that follow it.
-        // there is an implicit 'else' here, even if there isn't one in the author's code.
-        let else_jump = self.emit_jump(OpCode::Jump(0xff, 0xff));
-
-        // we've passed through the then statement(s) backpatch the jump that was emitted for the if block. we needed
-        // to emit the pop instruction for the condition being truthy to ensure we calculated the distance for
-        // backpatching correctly.
-        self.patch_jump(if_was_false_jump);
-
-        // when the if statement condition is falsy, pop the result off the stack.
-        self.emit_byte(OpCode::Pop);
+        let then_branch = Box::new(self.statement());
+        let else_branch = if self.match_token(&TokenType::Else) {
+            Some(Box::new(self.statement()))
+        } else {
+            None
+        };
 
-        if self.match_token(&TokenType::Else) {
-            self.statement();
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
         }
-
-        // we've passed through the else block statement(s), backpatch the jump that was emitted for the else block
-        self.patch_jump(else_jump);
     }
 
     /// Parsing method for while statements
-    fn while_statement(&mut self) {
-        // mark the location where the loop begins
-        let while_start = self.compiling_chunk.code.len();
-
-        // Store a reference to the active loop start for this call frame. The value on `self` will be mutated when the
-        // body of the while statement is parsed
-        let surrounding_loop_start = self.inner_most_loop_start;
-
-        // Store where the loop starts should we run into a `continue` or `break` statement
-        self.inner_most_loop_start = self.compiling_chunk.code.len() as i32;
-
-        // Store a reference to the last loop's end place for this call frame. The value on `self` will be mutated when
-        // the body of the while statement is parsed
-        let surrounding_loop_end = self.inner_most_loop_end;
-
+    fn while_statement(&mut self) -> StmtKind {
         self.consume(&TokenType::LeftParenthesis, "Expect '(' after 'while'.");
-        self.expression();
+        let condition = self.expression();
         self.consume(
             &TokenType::RightParenthesis,
             "Expect ')' after 'while' condition.",
         );
 
-        // emit a jump that will jump over the body of the loop should it's condition be false
-        let while_condition_false = self.emit_jump(OpCode::JumpIfFalse(0xFF, 0xFF));
-
-        // pop the result of the while condition off the stack if the condition was truthy
-        self.emit_byte(OpCode::Pop);
-
-        self.statement();
-        // now that the body of the while loop has been parsed, emit a jump back to the start of the loop
-        self.emit_loop(while_start);
+        let body = Box::new(self.loop_body());
 
-        // in the event that we see a break statement during the course of the parsing, we need to patch it now that
-        // we're at the end of the loop
-        if self.inner_most_loop_end != -1 {
-            self.patch_jump(self.inner_most_loop_end as usize);
-        }
-        self.inner_most_loop_end = surrounding_loop_end;
-
-        // backpatch the jump for a falsy condition and pop the result off the stack
-        self.patch_jump(while_condition_false);
-        self.emit_byte(OpCode::Pop);
-
-        // Restore the references to the active loops start after parsing the body of the while
-        self.inner_most_loop_start = surrounding_loop_start;
+        StmtKind::While { condition, body }
     }
 
     /// Function for parsing a for loop
-    fn for_statement(&mut self) {
+    fn for_statement(&mut self) -> StmtKind {
         self.consume(&TokenType::LeftParenthesis, "Expect '(' after for.");
         // TODO Support `for (var in array) {}` when we get to arrays, that's why this for loop is funky ATM
-        if self.match_token(&TokenType::Semicolon) {
+        let initializer = if self.match_token(&TokenType::Semicolon) {
             // assume there is no variable initialization occurring
+            None
         } else {
-            self.expression();
+            let initializer = self.expression();
             self.consume(&TokenType::Semicolon, "Expect ';'.");
-            self.emit_byte(OpCode::Pop);
-        }
-
-        // Store a reference to the active loop start for this call frame. The value on `self` will be mutated when the
-        // body of the for statement is parsed
-        let surrounding_loop_start = self.inner_most_loop_start;
-
-        // Store a reference to the last loop's end place for this call frame. The value on `self` will be mutated when
-        // the body of the for statement is parsed
-        let surrounding_loop_end = self.inner_most_loop_end;
-
-        let mut loop_start = self.compiling_chunk.code.len();
-        let mut for_loop_exit_jump: Option<usize> = None;
+            Some(initializer)
+        };
 
-        if !self.match_token(&TokenType::Semicolon) {
-            self.expression();
+        let condition = if self.match_token(&TokenType::Semicolon) {
+            None
+        } else {
+            let condition = self.expression();
             self.consume(&TokenType::Semicolon, "Expect ';' after loop condition.");
+            Some(condition)
+        };
 
-            // if the condition is false, we need to jump out of the loop
-            for_loop_exit_jump = Some(self.emit_jump(OpCode::JumpIfFalse(0xFF, 0xFF)));
-            // if the condition is true, we need to pop the result off of the stack
-            self.emit_byte(OpCode::Pop);
-        }
-
-        if !self.match_token(&TokenType::RightParenthesis) {
-            // unconditionally jump over the incrementer, to the body of the loop
-            let body_jump = self.emit_jump(OpCode::Jump(0xFF, 0xFF));
-            let increment_clause_start = self.compiling_chunk.code.len();
-
-            // compile the incrementer, then throw away the result since it's often assignment
-            self.expression();
-            self.emit_jump(OpCode::Pop);
-
+        let increment = if self.match_token(&TokenType::RightParenthesis) {
+            None
+        } else {
+            let increment = self.expression();
             self.consume(
                 &TokenType::RightParenthesis,
                 "Expect ')' after for clauses.",
             );
+            Some(increment)
+        };
 
-            // this happens right after an increment, since an increment happens at the end of a loop (a little
-            // weird, I know)
-            // 1. take us back to the top of the for loop, right before the condition (which may not exist). this occurs
-            // _after_ the increment
-            self.emit_loop(loop_start);
-            // 2. update the loop start to point to the increment clause
-            loop_start = increment_clause_start;
-            // 3. back patch the jump for the entire body
-            self.patch_jump(body_jump);
-        }
-
-        // Store where the loop starts should we run into a `continue` or `break` statement
-        self.inner_most_loop_start = loop_start as i32;
-
-        self.statement();
-
-        self.emit_loop(loop_start);
+        let body = Box::new(self.loop_body());
 
-        // in the event that we see a break statement during the course of the parsing, we need to patch it now that
-        // we're at the end of the loop
-        if self.inner_most_loop_end != -1 {
-            self.patch_jump(self.inner_most_loop_end as usize);
-            self.emit_byte(OpCode::Pop);
-        }
-        self.inner_most_loop_end = surrounding_loop_end;
-
-        // patch the jump if the condition is false
-        if let Some(value) = for_loop_exit_jump {
-            self.patch_jump(value);
-            // if the condition is false, we still have that value on the stack
-            self.emit_byte(OpCode::Pop);
+        StmtKind::For {
+            initializer,
+            condition,
+            increment,
+            body,
         }
+    }
 
-        // Restore the references to the active loops start after parsing the body of the while
-        self.inner_most_loop_start = surrounding_loop_start;
+    /// Parses the body of a loop, in which `continue` and `break` statements may be used
+    fn loop_body(&mut self) -> Stmt {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        body
     }
 
     /// Function for parsing the continue token
-    fn continue_statement(&mut self) {
-        if self.inner_most_loop_start <= -1 {
+    fn continue_statement(&mut self) -> StmtKind {
+        if self.loop_depth == 0 {
             self.error_at_previous("Can't use 'continue' outside of a loop.");
         }
 
         self.consume(&TokenType::Semicolon, "Expect ';' after continue");
 
-        // casting is safer here, as we've ensure that the inner_most_loop_start >= 0 above
-        self.emit_loop(self.inner_most_loop_start as usize);
+        StmtKind::Continue
     }
 
     /// Function for parsing the break token
-    fn break_statement(&mut self) {
-        if self.inner_most_loop_start <= -1 {
+    fn break_statement(&mut self) -> StmtKind {
+        if self.loop_depth == 0 {
             self.error_at_previous("Can't use 'break' outside of a loop.");
         }
 
         self.consume(&TokenType::Semicolon, "Expect ';' after break");
 
-        self.inner_most_loop_end = self.emit_jump(OpCode::Jump(0xFF, 0xFF)) as i32;
-    }
-
-    /// Emits a looping instruction to go backwards in the code
-    ///
-    /// # Arguments
-    /// - `loop_start` the pointer to the the instruction where the loop began
-    fn emit_loop(&mut self, loop_start: usize) {
-        let offset = self.compiling_chunk.code.len() - loop_start + 1;
-        let offset1 = (offset >> 8) & 0xff;
-        let offset2 = offset & 0xff;
-        self.emit_byte(OpCode::Loop(offset1, offset2));
+        StmtKind::Break
     }
 
-    /// Emit a jump instruction
-    ///
-    /// # Arguments
-    /// - `instruction` the [OpCode] to emit
-    ///
-    /// # Return value
-    /// the location of the emitted jump instruction in the current chunk
-    fn emit_jump(&mut self, instruction: OpCode) -> usize {
-        self.emit_byte(instruction);
-
-        // return the offset, that is, where the instruction we'll later overwrites begins
-        self.compiling_chunk.code.len()
-    }
+    /// Function for parsing a delete statement, which removes either a single element of an array or
+    /// every element of an array
+    fn delete_statement(&mut self) -> StmtKind {
+        self.consume(&TokenType::Identifier, "Expect array name after 'delete'.");
+        let array = self.parse_variable();
 
-    /// Patch a jump instruction that was previously emitted
-    ///
-    /// # Arguments
-    /// - `offset` the location of the jump instruction that was emitted
-    fn patch_jump(&mut self, offset: usize) {
-        // pull the placeholder instruction out to be patched
-        let old_instruction = &self.compiling_chunk.code[offset - 1].code;
-
-        // calculate the jump distance
-        let jump = self.compiling_chunk.code.len() - offset;
-        let new_offset1 = (jump >> 8) & 0xff;
-        let new_offset2 = jump & 0xff;
-
-        // create a patch instruction, using the old one to avoid messiness in moving values
-        let patch_instruction = match &old_instruction {
-            OpCode::JumpIfFalse(_, _) => OpCode::JumpIfFalse(new_offset1, new_offset2),
-            OpCode::JumpIfTrue(_, _) => OpCode::JumpIfTrue(new_offset1, new_offset2),
-            OpCode::Jump(_, _) => OpCode::Jump(new_offset1, new_offset2),
-            _ => panic!(
-                "Instruction {:?} cannot be used to patch a jump!",
-                old_instruction
-            ),
+        let subscript = if self.match_token(&TokenType::LeftSquareBracket) {
+            Some(self.subscript())
+        } else {
+            None
         };
 
-        self.compiling_chunk.code[offset - 1].code = patch_instruction;
+        self.consume(&TokenType::Semicolon, "Expect ';' after delete");
+
+        StmtKind::Delete { array, subscript }
     }
 
-    fn print_expr_list_opt(&mut self) {
+    fn print_expr_list_opt(&mut self) -> Vec<Expr> {
         // TODO(FUTURE): when removing semicolon requirement, this will need to change to EoL and
         // other keyword checks
         if self.peek_token(&TokenType::Semicolon) {
-            // no expr - default to '$0'
-            self.emit_constant(Value::Number(0.0));
-            self.emit_byte(OpCode::GetFieldVariable());
+            // no expr - the code generator defaults to '$0'
+            vec![]
         } else {
-            self.print_expr_list();
+            self.print_expr_list()
         }
     }
 
-    fn print_expr_list(&mut self) {
-        self.print_expr();
+    fn print_expr_list(&mut self) -> Vec<Expr> {
+        let mut expressions = vec![self.print_expr()];
 
         while self.match_token(&TokenType::Comma) {
-            // for every comma operator, concatenate the expr that follows the comma with a space
-            // and the expr result
-            self.emit_constant(Value::String(String::from(" ")));
-            self.emit_byte(OpCode::Concatenate);
-            self.expression();
-            self.emit_byte(OpCode::Concatenate);
+            expressions.push(self.expression());
         }
+
+        expressions
     }
 
-    fn print_expr(&mut self) {
+    fn print_expr(&mut self) -> Expr {
         // TODO: These rules don't align with the grammar. We'll need to move that over and support
         // the who shebang at some point
         // print_expr       : unary_print_expr
         //                  | non_unary_print_expr
-        self.expression();
+        self.expression()
     }
 
-    fn expression(&mut self) {
+    fn expression(&mut self) -> Expr {
         // Parse the lowest precedence level that isn't [Precedence::None], which will subsume all
         // of the higher ones too. If we called this with None, it could consume tokens forever
         // e.g. (1) would fail trying to find an infix operator for ')'
-        self.parse_precedence(Precedence::Assignment);
+        self.parse_precedence(Precedence::Assignment)
     }
 
-    /// Emits an opcode to read the value of a global variable
+    /// Parses a reference to, or an assignment of, a variable
+    ///
+    /// An identifier that names a built in or native function is parsed as a call to that function instead
     ///
     /// # Arguments
     /// - `can_assign` `true` if a value can be assigned back to a variable, `false` otherwise
-    fn variable(&mut self, can_assign: bool) {
-        let chunk_index = self.parse_variable();
-        if can_assign && self.match_token(&TokenType::Equals) {
-            self.expression();
-            self.emit_byte(OpCode::SetGlobal(chunk_index));
+    fn variable(&mut self, can_assign: bool) -> Expr {
+        let start = self.previous();
+        let variable = self.parse_variable();
+
+        let kind = if let Some(function) = Builtin::resolve(&variable) {
+            self.builtin_call(function)
+        } else if let Some((function, arity)) = self.natives.resolve(&variable) {
+            self.native_call(function, arity)
+        } else if self.match_token(&TokenType::LeftSquareBracket) {
+            self.array_element(variable, can_assign)
+        } else if can_assign && self.match_token(&TokenType::Equals) {
+            ExprKind::Assignment {
+                variable,
+                operator: AssignmentOperator::Assign,
+                value: Box::new(self.expression()),
+            }
         } else if can_assign && self.op_assign_match() {
-            self.op_assign(chunk_index);
-            self.emit_byte(OpCode::SetGlobal(chunk_index));
+            self.op_assign(variable)
+        } else {
+            ExprKind::Variable(variable)
+        };
+
+        Expr {
+            kind,
+            span: self.span_from(start),
+        }
+    }
+
+    /// Parses a reference to, or an assignment of, an element of an array
+    ///
+    /// Assumes that the opening square bracket of the element's subscript has been consumed
+    ///
+    /// # Arguments
+    /// - `array` the name of the array
+    /// - `can_assign` `true` if a value can be assigned back to the element, `false` otherwise
+    fn array_element(&mut self, array: String, can_assign: bool) -> ExprKind {
+        let subscript = Box::new(self.subscript());
+        if can_assign && self.match_token(&TokenType::Equals) {
+            ExprKind::ArrayElementAssignment {
+                array,
+                subscript,
+                value: Box::new(self.expression()),
+            }
         } else {
-            self.emit_byte(OpCode::GetGlobal(chunk_index));
+            ExprKind::ArrayElement { array, subscript }
+        }
+    }
+
+    /// Parses a call to a built in function, checking that it is given a number of arguments it accepts
+    ///
+    /// Assumes that the name of the function has been consumed. `length` may be called without parentheses, in which
+    /// case it is given no arguments.
+    ///
+    /// # Arguments
+    /// - `function` the function
+    fn builtin_call(&mut self, function: Builtin) -> ExprKind {
+        let name_token = self.previous_token;
+        let arguments =
+            if function == Builtin::Length && !self.peek_token(&TokenType::LeftParenthesis) {
+                vec![]
+            } else {
+                self.call_arguments()
+            };
+
+        let arity = function.arity();
+        if !arity.contains(&arguments.len()) {
+            let expected = if arity.start() == arity.end() {
+                arity.start().to_string()
+            } else {
+                format!("{} to {}", arity.start(), arity.end())
+            };
+            self.error_at(
+                &name_token,
+                &format!("Expect {} arguments but got {}.", expected, arguments.len()),
+            );
+        }
+        ExprKind::BuiltinCall {
+            function,
+            arguments,
+        }
+    }
+
+    /// Parses a call to a native function, checking that it is given the number of arguments it expects
+    ///
+    /// Assumes that the name of the function has been consumed
+    ///
+    /// # Arguments
+    /// - `function` the index of the function
+    /// - `arity` the number of arguments the function expects
+    fn native_call(&mut self, function: usize, arity: usize) -> ExprKind {
+        let name_token = self.previous_token;
+        let arguments = self.call_arguments();
+
+        if arguments.len() != arity {
+            self.error_at(
+                &name_token,
+                &format!("Expect {} arguments but got {}.", arity, arguments.len()),
+            );
+        }
+        ExprKind::NativeCall {
+            function,
+            arguments,
         }
     }
 
-    /// Emits a number for the [TokenType::Number] token type
+    /// Parses the parenthesized, comma separated arguments of a function call
+    ///
+    /// Assumes that the name of the function has been consumed
+    fn call_arguments(&mut self) -> Vec<Expr> {
+        self.consume(
+            &TokenType::LeftParenthesis,
+            "Expect '(' after function name.",
+        );
+        let mut arguments = vec![];
+        if !self.peek_token(&TokenType::RightParenthesis) {
+            arguments.push(self.expression());
+            while self.match_token(&TokenType::Comma) {
+                arguments.push(self.expression());
+            }
+        }
+        self.consume(&TokenType::RightParenthesis, "Expect ')' after arguments.");
+        arguments
+    }
+
+    /// Parses the subscript of an array element
+    ///
+    /// Assumes that the opening square bracket of the subscript has been consumed
+    fn subscript(&mut self) -> Expr {
+        let subscript = self.expression();
+        // TODO: Support multiple subscripts joined by SUBSEP - e.g. `foo[1, 2]`
+        self.consume(
+            &TokenType::RightSquareBracket,
+            "Expect ']' after array subscript.",
+        );
+        subscript
+    }
+
+    /// Parses a number for the [TokenType::Number] token type
     ///
     /// Assumes that a [Token#structfield.token_type] with value of [TokenType::Number] has been
     /// detected and is currently pointed to in the [Parser#structfield.previous_token]
-    fn number(&mut self) {
+    fn number(&mut self) -> Expr {
         let raw_lexeme = self
             .previous_token
             .expect("No token was found!")
             .lexeme
             .as_ref()
             .expect("No lexeme for number found!");
-        let number: f32 = str::parse(raw_lexeme.as_str())
-            .unwrap_or_else(|err| panic!("Unable to convert {} to f32 - {}", raw_lexeme, err));
-        self.emit_constant(Value::Number(number));
+        let Ok(number) = str::parse::<f32>(raw_lexeme.as_str()) else {
+            self.error_at_previous("Invalid number.");
+            return self.error_expression();
+        };
+
+        Expr {
+            kind: ExprKind::Number(number),
+            span: self.span_from(self.previous()),
+        }
     }
 
-    /// Emits a string for the [TokenType::DoubleQuote] token type
+    /// Parses a string for the [TokenType::DoubleQuote] token type
     ///
     /// Assumes that a [Token#structfield.token_type] with value of [TokenType::DoubleQuote] has
     /// been detected and is currently pointed to in the [Parser#structfield.previous_token]
-    fn string(&mut self) {
-        self.emit_constant(Value::String(
-            self.previous_token
-                .unwrap()
-                .lexeme
-                .as_ref()
-                .unwrap()
-                .clone(),
-        ));
+    fn string(&mut self) -> Expr {
+        let string = self
+            .previous_token
+            .unwrap()
+            .lexeme
+            .as_ref()
+            .unwrap()
+            .clone();
+
+        Expr {
+            kind: ExprKind::String(string),
+            span: self.span_from(self.previous()),
+        }
     }
 
-    /// Emits the correct token while parsing a unary expression - e.g. `-42`
+    /// Parses a unary expression - e.g. `-42`
     ///
     /// Assumes that a [Token#structfield_token_type] that can be used within a unary expression has
     /// been detected and is currently pointed to in the [Parser#structfield.previous_token]
-    fn unary(&mut self) {
+    fn unary(&mut self) -> Expr {
+        let start = self.previous();
         let operator_type = self.previous_token.expect("missing token!").token_type;
 
-        // parse the operand first, then we'll negate it after this method has returned
-        // this also allows for nesting unary expressions, like `--2`
-        self.parse_precedence(Precedence::Unary);
+        // parsing the operand with the unary precedence allows for nesting unary expressions, like `--2`
+        let operand = Box::new(self.parse_precedence(Precedence::Unary));
+
+        let operator = match operator_type {
+            TokenType::Plus => UnaryOperator::Plus,
+            TokenType::Minus => UnaryOperator::Minus,
+            TokenType::Bang => UnaryOperator::LogicalNot,
+            _ => unreachable!("{:?} is not a unary operator", operator_type),
+        };
 
-        if let TokenType::Plus = operator_type {
-            self.emit_byte(OpCode::UnaryPlus)
-        } else if let TokenType::Minus = operator_type {
-            self.emit_byte(OpCode::UnaryMinus)
-        } else if let TokenType::Bang = operator_type {
-            self.emit_byte(OpCode::LogicalNot)
+        Expr {
+            kind: ExprKind::Unary { operator, operand },
+            span: self.span_from(start),
         }
     }
 
     /// Function for parsing operator assignment
     ///
+    /// Assumes that the operator has been detected and is currently pointed to in the
+    /// [Parser#structfield.previous_token]
+    ///
     /// # Arguments
-    /// - `global_variable_index` an index into the current compiling chunk's constants table
-    fn op_assign(&mut self, global_variable_index: usize) {
+    /// - `variable` the name of the variable being assigned to
+    fn op_assign(&mut self, variable: String) -> ExprKind {
         let operator_type = self.previous_token.expect("Missing token!").token_type;
-        match operator_type {
-            TokenType::AddAssign => {
-                self.parse_precedence(Precedence::Assignment);
-                self.emit_byte(OpCode::GetGlobal(global_variable_index));
-                self.emit_byte(OpCode::Add);
-            }
-            TokenType::SubAssign => {
-                self.emit_byte(OpCode::GetGlobal(global_variable_index));
-                self.parse_precedence(Precedence::Assignment);
-                self.emit_byte(OpCode::Subtract);
-            }
-            TokenType::MulAssign => {
-                self.parse_precedence(Precedence::Assignment);
-                self.emit_byte(OpCode::GetGlobal(global_variable_index));
-                self.emit_byte(OpCode::Multiply);
-            }
-            TokenType::DivAssign => {
-                self.emit_byte(OpCode::GetGlobal(global_variable_index));
-                self.parse_precedence(Precedence::Assignment);
-                self.emit_byte(OpCode::Divide);
-            }
-            TokenType::ModAssign => {
-                self.emit_byte(OpCode::GetGlobal(global_variable_index));
-                self.parse_precedence(Precedence::Assignment);
-                self.emit_byte(OpCode::Modulus);
-            }
-            TokenType::PowAssign => {
-                self.parse_precedence(Precedence::Assignment);
-                self.emit_byte(OpCode::GetGlobal(global_variable_index));
-                self.emit_byte(OpCode::Exponentiation);
-            }
-            _ => {}
+        let operator = match operator_type {
+            TokenType::AddAssign => AssignmentOperator::Add,
+            TokenType::SubAssign => AssignmentOperator::Subtract,
+            TokenType::MulAssign => AssignmentOperator::Multiply,
+            TokenType::DivAssign => AssignmentOperator::Divide,
+            TokenType::ModAssign => AssignmentOperator::Modulus,
+            TokenType::PowAssign => AssignmentOperator::Exponentiation,
+            _ => unreachable!("{:?} is not an operator assignment", operator_type),
+        };
+
+        ExprKind::Assignment {
+            variable,
+            operator,
+            value: Box::new(self.parse_precedence(Precedence::Assignment)),
         }
     }
 
     /// Function for parsing a binary infix expression.
-    fn binary(&mut self) {
+    ///
+    /// # Arguments
+    /// - `left` the left operand, which has already been parsed
+    fn binary(&mut self, left: Expr) -> Expr {
         let operator_type = self.previous_token.expect("Missing token!").token_type;
 
-        // Compile the right operand
+        // Parse the right operand
         let rule = parse_rules::get_rule(operator_type);
         // Note: We _could_ define a function for each of the operators and not have to do the
         // calculation for the next precedence, calling `parse_precedence` with the correct level.
         // This only works because the operators are left-associative:
         // 1 + 2 + 3 + 4 becomes ((1 + 2) + 3) + 4
         // To enable right associativity, we'd call with the same precedence
-        if rule.infix_associativity == Associativity::Right {
-            self.parse_precedence(rule.infix_precedence);
+        let right = if rule.infix_associativity == Associativity::Right {
+            self.parse_precedence(rule.infix_precedence)
         } else {
-            self.parse_precedence(Precedence::next_precedence(rule.infix_precedence));
-        }
+            self.parse_precedence(Precedence::next_precedence(rule.infix_precedence))
+        };
+
+        let operator = match operator_type {
+            TokenType::GreaterEqual => BinaryOperator::GreaterEqual,
+            TokenType::GreaterThan => BinaryOperator::Greater,
+            TokenType::LessEqual => BinaryOperator::LessEqual,
+            TokenType::LessThan => BinaryOperator::Less,
+            TokenType::DoubleEqual => BinaryOperator::Equal,
+            TokenType::NotEqual => BinaryOperator::NotEqual,
+            TokenType::Plus => BinaryOperator::Add,
+            TokenType::Minus => BinaryOperator::Subtract,
+            TokenType::Star => BinaryOperator::Multiply,
+            TokenType::Slash => BinaryOperator::Divide,
+            TokenType::Modulus => BinaryOperator::Modulus,
+            TokenType::Caret => BinaryOperator::Exponentiation,
+            TokenType::StringConcat => BinaryOperator::Concatenate,
+            _ => unreachable!("{:?} is not a binary operator", operator_type),
+        };
 
-        match operator_type {
-            TokenType::GreaterEqual => self.emit_byte(OpCode::GreaterEqual),
-            TokenType::GreaterThan => self.emit_byte(OpCode::Greater),
-            TokenType::LessEqual => self.emit_byte(OpCode::LessEqual),
-            TokenType::LessThan => self.emit_byte(OpCode::Less),
-            TokenType::DoubleEqual => self.emit_byte(OpCode::DoubleEqual),
-            TokenType::NotEqual => self.emit_byte(OpCode::NotEqual),
-            TokenType::Plus => self.emit_byte(OpCode::Add),
-            TokenType::Minus => self.emit_byte(OpCode::Subtract),
-            TokenType::Star => self.emit_byte(OpCode::Multiply),
-            TokenType::Slash => self.emit_byte(OpCode::Divide),
-            TokenType::Modulus => self.emit_byte(OpCode::Modulus),
-            TokenType::Caret => self.emit_byte(OpCode::Exponentiation),
-            TokenType::StringConcat => self.emit_byte(OpCode::Concatenate),
-            _ => {}
+        let span = left.span.to(right.span);
+        Expr {
+            kind: ExprKind::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            },
+            span,
         }
     }
 
@@ -806,76 +818,66 @@ impl<'a> Parser<'a> {
     /// A condition expression takes the form `expr1 ? expr2 : expr3` where:
     /// - if `expr1` is truthy, `expr2` is evaluated and `expr3` is not evaluated
     /// - if `expr1` is falsy, `expr2` is not evaluated and `expr3` is evaluated
-    fn conditional_expression(&mut self) {
-        // expr1 has already been evaluated and is at the top of the stack.
-        // we need to jump over expr2 if that value on the stack if falsy
-        let over_expr_2_jump = self.emit_jump(OpCode::JumpIfFalse(0xFF, 0xFF));
-
-        // if the value from expr1 is truthy, pop it off the stack now. we cannot wait to pop this value,
-        // as that may lead to popping the result from expr2 being popped off the stack
-        self.emit_byte(OpCode::Pop);
-
+    ///
+    /// # Arguments
+    /// - `condition` expr1, which has already been parsed
+    fn conditional_expression(&mut self, condition: Expr) -> Expr {
         // parse expr2
-        self.expression();
+        let then_expr = self.expression();
 
         // we need to handle the colon (:) in this block
         self.consume(&TokenType::Colon, "Expect ':' after expression in ternary.");
 
-        // if expr1 (which is on the stack) is truthy, we'll run through expr2. now we need to emit a jump over expr3
-        // to ensure we don't evaluate that as well
-        let over_expr_3_jump = self.emit_jump(OpCode::Jump(0xFF, 0xFF));
-
-        // by now, both expr2 and the colon in the ternary have been parsed, so we are exactly where we need to be to
-        // backpatch in the event expr1 was falsy
-        self.patch_jump(over_expr_2_jump);
-
-        // if the value from expr1 is falsy, pop it off the stack now. we cannot wait to pop this value,
-        // as that may lead to popping the result from expr3 being popped off the stack
-        self.emit_byte(OpCode::Pop);
-
         // parse expr3
-        self.expression();
-
-        // now that expr3 has been parsed, we can backpatch the jump we'd have taken if expr1 was truthy
-        self.patch_jump(over_expr_3_jump);
+        let else_expr = self.expression();
+
+        let span = condition.span.to(else_expr.span);
+        Expr {
+            kind: ExprKind::Conditional {
+                condition: Box::new(condition),
+                then_expr: Box::new(then_expr),
+                else_expr: Box::new(else_expr),
+            },
+            span,
+        }
     }
 
-    /// Function for parsing logical or (||) to support short circuiting.
+    /// Function for parsing logical or (||), whose right hand side is short circuited.
     ///
-    /// When this function is reached, the left hand side of the expression should have already been parsed and its
-    /// contents on the top of the stack. If that value is truthy, the entire statement is truthy.
-    fn logical_or(&mut self) {
-        // create a placeholder to jump to the end of the or statement should it's condition be true
-        let end_jump = self.emit_jump(OpCode::JumpIfTrue(0xFF, 0xFF));
-
+    /// # Arguments
+    /// - `left` the left hand side of the expression, which has already been parsed
+    fn logical_or(&mut self, left: Expr) -> Expr {
         // continue parsing the right hand side of the 'or'
-        self.parse_precedence(Precedence::LogicalOr);
-
-        // the LHS and RHS are now on the stack, we'll need to evaluate them in the VM, since awk's return value for
-        // logical or is 1 or 0, not the return value of the last sub expression
-        self.emit_byte(OpCode::LogicalOr);
-
-        // we've now passed the or statement, backpatch the jump over the contents
-        self.patch_jump(end_jump);
+        let right = self.parse_precedence(Precedence::LogicalOr);
+        self.logical(LogicalOperator::Or, left, right)
     }
 
-    /// Function for parsing logical and (&&) to support short circuiting.
+    /// Function for parsing logical and (&&), whose right hand side is short circuited.
     ///
-    /// When this function is reached, the left hand side of the expression should have already been parsed and its
-    /// contents on the top of the stack. If that value is falsy, the entire statement is falsy.
-    fn logical_and(&mut self) {
-        // create a placeholder to jump to the end of the and statement should it's condition be false
-        let end_jump = self.emit_jump(OpCode::JumpIfFalse(0xFF, 0xFF));
-
+    /// # Arguments
+    /// - `left` the left hand side of the expression, which has already been parsed
+    fn logical_and(&mut self, left: Expr) -> Expr {
         // continue parsing the right hand side of the 'and'
-        self.parse_precedence(Precedence::LogicalAnd);
-
-        // the LHS and RHS are now on the stack, we'll need to evaluate them in the VM, since awk's return value for
-        // logical and is 1 or 0, not the return value of the last sub expression
-        self.emit_byte(OpCode::LogicalAnd);
+        let right = self.parse_precedence(Precedence::LogicalAnd);
+        self.logical(LogicalOperator::And, left, right)
+    }
 
-        // we've now passed the and statement, backpatch the jump over the contents
-        self.patch_jump(end_jump);
+    /// Helper function for building a logical expression from its parsed operands
+    ///
+    /// # Arguments
+    /// - `operator` the logical operator
+    /// - `left` the left hand side of the expression
+    /// - `right` the right hand side of the expression
+    fn logical(&mut self, operator: LogicalOperator, left: Expr, right: Expr) -> Expr {
+        let span = left.span.to(right.span);
+        Expr {
+            kind: ExprKind::Logical {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            },
+            span,
+        }
     }
 
     /// Function for parsing a grouping, denoted by an expression surrounded by parenthesis.
@@ -886,29 +888,45 @@ impl<'a> Parser<'a> {
     /// As far as the backend of the language is concerned, there's nothing to this. It only allows
     /// us to use a lower precedence expression in when a higher one is expected. The implication
     /// of this is that it does _not_ emit any code!
-    fn grouping(&mut self) {
-        self.expression();
+    fn grouping(&mut self) -> Expr {
+        let start = self.previous();
+        let expression = self.expression();
         self.consume(&TokenType::RightParenthesis, "Expect ')' token");
+
+        Expr {
+            kind: ExprKind::Grouping(Box::new(expression)),
+            span: self.span_from(start),
+        }
     }
 
-    /// Helper function to emit the bytes associated with a constant
+    /// Creates a placeholder expression to stand in for one that could not be parsed
     ///
-    /// # Arguments
-    /// - `value` the constant to emit bytes for
-    fn emit_constant(&mut self, value: Value) {
-        self.emit_byte(OpCode::OpConstant(value));
+    /// Once an error has been reported, the tree is discarded by [Parser::parse], so the placeholder
+    /// is never compiled.
+    fn error_expression(&self) -> Expr {
+        Expr {
+            kind: ExprKind::String(String::new()),
+            span: self.span_from(self.previous()),
+        }
     }
 
-    /// Helper function to emit bytes
+    /// Retrieves the token currently being examined
+    fn current(&self) -> &'a Token {
+        self.current_token.expect("Missing token!")
+    }
+
+    /// Retrieves the token that preceded the token currently being examined
+    fn previous(&self) -> &'a Token {
+        self.previous_token
+            .unwrap_or_else(|| self.current_token.unwrap())
+    }
+
+    /// Creates a span from a token through the most recently consumed token
     ///
     /// # Arguments
-    /// - `op_code` the value to emit the bytes for
-    fn emit_byte(&mut self, op_code: OpCode) {
-        let line_number = self
-            .previous_token
-            .unwrap_or_else(|| self.current_token.unwrap())
-            .line;
-        self.compiling_chunk.write_chunk(op_code, line_number);
+    /// - `start` the token that the span begins with
+    fn span_from(&self, start: &Token) -> Span {
+        Span::new(start, self.previous())
     }
 
     /// Helper function for reporting an error at the current token
@@ -934,7 +952,7 @@ impl<'a> Parser<'a> {
     /// Information from the provided token will be extracted as a part of the error message, making
     /// it imperative that the line number, token type, etc. are accurately set
     ///
-    /// Sets [Parser#structfield.had_error]
+    /// Records the error in [Parser#structfield.errors]
     ///
     /// If the compiler is currently in [Parser#structfield.panic_mode], error messages will be
     /// suppressed
@@ -949,34 +967,374 @@ impl<'a> Parser<'a> {
         self.panic_mode = true;
 
         let unwrapped_token = token.unwrap();
-        let mut error_msg = format!("[line {}] Error", unwrapped_token.line);
+        let mut error_msg = String::from("syntax error");
         match unwrapped_token.token_type {
             TokenType::Eof => {
                 error_msg.push_str(" at end");
             }
-            _ => {
-                error_msg.push_str(&format!(
-                    " at '{}'", // TODO: This is not exactly graceful on newlines
-                    unwrapped_token.lexeme.as_ref().unwrap_or(&String::from(
-                        "TODO: This is a shortsighted part of the lexeme"
-                    ))
-                ));
+            // the error token's lexeme is the message itself, so there is nothing to point at
+            TokenType::Error => {}
+            token_type => {
+                let text = unwrapped_token.lexeme.as_deref().or(token_type.symbol());
+                if let Some(text) = text {
+                    error_msg.push_str(&format!(" at '{}'", text));
+                }
             }
         }
-
         error_msg.push_str(&format!(": {}", message));
-        error!("{}", error_msg.as_str());
 
-        self.had_error = true;
+        self.errors.push(
+            Error::new(ErrorKind::Syntax, error_msg)
+                .with_line(unwrapped_token.line as usize)
+                .with_column(unwrapped_token.column),
+        );
     }
 
-    fn emit_return(&mut self) {
-        self.emit_byte(OpCode::OpReturn);
+    /// Leaves [Parser#structfield.panic_mode] by discarding tokens until the start of the next statement
+    ///
+    /// A statement is assumed to start after a ';', or at a keyword that begins a statement. The end of the enclosing
+    /// block is also a boundary, so that the block may be closed. If the end of the program is reached first, the parser
+    /// remains in panic mode, as any further errors would only be caused by the one already reported.
+    fn synchronize_statement(&mut self) {
+        while !self.peek_token(&TokenType::Eof) {
+            if self.previous_token.map(|token| token.token_type) == Some(&TokenType::Semicolon) {
+                self.panic_mode = false;
+                return;
+            }
+            match self.current_token.unwrap().token_type {
+                TokenType::RightCurly
+                | TokenType::LeftCurly
+                | TokenType::Print
+                | TokenType::If
+                | TokenType::While
+                | TokenType::For
+                | TokenType::Continue
+                | TokenType::Break
+                | TokenType::Delete => {
+                    self.panic_mode = false;
+                    return;
+                }
+                _ => self.advance(),
+            }
+        }
     }
 
-    fn end_compiler(&mut self) {
-        #[cfg(debug_assertions)]
-        self.compiling_chunk.disassemble_chunk("code");
-        self.emit_return();
+    /// Leaves [Parser#structfield.panic_mode] by discarding tokens until the start of the next rule
+    ///
+    /// A rule is assumed to start after the '}' that closes an action, or at a `BEGIN` or `END` pattern.
+    fn synchronize_rule(&mut self) {
+        while !self.peek_token(&TokenType::Eof) {
+            if self.previous_token.map(|token| token.token_type) == Some(&TokenType::RightCurly) {
+                self.panic_mode = false;
+                return;
+            }
+            match self.current_token.unwrap().token_type {
+                TokenType::Begin | TokenType::End => {
+                    self.panic_mode = false;
+                    return;
+                }
+                _ => self.advance(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod syntax_tree {
+    use super::*;
+    use crate::scanner::Scanner;
+    use crate::value::Value;
+
+    fn parse_source(source: &str) -> Option<Ast> {
+        let tokens = Scanner::new(String::from(source)).scan();
+        Parser::new(tokens.iter(), &NativeFunctions::new())
+            .parse()
+            .ok()
+    }
+
+    /// Creates the span of a node that begins and ends on the first line of a program
+    ///
+    /// # Arguments
+    /// - `start_offset` the byte of the program that the node begins at
+    /// - `end_offset` the byte of the program just past the end of the node
+    fn first_line_span(start_offset: usize, end_offset: usize) -> Span {
+        Span {
+            start_line: 1,
+            start_column: start_offset + 1,
+            end_line: 1,
+            start_offset,
+            end_offset,
+        }
+    }
+
+    #[test]
+    fn it_parses_an_action_without_a_pattern() {
+        let ast = parse_source("{ print; }").unwrap();
+
+        assert_eq!(
+            ast.rules,
+            vec![Rule {
+                pattern: Pattern::Always,
+                action: Some(vec![Stmt {
+                    kind: StmtKind::Print(vec![]),
+                    span: first_line_span(2, 8),
+                }]),
+                span: first_line_span(0, 10),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_parses_a_pattern_without_an_action() {
+        let ast = parse_source("1").unwrap();
+
+        assert_eq!(
+            ast.rules,
+            vec![Rule {
+                pattern: Pattern::Expression(Expr {
+                    kind: ExprKind::Number(1.0),
+                    span: first_line_span(0, 1),
+                }),
+                action: None,
+                span: first_line_span(0, 1),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_parses_binary_operators_by_precedence() {
+        let ast = parse_source("BEGIN { 1 + 2 * 3; }").unwrap();
+        let statements = ast.rules[0].action.as_ref().unwrap();
+
+        let StmtKind::Expression(Expr {
+            kind: ExprKind::Binary {
+                operator, right, ..
+            },
+            ..
+        }) = &statements[0].kind
+        else {
+            panic!("expected a binary expression, got {:?}", statements[0].kind);
+        };
+        assert_eq!(*operator, BinaryOperator::Add);
+        assert!(matches!(
+            right.kind,
+            ExprKind::Binary {
+                operator: BinaryOperator::Multiply,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn it_spans_every_line_of_a_rule() {
+        let ast = parse_source("{\nx = 1;\nprint x;\n}").unwrap();
+
+        let span = ast.rules[0].span;
+        assert_eq!((span.start_line, span.end_line), (1, 4));
+        let statements = ast.rules[0].action.as_ref().unwrap();
+        let span = statements[0].span;
+        assert_eq!((span.start_line, span.end_line), (2, 2));
+        let span = statements[1].span;
+        assert_eq!((span.start_line, span.end_line), (3, 3));
+    }
+
+    #[test]
+    fn it_spans_the_columns_and_bytes_of_a_node() {
+        let source = "{\n  x = \"é\" 1;\n}";
+        let ast = parse_source(source).unwrap();
+
+        let statements = ast.rules[0].action.as_ref().unwrap();
+        let StmtKind::Expression(Expr {
+            kind: ExprKind::Assignment { value, .. },
+            ..
+        }) = &statements[0].kind
+        else {
+            panic!("expected an assignment, got {:?}", statements[0].kind);
+        };
+        assert_eq!(
+            value.span,
+            Span {
+                start_line: 2,
+                start_column: 7,
+                end_line: 2,
+                start_offset: 8,
+                end_offset: 14,
+            }
+        );
+        assert_eq!(
+            &source[value.span.start_offset..value.span.end_offset],
+            "\"é\" 1"
+        );
+        assert_eq!(ast.rules[0].span.end_offset, source.len());
+    }
+
+    #[test]
+    fn it_parses_a_compound_assignment() {
+        let ast = parse_source("{ x += 2; }").unwrap();
+        let statements = ast.rules[0].action.as_ref().unwrap();
+
+        assert!(matches!(
+            &statements[0].kind,
+            StmtKind::Expression(Expr {
+                kind: ExprKind::Assignment {
+                    operator: AssignmentOperator::Add,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_break_outside_of_a_loop() {
+        assert_eq!(parse_source("{ break; }"), None);
+    }
+
+    #[test]
+    fn it_rejects_a_missing_semicolon() {
+        assert_eq!(parse_source("{ print 1 }"), None);
+    }
+
+    #[test]
+    fn it_reports_the_message_of_an_error_token() {
+        let tokens = Scanner::new(String::from("{ print \"oops; }")).scan();
+
+        assert_eq!(
+            Parser::new(tokens.iter(), &NativeFunctions::new()).parse(),
+            Err(vec![Error::new(
+                ErrorKind::Syntax,
+                "syntax error: Unterminated string."
+            )
+            .with_line(1)
+            .with_column(9)])
+        );
+    }
+
+    #[test]
+    fn it_parses_a_call_to_a_native_function() {
+        let mut natives = NativeFunctions::new();
+        natives
+            .register("unused", 0, |_| Value::Number(0.0))
+            .unwrap();
+        natives.register("pair", 2, |_| Value::Number(0.0)).unwrap();
+        let tokens = Scanner::new(String::from("{ x = pair(1, $2); }")).scan();
+        let ast = Parser::new(tokens.iter(), &natives).parse().unwrap();
+
+        let statements = ast.rules[0].action.as_ref().unwrap();
+        let StmtKind::Expression(Expr {
+            kind: ExprKind::Assignment { value, .. },
+            ..
+        }) = &statements[0].kind
+        else {
+            panic!("expected an assignment, got {:?}", statements[0].kind);
+        };
+        let ExprKind::NativeCall {
+            function,
+            arguments,
+        } = &value.kind
+        else {
+            panic!("expected a native call, got {:?}", value.kind);
+        };
+        assert_eq!(*function, 1);
+        assert_eq!(arguments.len(), 2);
+    }
+
+    #[test]
+    fn it_rejects_a_native_call_with_the_wrong_number_of_arguments() {
+        let mut natives = NativeFunctions::new();
+        natives.register("pair", 2, |_| Value::Number(0.0)).unwrap();
+        let tokens = Scanner::new(String::from("{ print pair(1); }")).scan();
+
+        assert_eq!(
+            Parser::new(tokens.iter(), &natives).parse(),
+            Err(vec![Error::new(
+                ErrorKind::Syntax,
+                "syntax error at 'pair': Expect 2 arguments but got 1."
+            )
+            .with_line(1)
+            .with_column(9)])
+        );
+    }
+
+    #[test]
+    fn it_parses_length_without_parentheses() {
+        let tokens = Scanner::new(String::from("length > 3 { print length($1); }")).scan();
+        let ast = Parser::new(tokens.iter(), &NativeFunctions::new())
+            .parse()
+            .unwrap();
+
+        let Pattern::Expression(Expr {
+            kind: ExprKind::Binary { left, .. },
+            ..
+        }) = &ast.rules[0].pattern
+        else {
+            panic!("expected a comparison, got {:?}", ast.rules[0].pattern);
+        };
+        assert!(matches!(
+            &left.kind,
+            ExprKind::BuiltinCall { function: Builtin::Length, arguments } if arguments.is_empty()
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_builtin_call_with_the_wrong_number_of_arguments() {
+        assert_eq!(
+            parse_errors("{ print substr($1); print index(1, 2, 3); }"),
+            vec![
+                "cmd. line:1:9: syntax error at 'substr': Expect 2 to 3 arguments but got 1.",
+                "cmd. line:1:27: syntax error at 'index': Expect 2 arguments but got 3.",
+            ]
+        );
+    }
+
+    /// Parses a program that is expected to be invalid, returning a description of each error reported
+    fn parse_errors(source: &str) -> Vec<String> {
+        let tokens = Scanner::new(String::from(source)).scan();
+        Parser::new(tokens.iter(), &NativeFunctions::new())
+            .parse()
+            .expect_err("the program should not parse")
+            .iter()
+            .map(Error::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn it_reports_the_column_of_punctuation() {
+        assert_eq!(
+            parse_errors("{ print 1 +; }"),
+            vec!["cmd. line:1:12: syntax error at ';': Expect expression."]
+        );
+    }
+
+    #[test]
+    fn it_reports_an_error_for_each_invalid_statement() {
+        assert_eq!(
+            parse_errors("{\n  x = ;\n  print 1;\n  break;\n  y = 2\n}"),
+            vec![
+                "cmd. line:2:7: syntax error at ';': Expect expression.",
+                "cmd. line:4:3: syntax error at 'break': Can't use 'break' outside of a loop.",
+                "cmd. line:6:1: syntax error at '}': Expect ';' at the end of a statement.",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_an_error_for_each_invalid_rule() {
+        assert_eq!(
+            parse_errors("BEGIN { print ( ; } $1 > { print; } END print"),
+            vec![
+                "cmd. line:1:17: syntax error at ';': Expect expression.",
+                "cmd. line:1:26: syntax error at '{': Expect expression.",
+                "cmd. line:1:41: syntax error at 'print': Expect '{' after END.",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_does_not_report_errors_caused_by_reaching_the_end_of_the_program() {
+        assert_eq!(
+            parse_errors("{ print \"oops; }"),
+            vec!["cmd. line:1:9: syntax error: Unterminated string."]
+        );
     }
 }
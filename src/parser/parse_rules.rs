@@ -1,11 +1,12 @@
 //! Module containing the parse rules for each token in the grammar
 
+use crate::ast::Expr;
 use crate::parser::associativity::Associativity;
 use crate::parser::precedence::Precedence;
 use crate::parser::Parser;
 use crate::TokenType;
 
-/// Type describing functions that will be invoked at parse time.
+/// Type describing functions that will be invoked at parse time to parse a prefix expression.
 ///
 /// The function that is invoked is predicated on a token that is read from the input stream. Some
 /// functions shall require the `can_assign` variable to be plumbed to them to act appropriately in
@@ -13,16 +14,29 @@ use crate::TokenType;
 ///
 /// # Arguments
 /// - `can_assign` whether or not assignment to a variable is permitted
-type ParseFn = fn(&mut Parser, can_assign: bool) -> ();
+///
+/// # Return value
+/// - the parsed expression
+type PrefixParseFn = fn(&mut Parser, can_assign: bool) -> Expr;
+
+/// Type describing functions that will be invoked at parse time to parse an infix expression.
+///
+/// # Arguments
+/// - `left` the left hand side of the infix expression, which has already been parsed
+/// - `can_assign` whether or not assignment to a variable is permitted
+///
+/// # Return value
+/// - the parsed expression, including its left hand side
+type InfixParseFn = fn(&mut Parser, left: Expr, can_assign: bool) -> Expr;
 
 #[derive(Copy, Clone)]
 pub struct ParseRule {
-    // function to compile a _prefix expression_ starting with a token of some type
+    // function to parse a _prefix expression_ starting with a token of some type
     // aka 'nuds' or 'null denotations'
-    pub prefix_parse_fn: Option<ParseFn>,
-    // function to compile an _infix expression_ whose left operand is followed by a token of that type
+    pub prefix_parse_fn: Option<PrefixParseFn>,
+    // function to parse an _infix expression_ whose left operand is followed by a token of that type
     // aka 'leds' or 'left denotations'
-    pub infix_parse_fn: Option<ParseFn>,
+    pub infix_parse_fn: Option<InfixParseFn>,
     // the precedence of an _infix expression_ that uses a token as an operator
     pub infix_precedence: Precedence,
     // the associativity of an infix expression
@@ -44,7 +58,7 @@ pub fn get_rule(token_type: &TokenType) -> &ParseRule {
 /// expression pointer, and a precedence.
 ///
 /// When an infix expression function from this table is called, it's left hand side (LHS) has
-/// already been compiled and the infix operator consumed.
+/// already been parsed and the infix operator consumed.
 const PARSE_RULES: [ParseRule; 65] = [
     // BEGIN
     ParseRule {
@@ -217,14 +231,14 @@ const PARSE_RULES: [ParseRule; 65] = [
     // (Logical) Or
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.logical_or()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.logical_or(left)),
         infix_precedence: Precedence::LogicalOr,
         infix_associativity: Associativity::Left,
     },
     // (Logical) And
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.logical_and()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.logical_and(left)),
         infix_precedence: Precedence::LogicalAnd,
         infix_associativity: Associativity::Left,
     },
@@ -238,28 +252,28 @@ const PARSE_RULES: [ParseRule; 65] = [
     // DoubleEqual
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Comparison,
         infix_associativity: Associativity::NA,
     },
     // LessEqual
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Comparison,
         infix_associativity: Associativity::NA,
     },
     // GreaterEqual
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Comparison,
         infix_associativity: Associativity::NA,
     },
     // NotEqual
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Comparison,
         infix_associativity: Associativity::NA,
     },
@@ -343,35 +357,35 @@ const PARSE_RULES: [ParseRule; 65] = [
     // Plus
     ParseRule {
         prefix_parse_fn: Some(|parser, _can_assign| parser.unary()),
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Term,
         infix_associativity: Associativity::Left,
     },
     // Minus
     ParseRule {
         prefix_parse_fn: Some(|parser, _can_assign| parser.unary()),
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Term,
         infix_associativity: Associativity::Left,
     },
     // Star
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Factor,
         infix_associativity: Associativity::Left,
     },
     // Modulus
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Factor,
         infix_associativity: Associativity::Left,
     },
     // Caret
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Exponentiation,
         infix_associativity: Associativity::Right,
     },
@@ -385,14 +399,14 @@ const PARSE_RULES: [ParseRule; 65] = [
     // GreaterThan
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Comparison,
         infix_associativity: Associativity::NA,
     },
     // LessThan
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Comparison,
         infix_associativity: Associativity::NA,
     },
@@ -406,7 +420,7 @@ const PARSE_RULES: [ParseRule; 65] = [
     // Question
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.conditional_expression()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.conditional_expression(left)),
         infix_precedence: Precedence::Conditional,
         infix_associativity: Associativity::Right,
     },
@@ -455,7 +469,7 @@ const PARSE_RULES: [ParseRule; 65] = [
     // Slash
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Term,
         infix_associativity: Associativity::Left,
     },
@@ -497,7 +511,7 @@ const PARSE_RULES: [ParseRule; 65] = [
     // string concatenation (synthetic)
     ParseRule {
         prefix_parse_fn: None,
-        infix_parse_fn: Some(|parser, _can_assign| parser.binary()),
+        infix_parse_fn: Some(|parser, left, _can_assign| parser.binary(left)),
         infix_precedence: Precedence::Concatenation,
         infix_associativity: Associativity::Left,
     },
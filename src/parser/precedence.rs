@@ -49,7 +49,7 @@ impl Precedence {
 }
 
 #[cfg(test)]
-mod precedence {
+mod precedence_order {
     use super::*;
 
     #[test]
@@ -0,0 +1,323 @@
+//! Module describing a compiled awk program
+
+mod symbol_table;
+
+use crate::chunk::Chunk;
+use crate::codegen::CodeGenerator;
+use crate::error::CompileError;
+use crate::native::NativeFunctions;
+use crate::optimizer;
+use crate::parser::Parser;
+pub use crate::program::symbol_table::SymbolTable;
+use crate::scanner::Scanner;
+use crate::token::Token;
+
+/// The sections of an awk program, each of which is run at a different point in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Section {
+    /// actions that run before any input is read (`BEGIN { ... }`)
+    Begin,
+    /// pattern-actions that run once per record
+    Main,
+    /// actions that run after all input has been read (`END { ... }`)
+    End,
+}
+
+/// A compiled awk program, made up of one chunk per [Section]
+///
+/// The variables and arrays referenced by a program are resolved to slots at compile time, which are shared by every
+/// section of the program. The program keeps the [NativeFunctions] it was compiled with, so that they may be called
+/// when it is run.
+pub struct Program {
+    begin: Chunk,
+    main: Chunk,
+    end: Chunk,
+    globals: SymbolTable,
+    arrays: SymbolTable,
+    natives: NativeFunctions,
+    // the sections that rules were compiled into, which is recorded before optimizing as optimizing may remove every
+    // instruction of a rule (e.g. `{}`)
+    sections_with_rules: Vec<Section>,
+}
+
+impl Program {
+    /// Instantiates a new program, with no code in any section
+    pub(crate) fn new() -> Program {
+        Program {
+            begin: Chunk::new(),
+            main: Chunk::new(),
+            end: Chunk::new(),
+            globals: SymbolTable::new(),
+            arrays: SymbolTable::new(),
+            natives: NativeFunctions::new(),
+            sections_with_rules: vec![],
+        }
+    }
+
+    /// Compiles a user's program, so that it may be run any number of times
+    ///
+    /// # Arguments
+    /// - `source` the source code of the user's program
+    ///
+    /// # Return value
+    /// - the compiled program
+    /// - a [CompileError] describing each error found if the program could not be compiled
+    pub fn compile(source: &str) -> Result<Program, CompileError> {
+        Program::compile_with_natives(source, &NativeFunctions::new())
+    }
+
+    /// Compiles a user's program that may call native functions provided by the host application
+    ///
+    /// # Arguments
+    /// - `source` the source code of the user's program
+    /// - `natives` the native functions that the program may call
+    ///
+    /// # Return value
+    /// - the compiled program
+    /// - a [CompileError] describing each error found if the program could not be compiled, including calls to native
+    ///   functions with the wrong number of arguments
+    pub fn compile_with_natives(
+        source: &str,
+        natives: &NativeFunctions,
+    ) -> Result<Program, CompileError> {
+        let tokens = Scanner::new(String::from(source)).scan();
+        Program::compile_tokens(&tokens, natives, true)
+    }
+
+    /// Compiles a user's program without running the [optimizer](crate::optimizer) over the generated code, as `-s`
+    /// (`--no-optimize`) does on the command line
+    ///
+    /// Programs compiled with [Program::compile] and [Program::compile_with_natives] are always optimized.
+    ///
+    /// # Arguments
+    /// - `source` the source code of the user's program
+    /// - `natives` the native functions that the program may call
+    ///
+    /// # Return value
+    /// - the compiled program
+    /// - a [CompileError] describing each error found if the program could not be compiled
+    pub fn compile_unoptimized(
+        source: &str,
+        natives: &NativeFunctions,
+    ) -> Result<Program, CompileError> {
+        let tokens = Scanner::new(String::from(source)).scan();
+        Program::compile_tokens(&tokens, natives, false)
+    }
+
+    /// Compiles a user's program that has already been scanned
+    ///
+    /// # Arguments
+    /// - `tokens` the tokens generated by scanning the user's program
+    /// - `natives` the native functions that the program may call
+    /// - `optimize` whether or not to run the [optimizer](crate::optimizer) over the generated code
+    ///
+    /// # Return value
+    /// - the compiled program
+    /// - a [CompileError] describing each error found if the program could not be compiled
+    pub(crate) fn compile_tokens(
+        tokens: &[Token],
+        natives: &NativeFunctions,
+        optimize: bool,
+    ) -> Result<Program, CompileError> {
+        let ast = Parser::new(tokens.iter(), natives)
+            .parse()
+            .map_err(CompileError::new)?;
+
+        let mut program = Program::new();
+        CodeGenerator::new(&mut program)
+            .generate(&ast)
+            .map_err(CompileError::new)?;
+        program.natives = natives.clone();
+
+        for section in [Section::Begin, Section::Main, Section::End] {
+            // every section ends with a return instruction, regardless of whether it has rules
+            if program.chunk(section).code.len() > 1 {
+                program.sections_with_rules.push(section);
+            }
+        }
+        if optimize {
+            program.optimize();
+        }
+        Ok(program)
+    }
+
+    /// Replaces the code of each section of the program with its optimized code
+    fn optimize(&mut self) {
+        for section in [Section::Begin, Section::Main, Section::End] {
+            // code that cannot be optimized is run as is
+            if let Some(optimized) = optimizer::optimize(self.chunk(section)) {
+                #[cfg(debug_assertions)]
+                optimized.disassemble_chunk(format!("{:?} (optimized)", section).as_str());
+                *self.chunk_mut(section) = optimized;
+            }
+        }
+    }
+
+    /// Retrieves the chunk for a section of the program
+    ///
+    /// # Arguments
+    /// - `section` the section to retrieve
+    pub(crate) fn chunk(&self, section: Section) -> &Chunk {
+        match section {
+            Section::Begin => &self.begin,
+            Section::Main => &self.main,
+            Section::End => &self.end,
+        }
+    }
+
+    /// Retrieves the chunk for a section of the program for writing
+    ///
+    /// # Arguments
+    /// - `section` the section to retrieve
+    pub(crate) fn chunk_mut(&mut self, section: Section) -> &mut Chunk {
+        match section {
+            Section::Begin => &mut self.begin,
+            Section::Main => &mut self.main,
+            Section::End => &mut self.end,
+        }
+    }
+
+    /// Retrieves the slot of a global variable, assigning it one if it has never been referenced before
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    pub(crate) fn resolve_global(&mut self, name: &str) -> usize {
+        self.globals.resolve(name)
+    }
+
+    /// Retrieves the slot of an array, assigning it one if it has never been referenced before
+    ///
+    /// # Arguments
+    /// - `name` the name of the array
+    pub(crate) fn resolve_array(&mut self, name: &str) -> usize {
+        self.arrays.resolve(name)
+    }
+
+    /// Retrieves the slots of the global variables referenced by the program
+    pub(crate) fn globals(&self) -> &SymbolTable {
+        &self.globals
+    }
+
+    /// Retrieves the slots of the arrays referenced by the program
+    pub(crate) fn arrays(&self) -> &SymbolTable {
+        &self.arrays
+    }
+
+    /// Retrieves the native functions that the program may call
+    pub(crate) fn natives(&self) -> &NativeFunctions {
+        &self.natives
+    }
+
+    /// Determines whether or not any rules were compiled into a section of the program
+    ///
+    /// # Arguments
+    /// - `section` the section to inspect
+    ///
+    /// # Return value
+    /// `true` if the section has at least one rule, `false` otherwise
+    pub(crate) fn has_rules(&self, section: Section) -> bool {
+        self.sections_with_rules.contains(&section)
+    }
+}
+
+#[cfg(test)]
+mod compilation {
+    use super::*;
+    use crate::chunk::OpCode;
+    use crate::error::{Error, ErrorKind};
+
+    fn compile_source(source: &str) -> Result<Program, CompileError> {
+        Program::compile(source)
+    }
+
+    fn compile_unoptimized(source: &str) -> Result<Program, CompileError> {
+        let tokens = Scanner::new(String::from(source)).scan();
+        Program::compile_tokens(&tokens, &NativeFunctions::new(), false)
+    }
+
+    #[test]
+    fn it_compiles_each_section_of_a_program() {
+        let program = compile_source("BEGIN { print 1; } { print 2; } END { print 3; }").unwrap();
+
+        assert!(program.has_rules(Section::Begin));
+        assert!(program.has_rules(Section::Main));
+        assert!(program.has_rules(Section::End));
+    }
+
+    #[test]
+    fn it_leaves_sections_without_rules_empty() {
+        let program = compile_source("BEGIN { print 1; }").unwrap();
+
+        assert!(program.has_rules(Section::Begin));
+        assert!(!program.has_rules(Section::Main));
+        assert!(!program.has_rules(Section::End));
+    }
+
+    #[test]
+    fn it_shares_slots_between_sections() {
+        let program =
+            compile_source("BEGIN { foo = 1; } { bar[1] = foo; } END { print foo; }").unwrap();
+
+        assert_eq!(program.globals().get("foo"), Some(0));
+        assert_eq!(program.globals().len(), 1);
+        assert_eq!(program.arrays().get("bar"), Some(0));
+        assert_eq!(program.globals().get("bar"), None);
+    }
+
+    #[test]
+    fn it_returns_an_error_for_too_many_constants() {
+        let statements: String = (0..=u16::MAX as u32 + 1)
+            .map(|number| format!("x = {};", number))
+            .collect();
+
+        let error = compile_source(&format!("BEGIN {{ {} }}", statements))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.errors()[0],
+            Error::new(ErrorKind::Compile, "Too many constants in one chunk.").with_line(1)
+        );
+    }
+
+    #[test]
+    fn it_only_widens_jumps_that_need_it() {
+        let program = compile_unoptimized(&format!(
+            "BEGIN {{ if (x) y = 1; while (x) {{ {} }} }}",
+            "x=x+1;".repeat(7000)
+        ))
+        .unwrap();
+        let code = &program.chunk(Section::Begin).code;
+
+        // the jump over the rule's action spans the loop, but the jump over the `if` statement's body does not
+        assert_eq!(code[3], OpCode::JumpIfFalseWide as u8);
+        assert_eq!(code[12], OpCode::JumpIfFalse as u8);
+        assert!(code.contains(&(OpCode::LoopWide as u8)));
+    }
+
+    #[test]
+    fn it_keeps_track_of_rules_that_optimize_away() {
+        let program = compile_source("BEGIN { } { }").unwrap();
+
+        assert_eq!(
+            program.chunk(Section::Main).code,
+            vec![OpCode::OpReturn as u8]
+        );
+        assert!(program.has_rules(Section::Begin));
+        assert!(program.has_rules(Section::Main));
+        assert!(!program.has_rules(Section::End));
+    }
+
+    #[test]
+    fn it_returns_an_error_for_an_invalid_program() {
+        let error = compile_source("{ print 1;").err().unwrap();
+        assert_eq!(
+            error.errors(),
+            [Error::new(
+                ErrorKind::Syntax,
+                "syntax error at end: Expect '}' after block."
+            )
+            .with_line(1)
+            .with_column(11)]
+        );
+    }
+}
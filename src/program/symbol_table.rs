@@ -0,0 +1,96 @@
+//! Module for resolving the names of variables to the slots they are stored in
+
+use std::collections::HashMap;
+
+/// A table of variable names, each of which is assigned a numeric slot
+///
+/// Slots are assigned in the order in which names are first resolved, starting at zero, so that
+/// the values of variables may be stored in a `Vec` and looked up by index at runtime.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    slots: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    /// Instantiates a new, empty symbol table
+    pub fn new() -> SymbolTable {
+        SymbolTable::default()
+    }
+
+    /// Retrieves the slot of a name, assigning it the next slot if it has never been seen before
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    ///
+    /// # Return value
+    /// the slot of the variable
+    pub fn resolve(&mut self, name: &str) -> usize {
+        if let Some(slot) = self.slots.get(name) {
+            return *slot;
+        }
+
+        let slot = self.slots.len();
+        self.slots.insert(String::from(name), slot);
+        slot
+    }
+
+    /// Retrieves the slot of a name, without assigning one
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    ///
+    /// # Return value
+    /// - the slot of the variable
+    /// - `None` if the name has never been resolved
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.slots.get(name).copied()
+    }
+
+    /// The number of slots that have been assigned
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+#[cfg(test)]
+mod slot_resolution {
+    use super::*;
+
+    #[test]
+    fn it_returns_the_slot_of_a_name_that_exists() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.resolve("foo");
+        symbol_table.resolve("bar");
+        symbol_table.resolve("baz");
+        let bar_slot = symbol_table.resolve("bar");
+
+        assert_eq!(bar_slot, 1);
+        assert_eq!(symbol_table.len(), 3);
+    }
+
+    #[test]
+    fn it_assigns_a_never_seen_before_name_the_next_slot() {
+        let mut symbol_table = SymbolTable::new();
+
+        // names are deliberately resolved out of alphabetical order
+        let foo_slot = symbol_table.resolve("foo");
+        assert_eq!(foo_slot, 0);
+        assert_eq!(symbol_table.resolve("foo"), 0);
+
+        let bar_slot = symbol_table.resolve("bar");
+        assert_eq!(bar_slot, 1);
+        assert_eq!(symbol_table.resolve("bar"), 1);
+        assert_eq!(symbol_table.resolve("foo"), 0);
+        assert_eq!(symbol_table.len(), 2);
+    }
+
+    #[test]
+    fn it_looks_up_a_slot_without_assigning_one() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.resolve("foo");
+
+        assert_eq!(symbol_table.get("foo"), Some(0));
+        assert_eq!(symbol_table.get("bar"), None);
+        assert_eq!(symbol_table.len(), 1);
+    }
+}
@@ -0,0 +1,944 @@
+//! Records of input data, and the fields they are split into
+
+use crate::characters::Characters;
+use crate::error::Error;
+use crate::value::Value;
+use regex::Regex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::iter;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// How the records of input data are split into fields
+#[derive(Clone, Debug)]
+pub enum FieldSplitter {
+    /// fields are separated by a field separator, `FS`, of a single character
+    Separator(Arc<str>),
+    /// fields are separated by the matches of a regular expression, a field separator of more than one character
+    SeparatorPattern(Arc<Regex>),
+    /// fields are each successive match of a regular expression, `FPAT`
+    Pattern(Arc<Regex>),
+    /// fields are found at fixed positions, `FIELDWIDTHS`
+    Widths(Arc<[FieldWidth]>),
+    /// fields are comma separated values, which may be quoted as described by RFC 4180
+    Csv,
+}
+
+impl FieldSplitter {
+    /// Creates the way records are split by a field separator
+    ///
+    /// A single space splits records by whitespace, an empty field separator splits records into characters, and any
+    /// other single character separates fields as is. A field separator of more than one character is a regular
+    /// expression.
+    ///
+    /// # Arguments
+    /// - `field_separator` the field separator
+    ///
+    /// # Return value
+    /// - how records are split by the field separator
+    /// - an error if the field separator is not a valid regular expression
+    pub fn separator(field_separator: &str) -> Result<FieldSplitter, regex::Error> {
+        if field_separator.chars().count() <= 1 {
+            Ok(FieldSplitter::Separator(Arc::from(field_separator)))
+        } else {
+            Ok(FieldSplitter::SeparatorPattern(Arc::new(Regex::new(
+                field_separator,
+            )?)))
+        }
+    }
+
+    /// Retrieves the name of the way records are split, as reported by `PROCINFO["FS"]`
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldSplitter::Separator(_) | FieldSplitter::SeparatorPattern(_) => "FS",
+            FieldSplitter::Pattern(_) => "FPAT",
+            FieldSplitter::Widths(_) => "FIELDWIDTHS",
+            FieldSplitter::Csv => "CSV",
+        }
+    }
+}
+
+/// The position of a single fixed width field, relative to the end of the field before it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldWidth {
+    /// the number of characters to skip over before the field begins
+    skip: usize,
+    /// the number of characters in the field, or `None` if the field takes up the rest of the record
+    width: Option<usize>,
+}
+
+impl FieldWidth {
+    /// Parses the value of `FIELDWIDTHS`
+    ///
+    /// The value is a whitespace separated list of field widths, each of which may be prefixed by a number of
+    /// characters to skip before the field (`skip:width`). The last width may be `*`, in which case the last field
+    /// takes up the rest of the record.
+    ///
+    /// # Arguments
+    /// - `field_widths` the value to parse, e.g. `"5 2:10 *"`
+    ///
+    /// # Return value
+    /// - the position of each field
+    /// - `None` if the value is not a valid list of field widths
+    pub fn parse_all(field_widths: &str) -> Option<Vec<FieldWidth>> {
+        let entries: Vec<&str> = field_widths.split_whitespace().collect();
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let (skip, width) = match entry.split_once(':') {
+                    Some((skip, width)) => (skip.parse().ok()?, width),
+                    None => (0, *entry),
+                };
+                let width = match width {
+                    // only the last field may take up the rest of the record
+                    "*" if index == entries.len() - 1 => None,
+                    width => Some(width.parse().ok()?),
+                };
+                Some(FieldWidth { skip, width })
+            })
+            .collect()
+    }
+}
+
+impl Default for FieldSplitter {
+    /// Fields separated by whitespace, as they are by default
+    fn default() -> FieldSplitter {
+        FieldSplitter::Separator(Arc::from(" "))
+    }
+}
+
+/// The index of each field of the records of an input, by the name given to it by the input's header
+pub type Header = HashMap<String, usize>;
+
+/// A single record of input data, split into fields based on a field separator
+///
+/// A record is only split the first time one of its fields is referenced, so that programs that only reference the
+/// record as a whole (`$0`) don't pay for splitting it. Fields are stored as ranges of the record's text, and the
+/// [Value] of each field is determined once, the first time it is referenced. The value of a field that looks like a
+/// number holds the number it converts to, and shares its text with every reference to the field.
+#[derive(Clone)]
+pub struct Record {
+    /// the text of the record, `$0`
+    text: String,
+    /// how the record is split into fields
+    splitter: FieldSplitter,
+    /// the value of the record as a whole, once it has been referenced
+    value: RefCell<Option<Value>>,
+    /// the fields of the record, once it has been split
+    fields: RefCell<Option<Vec<Field>>>,
+    /// the names of the record's fields, if the input it was read from has a header
+    header: Option<Arc<Header>>,
+    /// what a single character of the record is, when splitting it into characters or fixed width fields
+    characters: Characters,
+}
+
+/// A single field of a record
+#[derive(Clone)]
+struct Field {
+    /// the range of the record's text that the field spans
+    range: Range<usize>,
+    /// whether the field's text is surrounded by double quotes, which are not a part of its value
+    quoted: bool,
+    /// the value of the field, once it has been referenced
+    value: Option<Value>,
+}
+
+impl Record {
+    /// Constructs a new `Record`
+    ///
+    /// # Arguments
+    /// - `text` the text of the record
+    /// - `splitter` how the record is split into fields
+    pub fn new(text: String, splitter: FieldSplitter) -> Record {
+        Record {
+            text,
+            splitter,
+            value: RefCell::new(None),
+            fields: RefCell::new(None),
+            header: None,
+            characters: Characters::default(),
+        }
+    }
+
+    /// Sets what a single character of the record is, when splitting it into characters or fixed width fields
+    ///
+    /// # Arguments
+    /// - `characters` what a single character is
+    pub fn with_characters(mut self, characters: Characters) -> Record {
+        self.characters = characters;
+        self
+    }
+
+    /// Names the fields of the record
+    ///
+    /// # Arguments
+    /// - `header` the header of the input the record was read from
+    pub fn with_header(mut self, header: Arc<Header>) -> Record {
+        self.header = Some(header);
+        self
+    }
+
+    /// Retrieves the names of the record's fields, if the input it was read from has a header
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_deref()
+    }
+
+    /// Retrieves the value of a field of the record
+    ///
+    /// # Arguments
+    /// - `index` the index of the field, where `0` is the record as a whole
+    ///
+    /// # Return value
+    /// - the value of the field, a numeric string if it looks like a number
+    /// - `None` if the record does not have that many fields
+    pub fn field(&self, index: usize) -> Option<Value> {
+        if index == 0 {
+            let mut value = self.value.borrow_mut();
+            return Some(
+                value
+                    .get_or_insert_with(|| Value::from_user_input(self.text.clone()))
+                    .clone(),
+            );
+        }
+
+        let mut fields = self.fields.borrow_mut();
+        let field = fields
+            .get_or_insert_with(|| self.split())
+            .get_mut(index - 1)?;
+        let text = self.characters.slice(&self.text, field.range.clone());
+        let quoted = field.quoted;
+        Some(
+            field
+                .value
+                .get_or_insert_with(|| {
+                    let text = if quoted {
+                        unquote(&text)
+                    } else {
+                        text.into_owned()
+                    };
+                    Value::from_user_input(text)
+                })
+                .clone(),
+        )
+    }
+
+    /// Retrieves the value of every field of the record, not including the record as a whole
+    pub fn fields(&self) -> Vec<Value> {
+        let count = self
+            .fields
+            .borrow_mut()
+            .get_or_insert_with(|| self.split())
+            .len();
+        (1..=count).filter_map(|index| self.field(index)).collect()
+    }
+
+    /// Splits the record into fields
+    fn split(&self) -> Vec<Field> {
+        let field_separator = match &self.splitter {
+            // https://www.gnu.org/software/gawk/manual/html_node/Single-Character-Fields.html
+            // > Traditionally, the behavior of FS equal to "" was not defined. In this case, most versions of Unix awk
+            // > simply treat the entire record as only having one field. (d.c.) In compatibility mode (see section
+            // > Command-Line Options), if FS is the null string, then gawk also behaves this way.
+            // r-awk chooses to respect FS="" as g-awk does, making each character a field of its own
+            FieldSplitter::Separator(field_separator) if field_separator.is_empty() => {
+                return split_characters(self.characters, &self.text)
+            }
+            FieldSplitter::Separator(field_separator) => field_separator,
+            FieldSplitter::SeparatorPattern(separator) => {
+                return split_separator_matches(separator, &self.text)
+            }
+            FieldSplitter::Pattern(pattern) => return split_matches(pattern, &self.text),
+            FieldSplitter::Widths(widths) => {
+                return split_widths(widths, self.characters, &self.text)
+            }
+            FieldSplitter::Csv => return split_csv(&self.text),
+        };
+
+        let start_of_text = self.text.as_ptr() as usize;
+        split_user_data(field_separator, &self.text)
+            .into_iter()
+            .map(|field| {
+                // every field is a slice of the record's text
+                let start = field.as_ptr() as usize - start_of_text;
+                Field {
+                    range: start..start + field.len(),
+                    quoted: false,
+                    value: None,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for Record {
+    /// An empty record, as seen by `BEGIN` actions
+    fn default() -> Record {
+        Record::new(String::new(), FieldSplitter::default())
+    }
+}
+
+/// Splits a record into the fields in between the matches of a regular expression
+///
+/// Unlike splitting by whitespace, a match at the beginning or end of the record separates an empty field. A match that
+/// is empty does not separate fields, and an empty record has no fields.
+///
+/// # Arguments
+/// - `separator` the regular expression matching the text that separates fields
+/// - `text` the text of the record
+fn split_separator_matches(separator: &Regex, text: &str) -> Vec<Field> {
+    if text.is_empty() {
+        return vec![];
+    }
+
+    let mut fields = vec![];
+    let mut start = 0;
+    for found in separator
+        .find_iter(text)
+        .filter(|found| !found.range().is_empty())
+    {
+        fields.push(Field {
+            range: start..found.start(),
+            quoted: false,
+            value: None,
+        });
+        start = found.end();
+    }
+    fields.push(Field {
+        range: start..text.len(),
+        quoted: false,
+        value: None,
+    });
+    fields
+}
+
+/// Splits a record into fields made up of each successive match of a regular expression
+///
+/// A match that is empty is a field of its own, unless it immediately follows the previous match. Unlike the leftmost
+/// longest matching of POSIX regular expressions, alternatives are preferred in the order they are written.
+///
+/// # Arguments
+/// - `pattern` the regular expression describing the contents of a field
+/// - `text` the text of the record
+fn split_matches(pattern: &Regex, text: &str) -> Vec<Field> {
+    pattern
+        .find_iter(text)
+        .map(|found| Field {
+            range: found.range(),
+            quoted: false,
+            value: None,
+        })
+        .collect()
+}
+
+/// Splits a record into fields of a single character each
+///
+/// # Arguments
+/// - `characters` what a single character is
+/// - `text` the text of the record
+fn split_characters(characters: Characters, text: &str) -> Vec<Field> {
+    characters
+        .offsets(text)
+        .windows(2)
+        .map(|offsets| Field {
+            range: offsets[0]..offsets[1],
+            quoted: false,
+            value: None,
+        })
+        .collect()
+}
+
+/// Splits a record into fields found at fixed positions
+///
+/// Positions are counted in characters. A field that would begin past the end of the record is left out, and a field
+/// that would end past the end of the record is cut short.
+///
+/// # Arguments
+/// - `widths` the position of each field
+/// - `characters` what a single character is
+/// - `text` the text of the record
+fn split_widths(widths: &[FieldWidth], characters: Characters, text: &str) -> Vec<Field> {
+    // the byte offset of each character, followed by the end of the record
+    let offsets = characters.offsets(text);
+    let length = offsets.len() - 1;
+
+    let mut fields = vec![];
+    let mut position = 0;
+    for field_width in widths {
+        position += field_width.skip;
+        if position >= length {
+            break;
+        }
+        let end = field_width
+            .width
+            .map_or(length, |width| length.min(position + width));
+        fields.push(Field {
+            range: offsets[position]..offsets[end],
+            quoted: false,
+            value: None,
+        });
+        position = end;
+    }
+    fields
+}
+
+/// Joins lines of CSV input into records, as a quoted field may span more than one line
+///
+/// A trailing carriage return is removed from each line, so that CRLF line endings are treated as newlines.
+///
+/// # Arguments
+/// - `lines` the lines of input, without their trailing newlines
+///
+/// # Return value
+/// an iterator over the records, or the error that prevented a line from being read
+pub(crate) fn csv_records<'a>(
+    mut lines: impl Iterator<Item = Result<String, Error>> + 'a,
+) -> impl Iterator<Item = Result<String, Error>> + 'a {
+    let mut next_line = move || {
+        lines.next().map(|line| {
+            line.map(|mut line| {
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+                line
+            })
+        })
+    };
+
+    iter::from_fn(move || {
+        let mut record = match next_line()? {
+            Ok(line) => line,
+            Err(err) => return Some(Err(err)),
+        };
+        // every escaped quote is written as a pair, so a field is left open by an odd number of quotes
+        let mut is_open = record.matches('"').count() % 2 == 1;
+        while is_open {
+            match next_line() {
+                Some(Ok(line)) => {
+                    is_open ^= line.matches('"').count() % 2 == 1;
+                    record.push('\n');
+                    record.push_str(&line);
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                // the quoted field was never closed, which leaves it open until the end of the input
+                None => break,
+            }
+        }
+        Some(Ok(record))
+    })
+}
+
+/// Splits a record of comma separated values into fields, as described by RFC 4180
+///
+/// A field that begins with a double quote runs until its closing quote, and may contain commas, newlines, and
+/// escaped quotes (`""`). An empty record has no fields.
+///
+/// # Arguments
+/// - `text` the text of the record
+fn split_csv(text: &str) -> Vec<Field> {
+    let mut fields = vec![];
+    if text.is_empty() {
+        return fields;
+    }
+
+    // quotes and commas are single bytes, so the record's text can be scanned byte by byte
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    loop {
+        let quoted = bytes.get(start) == Some(&b'"');
+        let mut end = start;
+        if quoted {
+            end += 1;
+            while end < bytes.len() {
+                if bytes[end] == b'"' {
+                    if bytes.get(end + 1) == Some(&b'"') {
+                        end += 2;
+                        continue;
+                    }
+                    end += 1;
+                    break;
+                }
+                end += 1;
+            }
+        }
+        // anything found between a closing quote and the next comma is kept a part of the field
+        let end = text[end..]
+            .find(',')
+            .map_or(text.len(), |comma| end + comma);
+
+        fields.push(Field {
+            range: start..end,
+            quoted,
+            value: None,
+        });
+        if end == text.len() {
+            return fields;
+        }
+        start = end + 1;
+    }
+}
+
+/// Removes the quotes surrounding a CSV field, and unescapes the quotes within it
+///
+/// # Arguments
+/// - `field` the text of the field, beginning with a double quote
+fn unquote(field: &str) -> String {
+    let mut unquoted = String::with_capacity(field.len());
+    let mut chars = field.chars().skip(1).peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '"' {
+            unquoted.push(ch);
+        } else if chars.peek() == Some(&'"') {
+            chars.next();
+            unquoted.push('"');
+        }
+    }
+    unquoted
+}
+
+/// Splits data to be used as field variables based on the provided field separator.
+fn split_user_data<'a>(field_separator: &str, data_received: &'a str) -> Vec<&'a str> {
+    // an empty field separator splits a record into characters, which is done by [split_characters], and a field
+    // separator of more than one character is a regular expression, which is done by [split_separator_matches]
+    if field_separator.eq(" ") {
+        // Case: a single empty string, where we must strip all leading, trailing, and in-between whitespace
+        data_received.split_whitespace().collect::<Vec<&str>>()
+    } else {
+        // Case: field separator is single character separator that is not (" "), which may be more than one byte long
+        data_received.split(field_separator).collect::<Vec<&str>>()
+    }
+}
+
+#[cfg(test)]
+mod field_splitting {
+    use super::*;
+    use crate::binary;
+
+    #[test]
+    fn it_splits_data_by_single_char_fs() {
+        let test_data = "  Hello,World  ,I\tam,someone! ";
+
+        let split_data = split_user_data(",", test_data);
+
+        assert_eq!(split_data, vec!["  Hello", "World  ", "I\tam", "someone! "]);
+    }
+
+    #[test]
+    fn it_does_not_truncate_whitespace_for_fs() {
+        let test_data = " Alice  ,40 ,25 ";
+
+        let split_data = split_user_data(",", test_data);
+
+        assert_eq!(split_data, vec![" Alice  ", "40 ", "25 "]);
+    }
+
+    #[test]
+    fn it_counts_two_consecutive_fs_as_empty_record() {
+        let test_data = "Hello,,World!";
+
+        let split_data = split_user_data(",", test_data);
+
+        assert_eq!(split_data, vec!["Hello", "", "World!"]);
+    }
+
+    #[test]
+    fn it_splits_nothing_when_fs_not_found() {
+        let test_data = "  Hello World  I\tam  someone! ";
+
+        let split_data = split_user_data(",", test_data);
+
+        assert_eq!(split_data, vec![test_data]);
+    }
+
+    #[test]
+    fn it_splits_data_by_whitespace_when_no_fs_provided() {
+        let test_data = "  Hello World  I\tam  someone! ";
+
+        // awk/gawk/r-awk defaults to a single empty character, provide it in the test to satisfy the contract of the fn
+        let split_data = split_user_data(" ", test_data);
+
+        assert_eq!(split_data, vec!["Hello", "World", "I", "am", "someone!"]);
+    }
+
+    #[test]
+    fn it_splits_data_by_a_single_multi_byte_char_fs() {
+        let split_data = split_user_data("→", "a→b→c");
+
+        assert_eq!(split_data, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn it_splits_data_by_byte_when_every_byte_is_a_character() {
+        let record = Record::new(String::from("aé"), FieldSplitter::Separator(Arc::from("")))
+            .with_characters(Characters::Bytes);
+
+        let fields: Vec<Vec<u8>> = record
+            .fields()
+            .iter()
+            .map(|field| binary::encode(&field.str_value()).into_owned())
+            .collect();
+        assert_eq!(fields, vec![vec![b'a'], vec![0xc3], vec![0xa9]]);
+    }
+
+    #[test]
+    fn it_splits_data_by_character_when_null_string_fs_provided() {
+        // https://www.gnu.org/software/gawk/manual/html_node/Single-Character-Fields.html
+        // > Traditionally, the behavior of FS equal to "" was not defined. In this case, most versions of Unix awk
+        // > simply treat the entire record as only having one field. (d.c.) In compatibility mode (see section
+        // > Command-Line Options), if FS is the null string, then gawk also behaves this way.
+        // r-awk chooses to respect FS="" as g-awk does
+        let test_data = "Hello World";
+
+        let split_data: Vec<&str> = split_characters(Characters::Utf8, test_data)
+            .into_iter()
+            .map(|field| &test_data[field.range])
+            .collect();
+
+        assert_eq!(
+            split_data,
+            vec!["H", "e", "l", "l", "o", " ", "W", "o", "r", "l", "d"]
+        );
+    }
+
+    #[test]
+    fn it_splits_empty_data_when_fs_is_null_string() {
+        let test_data = "";
+
+        let split_data = split_characters(Characters::Utf8, test_data);
+
+        assert_eq!(split_data.len(), 0);
+    }
+
+    #[test]
+    fn it_splits_data_entirely_when_fs_matches_test_data_single_char() {
+        let test_data = "a";
+
+        let split_data = split_user_data("a", test_data);
+
+        assert_eq!(split_data, vec!["", ""]);
+    }
+
+    #[test]
+    fn it_splits_data_when_fs_matches_leading_char() {
+        let test_data = "abac";
+
+        let split_data = split_user_data("a", test_data);
+
+        assert_eq!(split_data, vec!["", "b", "c"]);
+    }
+
+    #[test]
+    fn it_splits_data_when_fs_matches_trailing_char() {
+        let test_data = "baca";
+
+        let split_data = split_user_data("a", test_data);
+
+        assert_eq!(split_data, vec!["b", "c", ""]);
+    }
+
+    // See https://www.gnu.org/software/gawk/manual/html_node/Regexp-Field-Splitting.html
+    #[test]
+    fn it_splits_data_by_whitespace_via_regex() {
+        let test_data = "  Hello World  I\tam  someone! ";
+
+        let split_data: Vec<&str> =
+            split_separator_matches(&Regex::new("[ \t\n]+").unwrap(), test_data)
+                .into_iter()
+                .map(|field| &test_data[field.range])
+                .collect();
+
+        // unlike the default field separator, leading and trailing whitespace separate empty fields
+        assert_eq!(
+            split_data,
+            vec!["", "Hello", "World", "I", "am", "someone!", ""]
+        );
+    }
+
+    #[test]
+    fn it_does_not_separate_fields_by_an_empty_match() {
+        let split_data = split_separator_matches(&Regex::new(",*").unwrap(), "a,,b");
+
+        let ranges: Vec<Range<usize>> = split_data.into_iter().map(|field| field.range).collect();
+        assert_eq!(ranges, vec![0..1, 3..4]);
+    }
+
+    #[test]
+    fn it_splits_by_a_regex_when_fs_is_more_than_one_character() {
+        let record = Record::new(
+            String::from("a, b;c"),
+            FieldSplitter::separator("[,;] ?").unwrap(),
+        );
+
+        assert_eq!(
+            record.fields(),
+            vec![
+                Value::String(String::from("a")),
+                Value::String(String::from("b")),
+                Value::String(String::from("c")),
+            ]
+        );
+        assert!(FieldSplitter::separator("[,").is_err());
+    }
+}
+
+#[cfg(test)]
+mod lazy_fields {
+    use super::*;
+    use crate::value::StrNum;
+    use std::ptr;
+
+    fn record(text: &str) -> Record {
+        Record::new(String::from(text), FieldSplitter::Separator(Arc::from(",")))
+    }
+
+    #[test]
+    fn it_does_not_split_a_record_referenced_as_a_whole() {
+        let record = record("a,b");
+
+        assert_eq!(record.field(0), Some(Value::String(String::from("a,b"))));
+        assert!(record.fields.borrow().is_none());
+    }
+
+    #[test]
+    fn it_splits_a_record_once_a_field_is_referenced() {
+        let record = record(" 1 ,b");
+
+        assert_eq!(record.field(1), Some(Value::StrNum(StrNum::from(" 1 "))));
+        assert_eq!(record.field(2), Some(Value::String(String::from("b"))));
+        assert_eq!(record.field(3), None);
+
+        let fields = record.fields.borrow();
+        let ranges: Vec<Range<usize>> = fields
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|field| field.range.clone())
+            .collect();
+        assert_eq!(ranges, vec![0..3, 4..5]);
+    }
+
+    #[test]
+    fn it_caches_the_value_of_a_referenced_field() {
+        let record = record("1,2");
+
+        record.field(2);
+
+        let fields = record.fields.borrow();
+        let fields = fields.as_ref().unwrap();
+        assert_eq!(fields[0].value, None);
+        assert_eq!(fields[1].value, Some(Value::StrNum(StrNum::from("2"))));
+    }
+
+    #[test]
+    fn it_shares_the_text_of_a_numeric_field_between_references() {
+        let record = record("1,2.5");
+
+        let (Some(Value::StrNum(first)), Some(Value::StrNum(second))) =
+            (record.field(2), record.field(2))
+        else {
+            panic!("the field should be a numeric string");
+        };
+        assert!(ptr::eq(first.as_str(), second.as_str()));
+        assert_eq!(Value::StrNum(second).num_value(), 2.5);
+    }
+}
+
+#[cfg(test)]
+mod csv {
+    use super::*;
+    use crate::value::StrNum;
+
+    /// Retrieves the string value of each field of a CSV record
+    fn fields(text: &str) -> Vec<String> {
+        let record = Record::new(String::from(text), FieldSplitter::Csv);
+        (1..)
+            .map_while(|index| record.field(index))
+            .map(|value| value.str_value())
+            .collect()
+    }
+
+    /// Joins the provided lines into CSV records
+    fn records(lines: &[&str]) -> Vec<String> {
+        csv_records(lines.iter().map(|line| Ok(String::from(*line))))
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn it_splits_unquoted_fields_on_commas() {
+        assert_eq!(fields("a, b ,,c,"), vec!["a", " b ", "", "c", ""]);
+    }
+
+    #[test]
+    fn it_splits_an_empty_record_into_no_fields() {
+        assert_eq!(fields(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_removes_the_quotes_around_a_field() {
+        assert_eq!(fields("\"Smith, John\",42"), vec!["Smith, John", "42"]);
+    }
+
+    #[test]
+    fn it_unescapes_doubled_quotes() {
+        assert_eq!(
+            fields("\"say \"\"hi\"\"\",\"\"\"\",\"\""),
+            vec!["say \"hi\"", "\"", ""]
+        );
+    }
+
+    #[test]
+    fn it_keeps_newlines_in_a_quoted_field() {
+        assert_eq!(
+            fields("\"1 Main St\nApt 2\",x"),
+            vec!["1 Main St\nApt 2", "x"]
+        );
+    }
+
+    #[test]
+    fn it_keeps_text_following_a_closing_quote() {
+        assert_eq!(fields("\"a\"b,c"), vec!["ab", "c"]);
+    }
+
+    #[test]
+    fn it_treats_a_number_in_quotes_as_a_numeric_string() {
+        let record = Record::new(String::from("\"42\""), FieldSplitter::Csv);
+
+        assert_eq!(record.field(1), Some(Value::StrNum(StrNum::from("42"))));
+    }
+
+    #[test]
+    fn it_joins_lines_of_a_quoted_field_into_one_record() {
+        assert_eq!(
+            records(&["a,\"multi", "line\",b", "\"x\"\"", "\"\"y\"", "last"]),
+            vec!["a,\"multi\nline\",b", "\"x\"\"\n\"\"y\"", "last"]
+        );
+    }
+
+    #[test]
+    fn it_removes_carriage_returns_from_the_end_of_lines() {
+        assert_eq!(
+            records(&["a,b\r", "\"c\r", "d\"\r"]),
+            vec!["a,b", "\"c\nd\""]
+        );
+    }
+
+    #[test]
+    fn it_ends_an_unterminated_quoted_field_with_the_input() {
+        assert_eq!(records(&["\"open", "more"]), vec!["\"open\nmore"]);
+    }
+}
+
+#[cfg(test)]
+mod field_patterns {
+    use super::*;
+
+    /// Retrieves the string value of each field of a record split with the provided pattern
+    fn fields(pattern: &str, text: &str) -> Vec<String> {
+        let splitter = FieldSplitter::Pattern(Arc::new(Regex::new(pattern).unwrap()));
+        let record = Record::new(String::from(text), splitter);
+        (1..)
+            .map_while(|index| record.field(index))
+            .map(|value| value.str_value())
+            .collect()
+    }
+
+    #[test]
+    fn it_splits_a_record_into_each_match() {
+        assert_eq!(
+            fields("[a-z]+=[0-9]+", "at 10:00 user=42 took ms=17!"),
+            vec!["user=42", "ms=17"]
+        );
+    }
+
+    #[test]
+    fn it_splits_a_record_without_a_match_into_no_fields() {
+        assert_eq!(fields("[0-9]+", "none here"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_keeps_empty_matches_between_separators() {
+        assert_eq!(
+            fields("(\"[^\"]+\")|([^,]*)", "a,,\"b,c\",d"),
+            vec!["a", "", "\"b,c\"", "d"]
+        );
+    }
+
+    #[test]
+    fn it_keeps_a_leading_empty_match() {
+        assert_eq!(fields("[^,]*", ",a"), vec!["", "a"]);
+    }
+}
+
+#[cfg(test)]
+mod field_widths {
+    use super::*;
+
+    /// Retrieves the string value of each field of a record split with the provided field widths
+    fn fields(field_widths: &str, text: &str) -> Vec<String> {
+        let widths = FieldWidth::parse_all(field_widths).unwrap();
+        let record = Record::new(String::from(text), FieldSplitter::Widths(Arc::from(widths)));
+        (1..)
+            .map_while(|index| record.field(index))
+            .map(|value| value.str_value())
+            .collect()
+    }
+
+    #[test]
+    fn it_parses_widths_skips_and_a_trailing_star() {
+        assert_eq!(
+            FieldWidth::parse_all(" 5 2:10\t* "),
+            Some(vec![
+                FieldWidth {
+                    skip: 0,
+                    width: Some(5)
+                },
+                FieldWidth {
+                    skip: 2,
+                    width: Some(10)
+                },
+                FieldWidth {
+                    skip: 0,
+                    width: None
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_widths() {
+        assert_eq!(FieldWidth::parse_all("5 x"), None);
+        assert_eq!(FieldWidth::parse_all("5 -1"), None);
+        assert_eq!(FieldWidth::parse_all("1: 2"), None);
+        assert_eq!(FieldWidth::parse_all("* 5"), None);
+    }
+
+    #[test]
+    fn it_splits_a_record_at_fixed_positions() {
+        assert_eq!(
+            fields("5 10 3", "ALICE1234567890ABCXYZ"),
+            vec!["ALICE", "1234567890", "ABC"]
+        );
+    }
+
+    #[test]
+    fn it_skips_characters_before_a_field() {
+        assert_eq!(fields("3 2:2", "abc--de"), vec!["abc", "de"]);
+    }
+
+    #[test]
+    fn it_takes_the_rest_of_the_record_for_a_trailing_star() {
+        assert_eq!(fields("2 1:*", "ab rest of it"), vec!["ab", "rest of it"]);
+    }
+
+    #[test]
+    fn it_cuts_fields_short_at_the_end_of_the_record() {
+        assert_eq!(fields("2 4 3", "abcde"), vec!["ab", "cde"]);
+    }
+
+    #[test]
+    fn it_counts_positions_in_characters() {
+        assert_eq!(fields("2 3", "héllo"), vec!["hé", "llo"]);
+    }
+}
@@ -1,30 +1,63 @@
 //! Runtime configuration structs and impl
 
+use crate::assignment::Assignment;
+
 pub struct RuntimeConfig {
-    // the path of the data files to read and run a program against
+    // the operands that follow the program, which are either paths of data files to read and run a program against, or
+    // variable assignments of the form `name=value` to perform when the operand is reached
     pub data_file_paths: Vec<String>,
     // a single character or regex to be used to split user input by
     pub field_separator: String,
+    // whether or not user input is read as comma separated values, in which case the field separator is not used
+    pub is_csv: bool,
+    // whether or not the first record of each data file is a header that names the fields of the records that follow
+    pub has_header: bool,
+    // whether or not each record of user input is a JSON value, whose contents are made available through the `J` array
+    pub is_jsonl: bool,
+    // whether or not records are separated by NUL bytes rather than newlines
+    pub is_null_separated: bool,
+    // whether or not every byte is treated as a character, rather than every UTF-8 encoded character
+    pub is_bytes: bool,
+    // whether or not the values given to `print` statements are written as comma separated values
+    pub is_ocsv: bool,
+    // whether or not the values given to `print` statements are written as JSON
+    pub is_ojson: bool,
+    // variable assignments provided via `-v name=value`, to be performed before the program is run
+    pub variable_assignments: Vec<Assignment>,
     // whether or not a single line of awk code is being interpreted. If so, terminate after a single line of code from
     // STDIN has been evaluated.
     pub is_eval: bool,
     // whether or not a single line of awk code is being interpreted without data. If so, terminate after no code from
     // STDIN has been evaluated and is a temporary stand-in for `BEGIN`.
     pub is_quick: bool,
+    // whether or not the program's bytecode is optimized once it has been compiled. Disabling the optimizer may be
+    // useful when debugging the bytecode that is generated for a program.
+    pub is_optimized: bool,
 }
 
 impl RuntimeConfig {
     pub fn new(
         data_file_paths: Vec<String>,
         field_separator: String,
+        variable_assignments: Vec<Assignment>,
         is_eval: bool,
         is_quick: bool,
+        is_optimized: bool,
     ) -> RuntimeConfig {
         RuntimeConfig {
             data_file_paths,
             field_separator,
+            is_csv: false,
+            has_header: false,
+            is_jsonl: false,
+            is_null_separated: false,
+            is_bytes: false,
+            is_ocsv: false,
+            is_ojson: false,
+            variable_assignments,
             is_eval,
             is_quick,
+            is_optimized,
         }
     }
 }
@@ -1,11 +1,44 @@
 use super::token::token_type::TokenType;
 use super::token::Token;
-use log::{debug, error};
+use log::debug;
 use std::collections::HashMap;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+/// A peekable stream of the characters of a corpus, which keeps track of the byte offset of the next character
+struct SourceChars<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// the byte offset of the next character in the corpus
+    offset: usize,
+}
 
-#[derive(Debug)]
-enum ScanError {
-    UnterminatedString,
+impl<'a> SourceChars<'a> {
+    /// Constructs a new `SourceChars`
+    ///
+    /// # Arguments
+    /// - `corpus` the corpus to stream the characters of
+    fn new(corpus: &'a str) -> SourceChars<'a> {
+        SourceChars {
+            chars: corpus.chars().peekable(),
+            offset: 0,
+        }
+    }
+
+    /// Retrieves the next character without consuming it
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+}
+
+impl<'a> Iterator for SourceChars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        self.offset += ch.len_utf8();
+        Some(ch)
+    }
 }
 
 #[derive(Debug)]
@@ -44,8 +77,11 @@ impl Scanner {
         let mut tokens: Vec<Token> = Vec::new();
         let mut current_line = 1;
 
-        let mut char_stream = self.input.chars().peekable();
+        let mut char_stream = SourceChars::new(&self.input);
         while char_stream.peek().is_some() {
+            // every token found in this iteration was scanned from the characters consumed by it
+            let first_new_token = tokens.len();
+            let token_start = char_stream.offset;
             let mut ch = char_stream.next().unwrap();
             debug!("Inspecting Character: '{}'", ch);
             match ch {
@@ -63,15 +99,13 @@ impl Scanner {
                 }
                 ',' => {
                     Scanner::report_scanned_character(ch, &TokenType::Comma);
-                    tokens.push(Token {
-                        lexeme: None,
-                        token_type: &TokenType::Comma,
-                        line: current_line,
-                    });
+                    tokens.push(Token::new(None, &TokenType::Comma, current_line));
                 }
                 '#' => {
                     // consume the rest of the line, as we've found a comment
-                    char_stream.find(|x| x == &'\n');
+                    if char_stream.find(|x| x == &'\n').is_some() {
+                        current_line += 1;
+                    }
                     Scanner::report_scanned_character(ch, &TokenType::Pound);
                 }
                 '{' => {
@@ -128,8 +162,6 @@ impl Scanner {
                             if let Some(escaped_ch) = char_stream.next() {
                                 ch = escaped_ch;
                                 string_parsed.push(ch);
-                            } else {
-                                panic!("{:?}", ScanError::UnterminatedString);
                             }
                             continue;
                         } else if next_ch == '\"' {
@@ -140,15 +172,19 @@ impl Scanner {
                         string_parsed.push(ch);
                     }
 
-                    if char_stream.peek().is_none() && !is_properly_terminated {
+                    if is_properly_terminated {
+                        tokens.push(Token::new(
+                            Some(string_parsed),
+                            &TokenType::DoubleQuote,
+                            current_line,
+                        ));
+                    } else {
                         // the end of the stream was reached, but the string wasn't terminated
-                        panic!("{:?}", ScanError::UnterminatedString);
+                        tokens.push(Token::error_token(
+                            String::from("Unterminated string."),
+                            current_line,
+                        ));
                     }
-
-                    let string_token =
-                        Token::new(Some(string_parsed), &TokenType::DoubleQuote, current_line);
-
-                    tokens.push(string_token);
                 }
                 '>' => {
                     if self.match_char('=', char_stream.peek()) {
@@ -376,7 +412,6 @@ impl Scanner {
                             current_line,
                         ));
                     } else {
-                        error!("ALERT: We found a character we can not handle, '{}'", ch);
                         tokens.push(Token::error_token(
                             String::from("Unexpected character."),
                             current_line,
@@ -384,19 +419,40 @@ impl Scanner {
                     }
                 }
             }
+
+            for token in &mut tokens[first_new_token..] {
+                // synthetic concatenation tokens take up no space before the token that follows them
+                let token_end = if token.token_type == &TokenType::StringConcat {
+                    token_start
+                } else {
+                    char_stream.offset
+                };
+                self.locate(token, token_start..token_end);
+            }
         }
 
         // Add the EOF token
-        tokens.push(Token::new(
-            Some(String::from("")),
-            &TokenType::Eof,
-            current_line,
-        ));
+        let mut eof = Token::new(Some(String::from("")), &TokenType::Eof, current_line);
+        self.locate(&mut eof, self.input.len()..self.input.len());
+        tokens.push(eof);
 
         debug!("This is the final list of Tokens {:?}", tokens);
         tokens
     }
 
+    /// Records where in the corpus a token was scanned from
+    ///
+    /// # Arguments
+    /// - `token` the token to locate
+    /// - `span` the bytes of the corpus that the token was scanned from
+    fn locate(&self, token: &mut Token, span: Range<usize>) {
+        let line_start = self.input[..span.start]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        token.column = self.input[line_start..span.start].chars().count() + 1;
+        token.span = span;
+    }
+
     /// Determine if the current character matches one that is expected
     ///
     /// # Arguments
@@ -422,12 +478,11 @@ impl Scanner {
     fn check_and_emit_concatenation(tokens: &mut Vec<Token>, current_line: i32) {
         if let Some(last_token_type) = tokens.last() {
             match &last_token_type.token_type {
-                TokenType::Number | TokenType::DoubleQuote | TokenType::Identifier => {
-                    tokens.push(Token {
-                        lexeme: None,
-                        token_type: &TokenType::StringConcat,
-                        line: current_line,
-                    })
+                TokenType::Number
+                | TokenType::DoubleQuote
+                | TokenType::Identifier
+                | TokenType::RightSquareBracket => {
+                    tokens.push(Token::new(None, &TokenType::StringConcat, current_line))
                 }
                 _ => (),
             }
@@ -475,6 +530,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::LeftCurly,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
         assert_eq!(
@@ -483,6 +540,8 @@ mod lexing {
                 lexeme: Some(String::from("print")),
                 token_type: &TokenType::Print,
                 line: 1,
+                column: 2,
+                span: 1..6,
             })
         );
         assert_eq!(
@@ -491,6 +550,24 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::RightCurly,
                 line: 1,
+                column: 7,
+                span: 6..7,
+            })
+        );
+    }
+
+    #[test]
+    fn it_counts_the_line_that_ends_a_comment() {
+        let tokens = Scanner::new(String::from("# a comment\n{print}")).scan();
+
+        assert_eq!(
+            tokens.first(),
+            Some(&Token {
+                lexeme: None,
+                token_type: &TokenType::LeftCurly,
+                line: 2,
+                column: 1,
+                span: 12..13,
             })
         );
     }
@@ -502,11 +579,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: None,
                 token_type: &TokenType::SingleQuote,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
     }
@@ -518,11 +597,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("Hello World!")),
                 token_type: &TokenType::DoubleQuote,
                 line: 1,
+                column: 1,
+                span: 0..14,
             })
         );
     }
@@ -535,34 +616,51 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("")),
                 token_type: &TokenType::DoubleQuote,
                 line: 1,
+                column: 1,
+                span: 0..2,
+            })
+        );
+    }
+
+    /// Asserts that scanning the provided text emits an error token for an unterminated string
+    fn assert_unterminated_string(text: &str) {
+        let tokens = Scanner::new(String::from(text)).scan();
+
+        // +1 for EOF token
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            tokens.first(),
+            Some(&Token {
+                lexeme: Some(String::from("Unterminated string.")),
+                token_type: &TokenType::Error,
+                line: 1,
+                column: 1,
+                span: 0..text.len(),
             })
         );
     }
 
     #[test]
-    #[should_panic(expected = "UnterminatedString")]
-    fn it_panics_on_an_unterminated_string() {
+    fn it_emits_an_error_token_for_an_unterminated_string() {
         // parse the text: "Hello
-        Scanner::new(String::from("\"Hello")).scan();
+        assert_unterminated_string("\"Hello");
     }
 
     #[test]
-    #[should_panic(expected = "UnterminatedString")]
-    fn it_panics_on_an_unterminated_empty_string() {
+    fn it_emits_an_error_token_for_an_unterminated_empty_string() {
         // parse the text: "
-        Scanner::new(String::from("\"")).scan();
+        assert_unterminated_string("\"");
     }
 
     #[test]
-    #[should_panic(expected = "UnterminatedString")]
-    fn it_panics_on_an_unterminated_escaped_string() {
+    fn it_emits_an_error_token_for_an_unterminated_escaped_string() {
         // parse the text: "Hello\"
-        Scanner::new(String::from("\"Hello\\\"")).scan();
+        assert_unterminated_string("\"Hello\\\"");
     }
 
     #[test]
@@ -573,11 +671,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("Hello\"")),
                 token_type: &TokenType::DoubleQuote,
                 line: 1,
+                column: 1,
+                span: 0..9,
             })
         );
     }
@@ -589,11 +689,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: None,
                 token_type: &TokenType::Slash,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
     }
@@ -628,11 +730,13 @@ mod lexing {
             // +1 for EOF token
             assert_eq!(tokens.len(), 2);
             assert_eq!(
-                tokens.iter().next(),
+                tokens.first(),
                 Some(&Token {
                     lexeme: None,
                     token_type,
                     line: 1,
+                    column: 1,
+                    span: 0..token.len(),
                 })
             );
         }
@@ -673,11 +777,13 @@ mod lexing {
             // +1 for EOF token
             assert_eq!(tokens.len(), 2);
             assert_eq!(
-                tokens.iter().next(),
+                tokens.first(),
                 Some(&Token {
                     lexeme: None,
                     token_type,
                     line: 1,
+                    column: 1,
+                    span: 0..token.len(),
                 })
             );
         }
@@ -690,11 +796,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("1")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
     }
@@ -706,11 +814,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("54")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..2,
             })
         );
     }
@@ -722,11 +832,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("01")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..2,
             })
         );
     }
@@ -738,11 +850,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("1.0")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..3,
             })
         );
     }
@@ -754,11 +868,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("987.2")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..5,
             })
         );
     }
@@ -770,11 +886,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("1.09876")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..7,
             })
         );
     }
@@ -786,11 +904,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("1.")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..2,
             })
         );
     }
@@ -808,6 +928,8 @@ mod lexing {
                 lexeme: Some(String::from("1")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
         assert_eq!(
@@ -816,6 +938,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::GreaterThan,
                 line: 1,
+                column: 3,
+                span: 2..3,
             })
         );
         assert_eq!(
@@ -824,6 +948,8 @@ mod lexing {
                 lexeme: Some(String::from("0")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 5,
+                span: 4..5,
             })
         );
     }
@@ -841,6 +967,8 @@ mod lexing {
                 lexeme: Some(String::from("1")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
         assert_eq!(
@@ -849,6 +977,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::Comma,
                 line: 1,
+                column: 2,
+                span: 1..2,
             })
         );
         assert_eq!(
@@ -857,6 +987,8 @@ mod lexing {
                 lexeme: Some(String::from("2")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 3,
+                span: 2..3,
             })
         );
     }
@@ -874,6 +1006,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::LeftParenthesis,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
         assert_eq!(
@@ -882,6 +1016,8 @@ mod lexing {
                 lexeme: Some(String::from("1")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 2,
+                span: 1..2,
             })
         );
         assert_eq!(
@@ -890,6 +1026,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::RightParenthesis,
                 line: 1,
+                column: 3,
+                span: 2..3,
             })
         );
         assert_eq!(
@@ -898,6 +1036,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::Comma,
                 line: 1,
+                column: 4,
+                span: 3..4,
             })
         );
         assert_eq!(
@@ -906,10 +1046,32 @@ mod lexing {
                 lexeme: Some(String::from("2")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 5,
+                span: 4..5,
             })
         );
     }
 
+    #[test]
+    fn it_emits_a_concatenation_following_an_array_element() {
+        let tokens = Scanner::new(String::from("a[1] \"b\"")).scan();
+        let token_types: Vec<&TokenType> = tokens.iter().map(|token| token.token_type).collect();
+
+        // +1 for EOF token.
+        assert_eq!(
+            token_types,
+            vec![
+                &TokenType::Identifier,
+                &TokenType::LeftSquareBracket,
+                &TokenType::Number,
+                &TokenType::RightSquareBracket,
+                &TokenType::StringConcat,
+                &TokenType::DoubleQuote,
+                &TokenType::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn it_parses_keywords() {
         let test_cases: [(&str, &TokenType); 18] = [
@@ -942,11 +1104,13 @@ mod lexing {
             // +1 for EOF token
             assert_eq!(tokens.len(), 2);
             assert_eq!(
-                tokens.iter().next(),
+                tokens.first(),
                 Some(&Token {
                     lexeme: Some(String::from(token)),
                     token_type,
                     line: 1,
+                    column: 1,
+                    span: 0..token.len(),
                 })
             );
         }
@@ -959,11 +1123,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("PRINT")),
                 token_type: &TokenType::Identifier,
                 line: 1,
+                column: 1,
+                span: 0..5,
             })
         );
     }
@@ -975,11 +1141,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("h3ll0")),
                 token_type: &TokenType::Identifier,
                 line: 1,
+                column: 1,
+                span: 0..5,
             })
         );
     }
@@ -991,11 +1159,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("hello_world")),
                 token_type: &TokenType::Identifier,
                 line: 1,
+                column: 1,
+                span: 0..11,
             })
         );
     }
@@ -1007,11 +1177,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("_hello")),
                 token_type: &TokenType::Identifier,
                 line: 1,
+                column: 1,
+                span: 0..6,
             })
         );
     }
@@ -1023,11 +1195,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("Hello")),
                 token_type: &TokenType::Identifier,
                 line: 1,
+                column: 1,
+                span: 0..5,
             })
         );
     }
@@ -1045,6 +1219,8 @@ mod lexing {
                 lexeme: Some(String::from("1")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
         assert_eq!(
@@ -1053,6 +1229,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::StringConcat,
                 line: 1,
+                column: 2,
+                span: 1..1,
             })
         );
         assert_eq!(
@@ -1061,6 +1239,8 @@ mod lexing {
                 lexeme: Some(String::from("Hello")),
                 token_type: &TokenType::Identifier,
                 line: 1,
+                column: 2,
+                span: 1..6,
             })
         );
     }
@@ -1078,6 +1258,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::SingleQuote,
                 line: 1,
+                column: 1,
+                span: 0..1,
             })
         );
         assert_eq!(
@@ -1086,6 +1268,8 @@ mod lexing {
                 lexeme: Some(String::from("1")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 2,
+                span: 1..2,
             })
         );
         assert_eq!(
@@ -1094,6 +1278,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::GreaterThan,
                 line: 1,
+                column: 4,
+                span: 3..4,
             })
         );
         assert_eq!(
@@ -1102,6 +1288,8 @@ mod lexing {
                 lexeme: Some(String::from("0")),
                 token_type: &TokenType::Number,
                 line: 1,
+                column: 6,
+                span: 5..6,
             })
         );
         assert_eq!(
@@ -1110,6 +1298,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::LeftCurly,
                 line: 1,
+                column: 8,
+                span: 7..8,
             })
         );
         assert_eq!(
@@ -1118,6 +1308,8 @@ mod lexing {
                 lexeme: Some(String::from("print")),
                 token_type: &TokenType::Print,
                 line: 1,
+                column: 10,
+                span: 9..14,
             })
         );
         assert_eq!(
@@ -1126,6 +1318,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::Semicolon,
                 line: 1,
+                column: 15,
+                span: 14..15,
             })
         );
         assert_eq!(
@@ -1134,6 +1328,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::RightCurly,
                 line: 1,
+                column: 17,
+                span: 16..17,
             })
         );
         assert_eq!(
@@ -1142,6 +1338,8 @@ mod lexing {
                 lexeme: None,
                 token_type: &TokenType::SingleQuote,
                 line: 1,
+                column: 18,
+                span: 17..18,
             })
         );
     }
@@ -1153,11 +1351,13 @@ mod lexing {
         // +1 for EOF token
         assert_eq!(tokens.len(), 2);
         assert_eq!(
-            tokens.iter().next(),
+            tokens.first(),
             Some(&Token {
                 lexeme: Some(String::from("Unexpected character.")),
                 token_type: &TokenType::Error,
                 line: 1,
+                column: 1,
+                span: 0..3,
             })
         );
     }
@@ -1,6 +1,7 @@
 pub mod token_type;
 
 use crate::token::token_type::TokenType;
+use std::ops::Range;
 
 /// A representation of a Token
 #[derive(Debug, PartialEq)]
@@ -8,6 +9,10 @@ pub struct Token {
     pub lexeme: Option<String>,
     pub token_type: &'static TokenType,
     pub line: i32,
+    /// the column at which the Token begins on its line, counted in characters starting from 1
+    pub column: usize,
+    /// the bytes of the corpus that the Token was scanned from
+    pub span: Range<usize>,
 }
 
 impl Token {
@@ -17,11 +22,15 @@ impl Token {
     /// - `lexeme` the lexeme to store in the Token
     /// - `token_type` the [TokenType] associated with the Token
     /// - `line` the line number at which the Token appears in the corpus
+    ///
+    /// The Token is located at the start of the corpus until the scanner has determined where it was scanned from
     pub fn new(lexeme: Option<String>, token_type: &'static TokenType, line: i32) -> Token {
         Token {
             lexeme,
             token_type,
             line,
+            column: 1,
+            span: 0..0,
         }
     }
     /// Constructs a new `Token` that represents a synthetic error
@@ -86,3 +86,58 @@ pub enum TokenType {
     // Synthetic Concatenation
     StringConcat,
 }
+
+impl TokenType {
+    /// Retrieves the text that a token of this type is always scanned from
+    ///
+    /// # Return value
+    /// - the text of an operator or punctuation token
+    /// - `None` for tokens whose text varies (e.g. numbers), or that are not scanned from any text
+    pub fn symbol(&self) -> Option<&'static str> {
+        let symbol = match self {
+            TokenType::AddAssign => "+=",
+            TokenType::SubAssign => "-=",
+            TokenType::MulAssign => "*=",
+            TokenType::DivAssign => "/=",
+            TokenType::ModAssign => "%=",
+            TokenType::PowAssign => "^=",
+            TokenType::Or => "||",
+            TokenType::And => "&&",
+            TokenType::NoMatch => "!~",
+            TokenType::DoubleEqual => "==",
+            TokenType::LessEqual => "<=",
+            TokenType::GreaterEqual => ">=",
+            TokenType::NotEqual => "!=",
+            TokenType::Incr => "++",
+            TokenType::Decr => "--",
+            TokenType::Append => ">>",
+            TokenType::LeftCurly => "{",
+            TokenType::RightCurly => "}",
+            TokenType::LeftParenthesis => "(",
+            TokenType::RightParenthesis => ")",
+            TokenType::LeftSquareBracket => "[",
+            TokenType::RightSquareBracket => "]",
+            TokenType::Comma => ",",
+            TokenType::Semicolon => ";",
+            TokenType::Plus => "+",
+            TokenType::Minus => "-",
+            TokenType::Star => "*",
+            TokenType::Modulus => "%",
+            TokenType::Caret => "^",
+            TokenType::Bang => "!",
+            TokenType::GreaterThan => ">",
+            TokenType::LessThan => "<",
+            TokenType::Pipe => "|",
+            TokenType::Question => "?",
+            TokenType::Colon => ":",
+            TokenType::Tilde => "~",
+            TokenType::Sigil => "$",
+            TokenType::Equals => "=",
+            TokenType::SingleQuote => "'",
+            TokenType::Slash => "/",
+            TokenType::Pound => "#",
+            _ => return None,
+        };
+        Some(symbol)
+    }
+}
@@ -1,16 +1,77 @@
 //! Module to describe Values under the hood in r-awk
 
 use std::fmt;
+use std::sync::Arc;
 
 /// Enum whose variants are underlying data types
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     Number(f32),
     String(String),
-    StrNum(String),
+    StrNum(StrNum),
+}
+
+/// A numeric string, data from outside of a program that looks like a number
+///
+/// The text is shared between copies of the value, and the number it converts to is determined once, so that a field
+/// referenced many times is neither copied nor converted again each time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrNum {
+    /// the text of the data, as it was received
+    text: Arc<str>,
+    /// the text converted to a number
+    number: f32,
+    /// whether the whole text, aside from surrounding blanks, is a number
+    is_number: bool,
+}
+
+impl StrNum {
+    /// Retrieves the text of the numeric string
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<&str> for StrNum {
+    fn from(text: &str) -> StrNum {
+        StrNum {
+            number: parse_number(text),
+            is_number: text.trim().parse::<f32>().is_ok(),
+            text: Arc::from(text),
+        }
+    }
+}
+
+impl From<String> for StrNum {
+    fn from(text: String) -> StrNum {
+        StrNum {
+            number: parse_number(&text),
+            is_number: text.trim().parse::<f32>().is_ok(),
+            text: Arc::from(text),
+        }
+    }
 }
 
 impl Value {
+    /// Create a Value from data that originates from outside of a program, such as field variables
+    /// and command line assignments
+    ///
+    /// Such data is a numeric string (`Value::StrNum`) if it looks like a number, and a
+    /// `Value::String` otherwise
+    ///
+    /// # Arguments
+    /// - `input` the data to convert
+    ///
+    /// # Return value
+    /// The data as a Value
+    pub fn from_user_input(input: String) -> Value {
+        if input.trim().parse::<f32>().is_ok() {
+            Value::StrNum(StrNum::from(input))
+        } else {
+            Value::String(input)
+        }
+    }
+
     /// Convert a Value to a number
     ///
     /// # Return value
@@ -18,34 +79,8 @@ impl Value {
     pub fn num_value(&self) -> f32 {
         match self {
             Value::Number(val) => *val,
-            Value::String(val) | Value::StrNum(val) => {
-                let mut end_num_index = 0;
-                let mut scientific_notation_flag = false;
-                let mut plus_minus_prefix_flag = false;
-                let mut decimal_flag = false;
-
-                for char in val.chars() {
-                    let is_scientific = char.eq_ignore_ascii_case(&'e');
-                    let is_one_time_prefix = char == '+' || char == '-';
-                    let is_decimal = char == '.';
-
-                    if char.is_numeric()
-                        || (!decimal_flag && is_decimal)
-                        || (!scientific_notation_flag && is_scientific)
-                        || (!plus_minus_prefix_flag && is_one_time_prefix)
-                    {
-                        decimal_flag |= is_decimal;
-                        scientific_notation_flag |= is_scientific;
-                        plus_minus_prefix_flag |= is_one_time_prefix;
-
-                        end_num_index += 1;
-                    } else {
-                        break;
-                    }
-                }
-
-                val[0..end_num_index].parse().unwrap_or(0.0)
-            }
+            Value::String(val) => parse_number(val),
+            Value::StrNum(val) => val.number,
         }
     }
 
@@ -57,7 +92,7 @@ impl Value {
         match self {
             Value::Number(val) => val.to_string(),
             Value::String(val) => val.clone(),
-            Value::StrNum(val) => val.clone(),
+            Value::StrNum(val) => String::from(val.as_str()),
         }
     }
 
@@ -73,17 +108,47 @@ impl Value {
         match self {
             Value::Number(val) => *val != 0.0,
             Value::String(val) => !val.is_empty(),
-            Value::StrNum(val) => {
-                match val.parse::<f32>() {
-                    Ok(num) => num != 0.0,
-                    Err(_) => {
-                        // we know it's a string, if parse() failed
-                        !val.is_empty()
-                    }
-                }
-            }
+            Value::StrNum(val) if val.is_number => val.number != 0.0,
+            Value::StrNum(val) => !val.as_str().is_empty(),
+        }
+    }
+}
+
+/// Converts text to a number, from the longest prefix of the text that looks like a number, after any leading blanks
+///
+/// # Arguments
+/// - `val` the text to convert
+///
+/// # Return value
+/// the number, or zero if no prefix of the text looks like a number
+fn parse_number(val: &str) -> f32 {
+    let val = val.trim_start();
+    let mut end_num_index = 0;
+    let mut scientific_notation_flag = false;
+    let mut plus_minus_prefix_flag = false;
+    let mut decimal_flag = false;
+
+    for char in val.chars() {
+        let is_scientific = char.eq_ignore_ascii_case(&'e');
+        let is_one_time_prefix = char == '+' || char == '-';
+        let is_decimal = char == '.';
+
+        if char.is_numeric()
+            || (!decimal_flag && is_decimal)
+            || (!scientific_notation_flag && is_scientific)
+            || (!plus_minus_prefix_flag && is_one_time_prefix)
+        {
+            decimal_flag |= is_decimal;
+            scientific_notation_flag |= is_scientific;
+            plus_minus_prefix_flag |= is_one_time_prefix;
+
+            end_num_index += 1;
+        } else {
+            break;
         }
     }
+
+    val[0..end_num_index].parse().unwrap_or(0.0)
 }
 
 /// Display trait implementation for Value
@@ -105,8 +170,8 @@ impl fmt::Display for Value {
 }
 
 #[cfg(test)]
-mod value {
-    use crate::value::Value;
+mod conversions {
+    use crate::value::{StrNum, Value};
 
     #[test]
     fn it_displays_a_number_with_no_decimal_points() {
@@ -127,7 +192,23 @@ mod value {
     #[test]
     fn it_displays_a_numeric_string() {
         let input = "   +3.14";
-        assert_eq!(Value::StrNum(String::from(input)).to_string(), input);
+        assert_eq!(Value::StrNum(StrNum::from(input)).to_string(), input);
+    }
+
+    #[test]
+    fn from_user_input_creates_a_strnum_for_a_number() {
+        assert_eq!(
+            Value::from_user_input(String::from(" 3.14 ")),
+            Value::StrNum(StrNum::from(" 3.14 "))
+        );
+    }
+
+    #[test]
+    fn from_user_input_creates_a_string_for_a_non_number() {
+        assert_eq!(
+            Value::from_user_input(String::from("3.14abc")),
+            Value::String(String::from("3.14abc"))
+        );
     }
 
     #[test]
@@ -137,6 +218,11 @@ mod value {
         assert_eq!(Value::Number(expected_number).num_value(), expected_number);
     }
 
+    #[test]
+    fn num_value_returns_the_number_of_a_strnum() {
+        assert_eq!(Value::StrNum(StrNum::from("2.5e1abc")).num_value(), 25.0);
+    }
+
     #[test]
     fn num_value_converts_empty_str_to_zero() {
         assert_eq!(Value::String(String::from("")).num_value(), 0.0);
@@ -223,54 +309,63 @@ mod value {
     #[test]
     fn str_value_parses_a_string_number_to_string() {
         assert_eq!(
-            Value::StrNum(String::from("3.21")).str_value(),
+            Value::StrNum(StrNum::from("3.21")).str_value(),
             String::from("3.21")
         );
     }
 
     #[test]
     fn truthy_value_returns_true_for_nonzero_number() {
-        assert_eq!(Value::Number(1.0).truthy_value(), true);
+        assert!(Value::Number(1.0).truthy_value());
     }
 
     #[test]
     fn truthy_value_returns_false_for_zero() {
-        assert_eq!(Value::Number(0.0).truthy_value(), false);
+        assert!(!Value::Number(0.0).truthy_value());
     }
 
     #[test]
     fn truthy_value_returns_true_for_non_empty_string() {
-        assert_eq!(
-            Value::String(String::from("hello world")).truthy_value(),
-            true
-        );
+        assert!(Value::String(String::from("hello world")).truthy_value());
     }
 
     #[test]
     fn truthy_value_returns_true_for_string_containing_zero() {
-        assert_eq!(Value::String(String::from("0")).truthy_value(), true);
+        assert!(Value::String(String::from("0")).truthy_value());
     }
 
     #[test]
     fn truthy_value_returns_false_for_empty_string() {
-        assert_eq!(Value::String(String::from("")).truthy_value(), false);
+        assert!(!Value::String(String::from("")).truthy_value());
     }
 
     #[test]
     fn truthy_value_returns_true_for_non_empty_strnum() {
-        assert_eq!(
-            Value::StrNum(String::from("hello world")).truthy_value(),
-            true
-        );
+        assert!(Value::StrNum(StrNum::from("hello world")).truthy_value());
     }
 
     #[test]
     fn truthy_value_returns_true_for_strnum_containing_zero() {
-        assert_eq!(Value::StrNum(String::from("0")).truthy_value(), false);
+        assert!(!Value::StrNum(StrNum::from("0")).truthy_value());
+    }
+
+    #[test]
+    fn truthy_value_returns_false_for_strnum_of_zero_with_blanks() {
+        assert!(!Value::StrNum(StrNum::from(" 0 ")).truthy_value());
+    }
+
+    #[test]
+    fn truthy_value_returns_true_for_strnum_of_one_with_blanks() {
+        assert!(Value::StrNum(StrNum::from(" 1 ")).truthy_value());
+    }
+
+    #[test]
+    fn num_value_skips_leading_blanks() {
+        assert_eq!(Value::String(String::from("  42abc")).num_value(), 42.0);
     }
 
     #[test]
     fn truthy_value_returns_false_for_empty_strnum() {
-        assert_eq!(Value::StrNum(String::from("")).truthy_value(), false);
+        assert!(!Value::StrNum(StrNum::from("")).truthy_value());
     }
 }
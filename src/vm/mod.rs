@@ -1,290 +1,524 @@
+pub mod operations;
+
+use crate::assignment::Assignment;
+use crate::binary;
+use crate::builtin::Builtin;
+use crate::characters::Characters;
 use crate::chunk::{Chunk, OpCode};
-use crate::parser::Parser;
-use crate::token::Token;
+use crate::error::{Error, ErrorKind};
+use crate::output::OutputFormat;
+use crate::program::{Program, Section, SymbolTable};
+use crate::record::Record;
 use crate::value::Value;
-use crate::ParsedDataInput;
 
-use log::{debug, error};
+use log::debug;
 use std::collections::HashMap;
-
-#[derive(Debug, PartialEq)]
-pub enum InterpretError {
-    CompileError,
-    RuntimeError,
-}
+use std::io;
+use std::io::Write;
 
 pub struct VM {
-    chunk: Chunk,
+    program: Program,
+    section: Section,
     ip: usize,
     stack: Vec<Value>,
-    globals: HashMap<String, Value>,
+    // the values of global variables, indexed by slot
+    globals: Vec<Value>,
+    // the number of the most recent assignment to each global variable, indexed by slot, where 0 is never assigned
+    assigned_at: Vec<u64>,
+    // the number of assignments made to global variables so far
+    assignments: u64,
+    // the slots of global variables, starting with the ones resolved when the program was compiled
+    global_slots: SymbolTable,
+    // the elements of arrays, indexed by slot
+    arrays: Vec<HashMap<String, Value>>,
+    // the slots of arrays, starting with the ones resolved when the program was compiled
+    array_slots: SymbolTable,
+    // how the values given to `print` statements are written
+    output_format: OutputFormat,
+    // what a single character of a string is, to built in functions
+    characters: Characters,
 }
 
 impl VM {
-    pub fn new() -> VM {
+    /// Instantiates a new VM to run a compiled program
+    ///
+    /// # Arguments
+    /// - `program` the compiled program to run. The program is compiled once, and may be run against any number of
+    ///   records
+    pub fn new(program: Program) -> VM {
+        let global_slots = program.globals().clone();
+        let array_slots = program.arrays().clone();
         VM {
-            chunk: Chunk::new(),
+            program,
+            section: Section::Main,
             ip: 0,
             stack: vec![],
-            globals: HashMap::new(),
+            globals: vec![Value::String("".into()); global_slots.len()],
+            assigned_at: vec![0; global_slots.len()],
+            assignments: 0,
+            global_slots,
+            arrays: vec![HashMap::new(); array_slots.len()],
+            array_slots,
+            output_format: OutputFormat::default(),
+            characters: Characters::default(),
         }
     }
 
+    /// Sets what a single character of a string is, to built in functions
+    ///
+    /// # Arguments
+    /// - `characters` what a single character is
+    pub(crate) fn set_characters(&mut self, characters: Characters) {
+        self.characters = characters;
+    }
+
+    /// Sets how the values given to `print` statements are written
+    ///
+    /// # Arguments
+    /// - `output_format` the format to write values in
+    pub(crate) fn set_output_format(&mut self, output_format: OutputFormat) {
+        self.output_format = output_format;
+    }
+
     /// Runs code that has been compiled
     ///
     /// # Arguments
-    /// - `data` user provided data to use when running the compiled code
+    /// - `record` the record of user provided data to use when running the compiled code
+    /// - `output` where the output of `print` statements is written to
     ///
     /// # Return value
-    /// the result of running the provided source, expressed as an `InterpretError` if the code is
-    /// unable to run to completion
-    pub fn run(&mut self, all_data: &[ParsedDataInput]) -> Result<(), InterpretError> {
-        // create an iterator that can be manually advanced, as some functionality (`getline`)
-        // requires more than one advancement per instruction
-        let mut data_iter = all_data.iter();
-        // prime the pump with the first set of data
-        let mut data = data_iter.next();
+    /// the result of running the provided source, expressed as an [Error] if the code is unable to run to completion
+    fn run(&mut self, record: &Record, output: &mut dyn Write) -> Result<(), Error> {
         loop {
-            let instruction: OpCode = self.chunk.code[self.ip].code.clone();
-            self.ip += 1;
+            let instruction = OpCode::from_byte(self.read_byte());
 
             debug!("VM switching on instruction '{:#?}'", &instruction);
             match instruction {
-                OpCode::OpPrint => match self.stack.pop() {
-                    Some(val) => {
-                        println!("{}", val);
-                    }
-                    None => {
-                        error!("Error: The stack was empty when trying to print");
-                        break Err(InterpretError::RuntimeError);
-                    }
-                },
+                OpCode::OpPrint => {
+                    let count = self.read_short();
+                    let line = if count == 0 {
+                        self.output_format.format_record(record)
+                    } else {
+                        let values = self.stack.split_off(self.stack.len() - count);
+                        self.output_format.format_values(&values)
+                    };
+                    // any bytes of input that are not valid UTF-8 are written back out as they were read
+                    output
+                        .write_all(&binary::encode(&line))
+                        .and_then(|_| output.write_all(b"\n"))
+                        .map_err(|err| {
+                            let kind = match err.kind() {
+                                io::ErrorKind::BrokenPipe => ErrorKind::BrokenPipe,
+                                _ => ErrorKind::Io,
+                            };
+                            Error::new(kind, format!("unable to write output: {}", err))
+                        })?;
+                }
                 OpCode::OpReturn => {
                     if !self.stack.is_empty() {
-                        error!("The stack is not empty! {:?}", self.stack);
-                        break Err(InterpretError::RuntimeError);
-                    }
-
-                    data = data_iter.next();
-                    if data.is_none() {
-                        // we are out of records and are done running
-                        break Ok(());
-                    } else {
-                        // we have another record to process, reset the vm and start again
-                        self.reset_vm();
+                        break Err(Error::new(
+                            ErrorKind::Runtime,
+                            format!("the stack is not empty! {:?}", self.stack),
+                        ));
                     }
+                    break Ok(());
+                }
+                OpCode::GreaterEqual => self.comparison_op(&instruction)?,
+                OpCode::Greater => self.comparison_op(&instruction)?,
+                OpCode::LessEqual => self.comparison_op(&instruction)?,
+                OpCode::Less => self.comparison_op(&instruction)?,
+                OpCode::DoubleEqual => self.comparison_op(&instruction)?,
+                OpCode::NotEqual => self.comparison_op(&instruction)?,
+                OpCode::Add => self.arithmetic_op(&instruction)?,
+                OpCode::Subtract => self.arithmetic_op(&instruction)?,
+                OpCode::Multiply => self.arithmetic_op(&instruction)?,
+                OpCode::Divide => self.arithmetic_op(&instruction)?,
+                OpCode::Modulus => self.arithmetic_op(&instruction)?,
+                OpCode::Exponentiation => self.arithmetic_op(&instruction)?,
+                OpCode::Concatenate => self.concatenation_op()?,
+                OpCode::UnaryPlus => self.unary_op(&instruction)?,
+                OpCode::UnaryMinus => self.unary_op(&instruction)?,
+                OpCode::LogicalNot => self.unary_op(&instruction)?,
+                OpCode::LogicalAnd => self.logical_op(&instruction)?,
+                OpCode::LogicalOr => self.logical_op(&instruction)?,
+                OpCode::NumberConstant => {
+                    let index = self.read_short();
+                    self.stack.push(Value::Number(self.chunk().numbers[index]));
+                }
+                OpCode::StringConstant => {
+                    let index = self.read_short();
+                    self.stack
+                        .push(Value::String(self.chunk().strings[index].clone()));
                 }
-                OpCode::GreaterEqual => self.comparison_op(&instruction),
-                OpCode::Greater => self.comparison_op(&instruction),
-                OpCode::LessEqual => self.comparison_op(&instruction),
-                OpCode::Less => self.comparison_op(&instruction),
-                OpCode::DoubleEqual => self.comparison_op(&instruction),
-                OpCode::NotEqual => self.comparison_op(&instruction),
-                OpCode::Add => self.arithmetic_op(&instruction),
-                OpCode::Subtract => self.arithmetic_op(&instruction),
-                OpCode::Multiply => self.arithmetic_op(&instruction),
-                OpCode::Divide => self.arithmetic_op(&instruction),
-                OpCode::Modulus => self.arithmetic_op(&instruction),
-                OpCode::Exponentiation => self.arithmetic_op(&instruction),
-                OpCode::Concatenate => self.concatenation_op(&instruction),
-                OpCode::UnaryPlus => self.unary_op(&instruction),
-                OpCode::UnaryMinus => self.unary_op(&instruction),
-                OpCode::LogicalNot => self.unary_op(&instruction),
-                OpCode::LogicalAnd => self.logical_op(&instruction),
-                OpCode::LogicalOr => self.logical_op(&instruction),
-                OpCode::OpConstant(val) => self.stack.push(val),
                 OpCode::Pop => {
                     // TODO: Consider if we should return an error should we pop off the stack when it is empty
                     self.stack.pop();
                 }
-                OpCode::GetGlobal(chunk_index) => {
-                    let variable_name = self.read_variable_name(chunk_index);
-                    let default_val = &Value::String("".into());
-                    let val = self.globals.get(&*variable_name).unwrap_or(default_val);
-                    self.stack.push(val.clone());
+                OpCode::GetGlobal => {
+                    let slot = self.read_short();
+                    self.stack.push(self.globals[slot].clone());
                 }
-                OpCode::SetGlobal(chunk_index) => {
-                    let variable_name = self.read_variable_name(chunk_index);
-                    let val = self.peek(0).clone();
-                    self.globals.insert(variable_name, val);
+                OpCode::SetGlobal => {
+                    let slot = self.read_short();
+                    self.globals[slot] = self.peek(0).clone();
+                    self.record_assignment(slot);
                 }
-                OpCode::DefineGlobal(chunk_index) => {
-                    let variable_name = self.read_variable_name(chunk_index);
-                    let val = self.peek(0).clone();
-                    self.globals.insert(variable_name, val);
-                    self.stack.pop();
+                OpCode::DefineGlobal => {
+                    let slot = self.read_short();
+                    self.globals[slot] = self.pop()?;
+                    self.record_assignment(slot);
                 }
-                OpCode::GetFieldVariable() => {
+                OpCode::CallNative => {
+                    let function = self.read_short();
+                    let natives = self.program.natives();
+                    // the arity of every call was checked when the program was compiled
+                    let arguments = self
+                        .stack
+                        .split_off(self.stack.len() - natives.arity(function));
+                    let result = natives.call(function, &arguments);
+                    self.stack.push(result);
+                }
+                OpCode::CallBuiltin => {
+                    let function = Builtin::from_byte(self.read_byte());
+                    let count = self.read_byte() as usize;
+                    let arguments = self.stack.split_off(self.stack.len() - count);
+                    self.stack.push(function.call(&arguments, self.characters));
+                }
+                OpCode::GetArrayElement => {
+                    let slot = self.read_short();
+                    let subscript = self.pop()?.str_value();
+                    // referencing an element that does not exist creates it
+                    let val = self.arrays[slot]
+                        .entry(subscript)
+                        .or_insert_with(|| Value::String("".into()))
+                        .clone();
+                    self.stack.push(val);
+                }
+                OpCode::SetArrayElement => {
+                    let slot = self.read_short();
+                    let val = self.pop()?;
+                    let subscript = self.pop()?.str_value();
+                    self.arrays[slot].insert(subscript, val.clone());
+                    // like any other assignment, the assigned value is the result of the expression
+                    self.stack.push(val);
+                }
+                OpCode::DeleteArrayElement => {
+                    let slot = self.read_short();
+                    let subscript = self.pop()?.str_value();
+                    self.arrays[slot].remove(&subscript);
+                }
+                OpCode::DeleteArray => {
+                    let slot = self.read_short();
+                    self.arrays[slot].clear();
+                }
+                OpCode::GetFieldVariable => {
                     // the index may be the result of an expression - e.g. $(1+2), where the result
                     // (3) would be on the top of the stack. pop it off. if there is no value,
                     // that's illegal.
-                    let index = match self.stack.pop() {
-                        // https://www.gnu.org/software/gawk/manual/gawk.html#Nonconstant-Fields:
-                        // > Negative field numbers are not allowed; trying to reference one
-                        // > usually terminates the program. (The POSIX standard does not define
-                        // > what happens when you reference a negative field number. gawk notices
-                        // > this and terminates your program. Other awk implementations may behave
-                        // > differently.)
-                        // This awk will allow decimal accesses (for now) by loss of precision:
-                        // $2.3 -> $2
-                        Some(awk_value) => awk_value.num_value(),
-                        None => {
-                            error!("Error: The stack was empty when trying to determine a field reference lookup");
-                            break Err(InterpretError::RuntimeError);
+                    // https://www.gnu.org/software/gawk/manual/gawk.html#Nonconstant-Fields:
+                    // > Negative field numbers are not allowed; trying to reference one
+                    // > usually terminates the program. (The POSIX standard does not define
+                    // > what happens when you reference a negative field number. gawk notices
+                    // > this and terminates your program. Other awk implementations may behave
+                    // > differently.)
+                    let index = match self.pop()? {
+                        // in an input with a header, a field may be referenced by its name - e.g. $"status"
+                        Value::String(name) if record.header().is_some() && !is_numeric(&name) => {
+                            match record.header().and_then(|header| header.get(&name)) {
+                                Some(index) => *index as f32,
+                                None => {
+                                    break Err(Error::new(
+                                        ErrorKind::Runtime,
+                                        format!("trying to access unknown field \"{}\"", name),
+                                    ))
+                                }
+                            }
                         }
+                        index => index.num_value(),
                     };
                     if index < 0.0 {
-                        error!("r-awk: trying to access out of range field {}", index);
-                        break Err(InterpretError::RuntimeError);
+                        break Err(Error::new(
+                            ErrorKind::Runtime,
+                            format!("trying to access out of range field {}", index),
+                        ));
                     } else if (index - index.trunc()).abs() > 0.0 {
-                        error!("r-awk: trying to access non integer index {}", index);
-                        break Err(InterpretError::RuntimeError);
+                        break Err(Error::new(
+                            ErrorKind::Runtime,
+                            format!("trying to access non integer index {}", index),
+                        ));
                     }
 
-                    let safer_index = index as usize;
-                    let data = data.unwrap();
-                    if safer_index <= data.parsed.len() {
-                        let value = if safer_index == 0 {
-                            data.original.clone()
-                        } else {
-                            data.parsed[safer_index - 1].clone()
-                        };
-                        if value.trim().parse::<f32>().is_ok() {
-                            self.stack.push(Value::StrNum(value));
-                        } else {
-                            self.stack.push(Value::String(value));
-                        }
-                    } else {
-                        // if the index that user specified does not exist, push something on the
-                        // stack in case we're doing something like `print $9999;'
-                        self.stack.push(Value::String(String::from("")));
-                    }
+                    // if the index that user specified does not exist, push something on the stack in case we're doing
+                    // something like `print $9999;'
+                    let value = record
+                        .field(index as usize)
+                        .unwrap_or_else(|| Value::String(String::from("")));
+                    self.stack.push(value);
                 }
-                OpCode::JumpIfFalse(offset1, offset2) => {
+                OpCode::JumpIfFalse => {
+                    let offset = self.read_short();
                     let condition_result = self.peek(0).truthy_value();
                     if !condition_result {
-                        let offset = (offset1 >> 8) | offset2;
                         self.ip += offset;
                     }
                 }
-                OpCode::JumpIfTrue(offset1, offset2) => {
+                OpCode::JumpIfTrue => {
+                    let offset = self.read_short();
                     let condition_result = self.peek(0).num_value() != 0.0;
                     if condition_result {
                         // the top of the stack may not yield a one, force it
                         self.stack.pop();
                         self.stack.push(Value::Number(1.0));
 
-                        let offset = (offset1 >> 8) | offset2;
                         self.ip += offset;
                     }
                 }
-                OpCode::Jump(offset1, offset2) => {
-                    let offset = (offset1 >> 8) | offset2;
+                OpCode::Jump => {
+                    let offset = self.read_short();
+                    self.ip += offset;
+                }
+                OpCode::Loop => {
+                    let offset = self.read_short();
+                    self.ip -= offset;
+                }
+                OpCode::JumpIfFalseWide => {
+                    let offset = self.read_long();
+                    if !self.peek(0).truthy_value() {
+                        self.ip += offset;
+                    }
+                }
+                OpCode::JumpIfTrueWide => {
+                    let offset = self.read_long();
+                    if self.peek(0).num_value() != 0.0 {
+                        // the top of the stack may not yield a one, force it
+                        self.stack.pop();
+                        self.stack.push(Value::Number(1.0));
+
+                        self.ip += offset;
+                    }
+                }
+                OpCode::JumpWide => {
+                    let offset = self.read_long();
                     self.ip += offset;
                 }
-                OpCode::Loop(offset1, offset2) => {
-                    let offset = (offset1 >> 8) | offset2;
+                OpCode::LoopWide => {
+                    let offset = self.read_long();
                     self.ip -= offset;
                 }
+                OpCode::CompareJumpIfFalse => {
+                    let comparison = OpCode::from_byte(self.read_byte());
+                    let offset = self.read_short();
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    if !operations::comparison(&comparison, &a, &b).truthy_value() {
+                        self.ip += offset;
+                    }
+                }
             }
         }
     }
 
+    /// Assigns a value to a global variable from outside of a program, such as from the command line
+    ///
+    /// # Arguments
+    /// - `assignment` the assignment to perform
+    pub fn assign_variable(&mut self, assignment: &Assignment) {
+        self.set_global(
+            &assignment.name,
+            Value::from_user_input(assignment.value.clone()),
+        );
+    }
+
+    /// Retrieves the value of a global variable
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    ///
+    /// # Return value
+    /// the value of the variable, or an empty string if it has never been assigned
+    pub fn get_global(&self, name: &str) -> Value {
+        self.global_slots
+            .get(name)
+            .map(|slot| self.globals[slot].clone())
+            .unwrap_or_else(|| Value::String("".into()))
+    }
+
+    /// Assigns a value to a global variable
+    ///
+    /// A variable that the program never references is given a slot of its own, so that it may be read back later
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    /// - `value` the value to assign
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        let slot = self.global_slots.resolve(name);
+        if slot == self.globals.len() {
+            self.globals.push(value);
+            self.assigned_at.push(0);
+        } else {
+            self.globals[slot] = value;
+        }
+        self.record_assignment(slot);
+    }
+
+    /// Retrieves when a global variable was last assigned, so that the order of assignments to different variables may
+    /// be compared. Assigning a variable the value it already holds counts as an assignment
+    ///
+    /// # Arguments
+    /// - `name` the name of the variable
+    ///
+    /// # Return value
+    /// the number of the most recent assignment to the variable, which is greater for later assignments, or 0 if the
+    /// variable has never been assigned
+    pub(crate) fn assignment_of(&self, name: &str) -> u64 {
+        self.global_slots
+            .get(name)
+            .map_or(0, |slot| self.assigned_at[slot])
+    }
+
+    /// Records that a global variable has just been assigned
+    ///
+    /// # Arguments
+    /// - `slot` the slot of the variable
+    fn record_assignment(&mut self, slot: usize) {
+        self.assignments += 1;
+        self.assigned_at[slot] = self.assignments;
+    }
+
+    /// Retrieves an element of an array, without creating it if it does not exist
+    ///
+    /// # Arguments
+    /// - `name` the name of the array
+    /// - `subscript` the subscript of the element
+    ///
+    /// # Return value
+    /// the value of the element if it exists, `None` otherwise
+    pub fn get_array_element(&self, name: &str, subscript: &str) -> Option<&Value> {
+        let slot = self.array_slots.get(name)?;
+        self.arrays[slot].get(subscript)
+    }
+
+    /// Assigns a value to an element of an array, creating the array if it does not exist
+    ///
+    /// An array that the program never references is given a slot of its own, so that it may be read back later
+    ///
+    /// # Arguments
+    /// - `name` the name of the array
+    /// - `subscript` the subscript of the element
+    /// - `value` the value to assign
+    pub fn set_array_element(&mut self, name: &str, subscript: String, value: Value) {
+        let slot = self.array_slots.resolve(name);
+        if slot == self.arrays.len() {
+            self.arrays.push(HashMap::new());
+        }
+        self.arrays[slot].insert(subscript, value);
+    }
+
+    /// Deletes every element of an array
+    ///
+    /// # Arguments
+    /// - `name` the name of the array
+    pub fn delete_array(&mut self, name: &str) {
+        if let Some(slot) = self.array_slots.get(name) {
+            self.arrays[slot].clear();
+        }
+    }
+
+    /// Retrieves the chunk of the section that is currently being run
+    fn chunk(&self) -> &Chunk {
+        self.program.chunk(self.section)
+    }
+
+    /// Reads the byte at the instruction pointer, advancing the instruction pointer past it
+    fn read_byte(&mut self) -> u8 {
+        let byte = self.chunk().code[self.ip];
+        self.ip += 1;
+        byte
+    }
+
+    /// Reads the two byte wide operand at the instruction pointer, advancing the instruction pointer past it
+    fn read_short(&mut self) -> usize {
+        let operand = self.chunk().read_short(self.ip);
+        self.ip += 2;
+        operand as usize
+    }
+
+    /// Reads the four byte wide operand at the instruction pointer, advancing the instruction pointer past it
+    fn read_long(&mut self) -> usize {
+        let operand = self.chunk().read_long(self.ip);
+        self.ip += 4;
+        operand as usize
+    }
+
     fn reset_vm(&mut self) {
         self.ip = 0;
         self.stack = vec![];
     }
 
-    fn read_variable_name(&mut self, chunk_index: usize) -> String {
-        self.chunk
-            .constants
-            .get(chunk_index)
-            .expect("No variable found")
-            .clone()
-    }
-
     /// Entrypoint for the VM
     ///
     /// # Arguments
-    /// - `tokens` the tokens generated by parsing the user's program
-    /// - `data` any user provided data to run the user's program against
+    /// - `section` the section of the user's program to run
+    /// - `record` the record of user provided data to run the user's program against
+    /// - `output` where the output of `print` statements is written to
     ///
     /// # Return value
-    /// the result of running the provided source, expressed as an `InterpretError` if the code is unable to run to
-    /// completion
+    /// the result of running the program, expressed as an [Error] if the code is unable to run to completion. The error
+    /// carries the line of the program that was running when it was found
     pub fn interpret(
         &mut self,
-        tokens: &[Token],
-        data: &[ParsedDataInput],
-    ) -> Result<(), InterpretError> {
-        self.chunk = Chunk::new();
-        self.ip = 0;
-        let mut parser = Parser::new(tokens.iter(), &mut self.chunk);
+        section: Section,
+        record: &Record,
+        output: &mut dyn Write,
+    ) -> Result<(), Error> {
+        self.section = section;
+        self.reset_vm();
 
-        if !parser.parse() {
-            return Err(InterpretError::CompileError);
-        }
+        // TODO(FUTURE): include a stack trace of the function frames being run once functions are supported
+        self.run(record, output).map_err(|error| {
+            // the instruction pointer has moved past the instruction that failed, which is never the first of a chunk
+            let line = self.chunk().line(self.ip - 1);
+            error.with_line(line as usize)
+        })
+    }
 
-        self.run(data)
+    /// Determines whether or not a section of the program has any rules
+    ///
+    /// # Arguments
+    /// - `section` the section of the user's program to inspect
+    ///
+    /// # Return value
+    /// `true` if the section has at least one rule, `false` otherwise
+    pub fn has_rules(&self, section: Section) -> bool {
+        self.program.has_rules(section)
     }
 
     /// Perform an arithmetic operation on two values on the stack, placing the result on the stack
     ///
-    /// The values on the stack shall be implicitly converted into their string representations for
-    /// the operations supported by this method immediately following popping them from the stack
-    ///
     /// # Arguments
     /// - `op_code` the operation to perform
-    fn arithmetic_op(&mut self, op_code: &OpCode) {
-        let b = self.stack.pop().unwrap().num_value();
-        let a = self.stack.pop().unwrap().num_value();
-
-        let val = match *op_code {
-            OpCode::Add => Value::Number(a + b),
-            OpCode::Subtract => Value::Number(a - b),
-            OpCode::Multiply => Value::Number(a * b),
-            OpCode::Divide => Value::Number(a / b),
-            OpCode::Modulus => Value::Number(a % b),
-            OpCode::Exponentiation => Value::Number(a.powf(b)),
-            _ => panic!(
-                "Unknown op code given for arithmetic operation '{:?}'",
-                op_code
-            ),
-        };
-        self.stack.push(val)
+    fn arithmetic_op(&mut self, op_code: &OpCode) -> Result<(), Error> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.stack.push(operations::arithmetic(op_code, &a, &b));
+        Ok(())
     }
 
     /// Perform an a concatenation operation on two values on the stack, placing the result on the stack
-    ///
-    /// The values on the stack shall be implicitly converted into their string representations for
-    /// the operations supported by this method immediately following popping them from the stack
-    ///
-    /// # Arguments
-    /// - `op_code` the operation to perform
-    fn concatenation_op(&mut self, op_code: &OpCode) {
-        let b = self.stack.pop().unwrap().str_value();
-        let a = self.stack.pop().unwrap().str_value();
-
-        match *op_code {
-            OpCode::Concatenate => {
-                let mut concat_str = a;
-                concat_str.push_str(&b);
-                self.stack.push(Value::String(concat_str));
-            }
-            _ => panic!(
-                "Unknown op code given for concatenation operation '{:?}'",
-                op_code
-            ),
-        }
+    fn concatenation_op(&mut self) -> Result<(), Error> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.stack.push(operations::concatenation(&a, &b));
+        Ok(())
     }
 
     /// Perform logical comparison between two values on the stack
     ///
     /// # Arguments
     /// - `op_code` the operation to perform
-    fn logical_op(&mut self, op_code: &OpCode) {
-        let b = self.stack.pop().unwrap();
-        let a = self.stack.pop().unwrap();
+    fn logical_op(&mut self, op_code: &OpCode) -> Result<(), Error> {
+        let b = self.pop()?;
+        let a = self.pop()?;
 
         let val = match *op_code {
             OpCode::LogicalAnd => {
@@ -304,214 +538,40 @@ impl VM {
                 op_code
             ),
         };
-        self.stack.push(val)
+        self.stack.push(val);
+        Ok(())
     }
 
-    /// Perform a relational comparison between two values on the stack
-    ///
-    /// When two operands are compared, either string comparison or numeric comparison may be
-    /// used. This depends upon the attributes of the operands, according to the following
-    /// symmetric matrix:
-    ///         +----------------------------------------------
-    ///         |       STRING          NUMERIC         STRNUM
-    /// --------+----------------------------------------------
-    ///         |
-    /// STRING  |       string          string          string
-    ///         |
-    /// NUMERIC |       string          numeric         numeric
-    ///         |
-    /// STRNUM  |       string          numeric         numeric
-    /// --------+----------------------------------------------
-    /// [Source - GNU Awk Manual](https://www.gnu.org/software/gawk/manual/html_node/Variable-Typing.html)
+    /// Perform a relational comparison between two values on the stack, placing the result on the stack
     ///
     /// # Arguments
     /// - `op_code` the operation to perform
-    fn comparison_op(&mut self, op_code: &OpCode) {
-        let is_string_comparison =
-            matches!(self.peek(0), Value::String(_)) || matches!(self.peek(1), Value::String(_));
-
-        let b = self.stack.pop().unwrap();
-        let a = self.stack.pop().unwrap();
-
-        if is_string_comparison {
-            self.string_comparison(op_code, &a.str_value(), &b.str_value());
-        } else {
-            // implicitly convert `Value::StrNum` to numbers
-            self.numeric_comparison(op_code, a.num_value(), b.num_value());
-        }
+    fn comparison_op(&mut self, op_code: &OpCode) -> Result<(), Error> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.stack.push(operations::comparison(op_code, &a, &b));
+        Ok(())
     }
 
-    /// Perform a relational comparison between two strings, and push the result onto the stack
+    /// Perform an operation on the value on the top of the stack, placing the result on the stack
     ///
     /// # Arguments
     /// - `op_code` the operation to perform
-    /// - `a` the first argument of the comparison. Is placed on the left hand side of the expression
-    /// - `b` the second argument of the comparison. Is placed on the right hand side of the expression
-    fn string_comparison(&mut self, op_code: &OpCode, a: &str, b: &str) {
-        let val = match *op_code {
-            OpCode::GreaterEqual => {
-                let mut result: f32 = 0.0;
-                if a >= b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::Greater => {
-                let mut result: f32 = 0.0;
-                if a > b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::LessEqual => {
-                let mut result: f32 = 0.0;
-                if a <= b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::Less => {
-                let mut result: f32 = 0.0;
-                if a < b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::DoubleEqual => {
-                let mut result: f32 = 0.0;
-                if a.eq(b) {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::NotEqual => {
-                let mut result: f32 = 0.0;
-                if a.ne(b) {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            _ => panic!(
-                "Unknown op code given for string comparison '{:?}'",
-                op_code
-            ),
-        };
-        self.stack.push(val)
+    fn unary_op(&mut self, op_code: &OpCode) -> Result<(), Error> {
+        let a = self.pop()?;
+        self.stack.push(operations::unary(op_code, &a));
+        Ok(())
     }
 
-    /// Perform a relational comparison between two numbers, and push the result onto the stack
+    /// Pops the value on the top of the stack
     ///
-    /// # Arguments
-    /// - `op_code` the operation to perform
-    /// - `a` the first argument of the comparison. Is placed on the left hand side of the expression
-    /// - `b` the second argument of the comparison. Is placed on the right hand side of the expression
-    fn numeric_comparison(&mut self, op_code: &OpCode, a: f32, b: f32) {
-        let val = match *op_code {
-            OpCode::GreaterEqual => {
-                let mut result: f32 = 0.0;
-                if a >= b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::Greater => {
-                let mut result: f32 = 0.0;
-                if a > b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::LessEqual => {
-                let mut result: f32 = 0.0;
-                if a <= b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::Less => {
-                let mut result: f32 = 0.0;
-                if a < b {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::DoubleEqual => {
-                let mut result: f32 = 0.0;
-                if (a - b).abs() == 0.0 {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            OpCode::NotEqual => {
-                let mut result: f32 = 0.0;
-                if (a - b).abs() != 0.0 {
-                    result = 1.0;
-                }
-                Value::Number(result)
-            }
-            _ => panic!("Unknown op code given for comparison '{:?}'", op_code),
-        };
-        self.stack.push(val)
-    }
-
-    fn unary_op(&mut self, op_code: &OpCode) {
-        if matches!(self.peek(0), Value::Number(_)) || matches!(self.peek(0), Value::StrNum(_)) {
-            let num_like = self.stack.pop().unwrap().num_value();
-            let val = match *op_code {
-                // Unary plus will be more useful for converting a string to a number
-                OpCode::UnaryPlus => {
-                    if num_like == 0.0 {
-                        Value::Number(0.0)
-                    } else {
-                        Value::Number(num_like)
-                    }
-                }
-                OpCode::UnaryMinus => {
-                    if num_like == 0.0 {
-                        Value::Number(0.0)
-                    } else {
-                        Value::Number(-num_like)
-                    }
-                }
-                OpCode::LogicalNot => {
-                    let mut result: f32 = 1.0;
-                    if num_like > 0.0 {
-                        result = 0.0;
-                    }
-                    Value::Number(result)
-                }
-                _ => panic!(
-                    "Unknown op code given for unary on num/strnum: '{:?}'",
-                    op_code
-                ),
-            };
-            self.stack.push(val)
-        } else if matches!(self.peek(0), Value::String(_)) {
-            if let Value::String(a) = self.stack.pop().unwrap() {
-                let val = match *op_code {
-                    OpCode::LogicalNot => {
-                        let mut result: f32 = 1.0;
-                        if !a.is_empty() {
-                            result = 0.0;
-                        }
-                        Value::Number(result)
-                    }
-                    OpCode::UnaryMinus | OpCode::UnaryPlus => {
-                        // if we go out of bounds on a field variable, push zero
-                        Value::Number(0.0)
-                    }
-                    _ => panic!(
-                        "Unknown op code given for unary operation on string: '{:?}'",
-                        op_code
-                    ),
-                };
-                self.stack.push(val)
-            }
-        } else {
-            let err_msg = "Unary operand must be a number or string.";
-            eprintln!("{}", err_msg);
-            panic!("{}", err_msg);
-        }
+    /// # Return value
+    /// - the value that was on the top of the stack
+    /// - an [Error] if the stack was empty, which means that the compiled code is not well formed
+    fn pop(&mut self) -> Result<Value, Error> {
+        self.stack
+            .pop()
+            .ok_or_else(|| Error::new(ErrorKind::Runtime, "attempted to pop an empty stack"))
     }
 
     fn peek(&mut self, distance: usize) -> &Value {
@@ -521,3 +581,11 @@ impl VM {
             .expect("Unable to peek at stack!")
     }
 }
+
+/// Determines whether a string looks like a number, in which case it is used as the index of a field rather than a name
+///
+/// # Arguments
+/// - `text` the string to inspect
+fn is_numeric(text: &str) -> bool {
+    text.trim().parse::<f32>().is_ok()
+}
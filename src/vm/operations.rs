@@ -0,0 +1,234 @@
+//! Operations on values, shared by the [VM](crate::vm::VM) and the
+//! [optimizer](crate::optimizer), which folds operations on constants at compile time
+
+use crate::chunk::OpCode;
+use crate::value::Value;
+
+/// Perform an arithmetic operation on two values
+///
+/// The values shall be implicitly converted into their numeric representations for the operations
+/// supported by this function
+///
+/// # Arguments
+/// - `op_code` the operation to perform
+/// - `a` the left hand side of the operation
+/// - `b` the right hand side of the operation
+///
+/// # Return value
+/// the result of the operation
+pub fn arithmetic(op_code: &OpCode, a: &Value, b: &Value) -> Value {
+    let a = a.num_value();
+    let b = b.num_value();
+
+    match *op_code {
+        OpCode::Add => Value::Number(a + b),
+        OpCode::Subtract => Value::Number(a - b),
+        OpCode::Multiply => Value::Number(a * b),
+        OpCode::Divide => Value::Number(a / b),
+        OpCode::Modulus => Value::Number(a % b),
+        OpCode::Exponentiation => Value::Number(a.powf(b)),
+        _ => panic!(
+            "Unknown op code given for arithmetic operation '{:?}'",
+            op_code
+        ),
+    }
+}
+
+/// Concatenate two values
+///
+/// The values shall be implicitly converted into their string representations
+///
+/// # Arguments
+/// - `a` the left hand side of the concatenation
+/// - `b` the right hand side of the concatenation
+///
+/// # Return value
+/// the concatenated string
+pub fn concatenation(a: &Value, b: &Value) -> Value {
+    let mut concat_str = a.str_value();
+    concat_str.push_str(&b.str_value());
+    Value::String(concat_str)
+}
+
+/// Perform a relational comparison between two values
+///
+/// When two operands are compared, either string comparison or numeric comparison may be
+/// used. This depends upon the attributes of the operands, according to the following
+/// symmetric matrix:
+///         +----------------------------------------------
+///         |       STRING          NUMERIC         STRNUM
+/// --------+----------------------------------------------
+///         |
+/// STRING  |       string          string          string
+///         |
+/// NUMERIC |       string          numeric         numeric
+///         |
+/// STRNUM  |       string          numeric         numeric
+/// --------+----------------------------------------------
+/// [Source - GNU Awk Manual](https://www.gnu.org/software/gawk/manual/html_node/Variable-Typing.html)
+///
+/// # Arguments
+/// - `op_code` the operation to perform
+/// - `a` the left hand side of the comparison
+/// - `b` the right hand side of the comparison
+///
+/// # Return value
+/// a number, one if the comparison holds and zero otherwise
+pub fn comparison(op_code: &OpCode, a: &Value, b: &Value) -> Value {
+    let is_string_comparison = matches!(a, Value::String(_)) || matches!(b, Value::String(_));
+
+    if is_string_comparison {
+        string_comparison(op_code, &a.str_value(), &b.str_value())
+    } else {
+        // implicitly convert `Value::StrNum` to numbers
+        numeric_comparison(op_code, a.num_value(), b.num_value())
+    }
+}
+
+/// Perform a relational comparison between two strings
+///
+/// # Arguments
+/// - `op_code` the operation to perform
+/// - `a` the first argument of the comparison. Is placed on the left hand side of the expression
+/// - `b` the second argument of the comparison. Is placed on the right hand side of the expression
+fn string_comparison(op_code: &OpCode, a: &str, b: &str) -> Value {
+    let result = match *op_code {
+        OpCode::GreaterEqual => a >= b,
+        OpCode::Greater => a > b,
+        OpCode::LessEqual => a <= b,
+        OpCode::Less => a < b,
+        OpCode::DoubleEqual => a.eq(b),
+        OpCode::NotEqual => a.ne(b),
+        _ => panic!(
+            "Unknown op code given for string comparison '{:?}'",
+            op_code
+        ),
+    };
+    Value::Number(result as i32 as f32)
+}
+
+/// Perform a relational comparison between two numbers
+///
+/// # Arguments
+/// - `op_code` the operation to perform
+/// - `a` the first argument of the comparison. Is placed on the left hand side of the expression
+/// - `b` the second argument of the comparison. Is placed on the right hand side of the expression
+fn numeric_comparison(op_code: &OpCode, a: f32, b: f32) -> Value {
+    let result = match *op_code {
+        OpCode::GreaterEqual => a >= b,
+        OpCode::Greater => a > b,
+        OpCode::LessEqual => a <= b,
+        OpCode::Less => a < b,
+        OpCode::DoubleEqual => (a - b).abs() == 0.0,
+        OpCode::NotEqual => (a - b).abs() != 0.0,
+        _ => panic!("Unknown op code given for comparison '{:?}'", op_code),
+    };
+    Value::Number(result as i32 as f32)
+}
+
+/// Perform an operation on a single value
+///
+/// # Arguments
+/// - `op_code` the operation to perform
+/// - `a` the operand of the operation
+///
+/// # Return value
+/// the result of the operation
+pub fn unary(op_code: &OpCode, a: &Value) -> Value {
+    match a {
+        Value::Number(_) | Value::StrNum(_) => {
+            let num_like = a.num_value();
+            match *op_code {
+                // Unary plus will be more useful for converting a string to a number
+                OpCode::UnaryPlus => {
+                    if num_like == 0.0 {
+                        Value::Number(0.0)
+                    } else {
+                        Value::Number(num_like)
+                    }
+                }
+                OpCode::UnaryMinus => {
+                    if num_like == 0.0 {
+                        Value::Number(0.0)
+                    } else {
+                        Value::Number(-num_like)
+                    }
+                }
+                OpCode::LogicalNot => {
+                    let mut result: f32 = 1.0;
+                    if num_like > 0.0 {
+                        result = 0.0;
+                    }
+                    Value::Number(result)
+                }
+                _ => panic!(
+                    "Unknown op code given for unary on num/strnum: '{:?}'",
+                    op_code
+                ),
+            }
+        }
+        Value::String(a) => match *op_code {
+            OpCode::LogicalNot => {
+                let mut result: f32 = 1.0;
+                if !a.is_empty() {
+                    result = 0.0;
+                }
+                Value::Number(result)
+            }
+            OpCode::UnaryMinus | OpCode::UnaryPlus => {
+                // if we go out of bounds on a field variable, push zero
+                Value::Number(0.0)
+            }
+            _ => panic!(
+                "Unknown op code given for unary operation on string: '{:?}'",
+                op_code
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod value_operations {
+    use super::*;
+    use crate::value::StrNum;
+
+    #[test]
+    fn it_compares_strings_when_either_operand_is_a_string() {
+        assert_eq!(
+            comparison(
+                &OpCode::Less,
+                &Value::String(String::from("10")),
+                &Value::Number(9.0)
+            ),
+            Value::Number(1.0)
+        );
+    }
+
+    #[test]
+    fn it_compares_numbers_when_neither_operand_is_a_string() {
+        assert_eq!(
+            comparison(
+                &OpCode::Less,
+                &Value::StrNum(StrNum::from("10")),
+                &Value::Number(9.0)
+            ),
+            Value::Number(0.0)
+        );
+    }
+
+    #[test]
+    fn it_concatenates_the_string_values_of_its_operands() {
+        assert_eq!(
+            concatenation(&Value::Number(1.0), &Value::String(String::from("a"))),
+            Value::String(String::from("1a"))
+        );
+    }
+
+    #[test]
+    fn it_negates_a_string_to_zero() {
+        assert_eq!(
+            unary(&OpCode::UnaryMinus, &Value::String(String::from("a"))),
+            Value::Number(0.0)
+        );
+    }
+}
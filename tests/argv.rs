@@ -0,0 +1,84 @@
+//! Integration tests for the ARGC and ARGV special variables
+
+pub mod utils;
+
+#[cfg(test)]
+mod argv {
+    use crate::utils;
+
+    #[test]
+    fn it_counts_the_program_name_and_operands_in_argc() {
+        utils::CodeRunner::init()
+            .program("BEGIN {print ARGC;}")
+            .cli_options(vec!["foo", "bar=baz"])
+            .expect_output("3")
+            .assert()
+    }
+
+    #[test]
+    fn it_stores_the_program_name_and_operands_in_argv() {
+        utils::CodeRunner::init()
+            .program("BEGIN {print ARGV[0], ARGV[1], ARGV[2];}")
+            .cli_options(vec!["foo", "bar=baz"])
+            .expect_output("rawk foo bar=baz")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_a_file_appended_to_argv() {
+        utils::CodeRunner::init()
+            .program(
+                r#"BEGIN {ARGV[ARGC] = "./tests/data/hours2.dat"; ARGC = ARGC + 1;} {print $1;}"#,
+            )
+            .cli_options(vec!["./tests/data/hours1.dat"])
+            .expect_output("Alice\nBob\nCharlie\nDan\nErin\nFrank\nGerry\nHannah\nIgor\nLauren")
+            .assert()
+    }
+
+    #[test]
+    fn it_skips_a_file_deleted_from_argv() {
+        utils::CodeRunner::init()
+            .program("BEGIN {delete ARGV[1];} {print $1;}")
+            .cli_options(vec!["./does_not_exist.dat", "./tests/data/hours2.dat"])
+            .expect_output("Frank\nGerry\nHannah\nIgor\nLauren")
+            .assert()
+    }
+
+    #[test]
+    fn it_skips_a_file_blanked_out_in_argv() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {ARGV[2] = "";} {print $1;}"#)
+            .cli_options(vec!["./tests/data/hours2.dat", "./does_not_exist.dat"])
+            .expect_output("Frank\nGerry\nHannah\nIgor\nLauren")
+            .assert()
+    }
+
+    #[test]
+    fn it_ignores_elements_past_argc() {
+        utils::CodeRunner::init()
+            .program("BEGIN {ARGC = 2;} {print $1;}")
+            .cli_options(vec!["./tests/data/hours2.dat", "./does_not_exist.dat"])
+            .expect_output("Frank\nGerry\nHannah\nIgor\nLauren")
+            .assert()
+    }
+
+    #[test]
+    fn it_performs_an_assignment_added_to_argv() {
+        utils::CodeRunner::init()
+            .program(
+                r#"BEGIN {ARGV[1] = "name=Igor"; ARGV[2] = "./tests/data/hours2.dat"; ARGC = 3;} $1 == name {print $3;}"#,
+            )
+            .expect_output("10")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_stdin_when_every_file_is_removed_from_argv() {
+        utils::CodeRunner::init()
+            .program("BEGIN {delete ARGV;} {print $1;}")
+            .cli_options(vec!["./tests/data/hours1.dat"])
+            .stdin_data("Zed 1 2")
+            .expect_output("Zed")
+            .assert()
+    }
+}
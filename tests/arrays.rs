@@ -0,0 +1,75 @@
+//! Integration tests for arrays
+
+pub mod utils;
+
+#[cfg(test)]
+mod arrays {
+    use crate::utils;
+
+    #[test]
+    fn it_stores_and_reads_back_an_element() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {fruits["apple"] = 3; print fruits["apple"];}"#)
+            .expect_output("3")
+            .assert()
+    }
+
+    #[test]
+    fn it_uses_the_string_value_of_a_numeric_subscript() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {nums[1] = "one"; print nums["1"];}"#)
+            .expect_output("one")
+            .assert()
+    }
+
+    #[test]
+    fn it_evaluates_expressions_in_subscripts() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {i = 1; nums[i + 1] = "two"; print nums[2];}"#)
+            .expect_output("two")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_an_uninitialized_element_as_an_empty_string() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {print "[" nums[1] "]";}"#)
+            .expect_output(r#"\[\]"#)
+            .assert()
+    }
+
+    #[test]
+    fn it_uses_an_assigned_element_as_an_expression() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {print nums[1] = 2; print nums[1] * 2;}"#)
+            .expect_output("2\n4")
+            .assert()
+    }
+
+    #[test]
+    fn it_persists_elements_across_records() {
+        utils::CodeRunner::init()
+            .program(r#"{hours[$1] = $3;} END {print hours["Bob"], hours["Dan"];}"#)
+            .cli_options(vec!["./tests/data/hours1.dat"])
+            .expect_output("20 0")
+            .assert()
+    }
+
+    #[test]
+    fn it_deletes_an_element() {
+        utils::CodeRunner::init()
+            .program(
+                r#"BEGIN {nums[1] = 1; nums[2] = 2; delete nums[1]; print nums[1] "," nums[2];}"#,
+            )
+            .expect_output(",2")
+            .assert()
+    }
+
+    #[test]
+    fn it_deletes_an_entire_array() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {nums[1] = 1; nums[2] = 2; delete nums; print nums[1] "," nums[2];}"#)
+            .expect_output(",")
+            .assert()
+    }
+}
@@ -0,0 +1,62 @@
+//! Integration tests for BEGIN and END actions
+
+pub mod utils;
+
+#[cfg(test)]
+mod begin_end {
+    use crate::utils;
+
+    #[test]
+    fn it_runs_a_begin_action_without_reading_input() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {print "Hello";}"#)
+            .stdin_data("World")
+            .expect_output("Hello")
+            .assert()
+    }
+
+    #[test]
+    fn it_runs_begin_actions_before_other_actions() {
+        utils::CodeRunner::init()
+            .program(r#"{print $1;} BEGIN {print "first";} BEGIN {print "second";}"#)
+            .stdin_data("third")
+            .expect_output("first\nsecond\nthird")
+            .assert()
+    }
+
+    #[test]
+    fn it_runs_end_actions_after_all_records() {
+        utils::CodeRunner::init()
+            .program(r#"END {print "done";} {print $1;}"#)
+            .cli_options(vec!["./tests/data/hours1.dat"])
+            .expect_output("Alice\nBob\nCharlie\nDan\nErin\ndone")
+            .assert()
+    }
+
+    #[test]
+    fn it_retains_the_last_record_in_end_actions() {
+        utils::CodeRunner::init()
+            .program(r#"END {print $1, $3;}"#)
+            .cli_options(vec!["./tests/data/hours1.dat", "./tests/data/hours2.dat"])
+            .expect_output("Lauren 32")
+            .assert()
+    }
+
+    #[test]
+    fn it_shares_variables_between_begin_main_and_end() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {total = 0;} {total = total + $3;} END {print total;}"#)
+            .cli_options(vec!["./tests/data/hours1.dat"])
+            .expect_output("100")
+            .assert()
+    }
+
+    #[test]
+    fn it_runs_end_actions_after_reading_from_stdin() {
+        utils::CodeRunner::init()
+            .program(r#"END {print "last record:", $0;}"#)
+            .stdin_data("Alice 40 25")
+            .expect_output("last record: Alice 40 25")
+            .assert()
+    }
+}
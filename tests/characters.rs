@@ -0,0 +1,75 @@
+//! Integration tests for what a single character of a string is
+
+pub mod utils;
+
+#[cfg(test)]
+mod characters {
+    use crate::utils;
+
+    #[test]
+    fn it_counts_utf8_characters() {
+        utils::CodeRunner::init()
+            .program("{print length($1), length;}")
+            .stdin_data("naïve 日本")
+            .expect_output("5 8")
+            .assert()
+    }
+
+    #[test]
+    fn it_retrieves_substrings_of_utf8_characters() {
+        utils::CodeRunner::init()
+            .program("{print substr($0, 2, 2), substr($0, 4);}")
+            .stdin_data("日本語です")
+            .expect_output("本語 です")
+            .assert()
+    }
+
+    #[test]
+    fn it_finds_the_position_of_a_string_in_utf8_characters() {
+        utils::CodeRunner::init()
+            .program(r#"{print index($0, "語"), index($0, "x");}"#)
+            .stdin_data("日本語")
+            .expect_output("3 0")
+            .assert()
+    }
+
+    #[test]
+    fn it_splits_a_record_into_utf8_characters() {
+        utils::CodeRunner::init()
+            .program("{print $2, $3;}")
+            .cli_options(vec!["-F", ""])
+            .stdin_data("aé日")
+            .expect_output("é 日")
+            .assert()
+    }
+
+    #[test]
+    fn it_splits_a_record_by_a_multi_byte_field_separator() {
+        utils::CodeRunner::init()
+            .program("{print $2;}")
+            .cli_options(vec!["-F→"])
+            .stdin_data("a→b→c")
+            .expect_output("b")
+            .assert()
+    }
+
+    #[test]
+    fn it_counts_bytes_with_bytes() {
+        utils::CodeRunner::init()
+            .program(r#"{print length, index($0, "本"), substr($0, 4, 3);}"#)
+            .cli_options(vec!["--bytes"])
+            .stdin_data("日本")
+            .expect_output("6 4 本")
+            .assert()
+    }
+
+    #[test]
+    fn it_splits_fixed_width_fields_by_bytes_with_bytes() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FIELDWIDTHS = "2 3";} {print $2;}"#)
+            .cli_options(vec!["-b"])
+            .stdin_data("abéc")
+            .expect_output("éc")
+            .assert()
+    }
+}
@@ -0,0 +1,138 @@
+//! Integration tests for variable assignments provided on the command line
+
+pub mod utils;
+
+#[cfg(test)]
+mod command_line_assignments {
+    use crate::utils;
+
+    #[test]
+    fn it_assigns_a_variable_before_the_program_runs() {
+        utils::CodeRunner::init()
+            .program("{print foo;}")
+            .cli_options(vec!["-q", "-v", "foo=bar"])
+            .expect_output("bar")
+            .assert()
+    }
+
+    #[test]
+    fn it_assigns_multiple_variables_before_the_program_runs() {
+        utils::CodeRunner::init()
+            .program("{print foo, bar;}")
+            .cli_options(vec!["-q", "-v", "foo=1", "-v", "bar=2"])
+            .expect_output("1 2")
+            .assert()
+    }
+
+    #[test]
+    fn it_uses_the_last_assignment_for_the_same_variable() {
+        utils::CodeRunner::init()
+            .program("{print foo;}")
+            .cli_options(vec!["-q", "-v", "foo=1", "--assign", "foo=2"])
+            .expect_output("2")
+            .assert()
+    }
+
+    #[test]
+    fn it_processes_escape_sequences_in_assigned_values() {
+        utils::CodeRunner::init()
+            .program("{print foo;}")
+            .cli_options(vec!["-q", "-v", r#"foo=a\tb\"c\\"#])
+            .expect_output("a\tb\"c\\\\")
+            .assert()
+    }
+
+    #[test]
+    fn it_assigns_a_numeric_string_for_numeric_values() {
+        // a strnum is compared numerically with a number
+        utils::CodeRunner::init()
+            .program("{print foo == 10;}")
+            .cli_options(vec!["-q", "-v", "foo=10.0"])
+            .expect_output("1")
+            .assert()
+    }
+
+    #[test]
+    fn it_assigns_a_string_for_non_numeric_values() {
+        // a string is compared as a string with a number
+        utils::CodeRunner::init()
+            .program("{print foo == 10;}")
+            .cli_options(vec!["-q", "-v", "foo=10abc"])
+            .expect_output("0")
+            .assert()
+    }
+
+    #[test]
+    fn it_fails_for_an_invalid_assignment() {
+        utils::CodeRunner::init()
+            .program("{print foo;}")
+            .cli_options(vec!["-q", "-v", "1foo=bar"])
+            .assert_fail()
+    }
+
+    #[test]
+    fn it_performs_assignment_operands_when_they_are_reached() {
+        utils::CodeRunner::init()
+            .program("{print prefix, $1;}")
+            .cli_options(vec![
+                "prefix=first",
+                "./tests/data/hours1.dat",
+                "prefix=second",
+                "./tests/data/hours2.dat",
+            ])
+            .expect_output(
+                r#"first Alice
+first Bob
+first Charlie
+first Dan
+first Erin
+second Frank
+second Gerry
+second Hannah
+second Igor
+second Lauren"#,
+            )
+            .assert()
+    }
+
+    #[test]
+    fn it_performs_assignment_operands_after_assignment_flags() {
+        utils::CodeRunner::init()
+            .program("$1 == name {print rate;}")
+            .cli_options(vec![
+                "-v",
+                "name=Bob",
+                "-v",
+                "rate=none",
+                "rate=20.75",
+                "./tests/data/hours1.dat",
+            ])
+            .expect_output("20.75")
+            .assert()
+    }
+
+    #[test]
+    fn it_does_not_perform_a_trailing_assignment_operand_for_prior_files() {
+        utils::CodeRunner::init()
+            .program("{print foo $1;}")
+            .cli_options(vec!["./tests/data/hours1.dat", "foo=bar"])
+            .expect_output(
+                r#"Alice
+Bob
+Charlie
+Dan
+Erin"#,
+            )
+            .assert()
+    }
+
+    #[test]
+    fn it_performs_assignment_operands_before_reading_stdin() {
+        utils::CodeRunner::init()
+            .program("{print foo, $1;}")
+            .cli_options(vec!["foo=bar"])
+            .stdin_data("Alice 40 25")
+            .expect_output("bar Alice")
+            .assert()
+    }
+}
@@ -0,0 +1,69 @@
+//! Integration tests for reading data files that are compressed
+
+pub mod utils;
+
+#[cfg(test)]
+mod compressed_input {
+    use crate::utils;
+
+    #[test]
+    fn it_reads_a_gzip_compressed_data_file() {
+        utils::CodeRunner::init()
+            .program("$1 == \"Bob\" {print FILENAME, $3;}")
+            .cli_options(vec!["./tests/data/hours1.dat.gz"])
+            .expect_output("./tests/data/hours1.dat.gz 20")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_a_zstd_compressed_data_file() {
+        utils::CodeRunner::init()
+            .program("{total = total + $3;} END {print total;}")
+            .cli_options(vec!["./tests/data/hours1.dat.zst"])
+            .expect_output("100")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_every_frame_of_a_zstd_compressed_data_file() {
+        utils::CodeRunner::init()
+            .program("{total = total + $3;} END {print total;}")
+            .cli_options(vec!["./tests/data/hours1_twice.dat.zst"])
+            .expect_output("200")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_a_bzip2_compressed_data_file() {
+        utils::CodeRunner::init()
+            .program("END {print FILENAME, $1;}")
+            .cli_options(vec!["./tests/data/hours2.dat.bz2"])
+            .expect_output("./tests/data/hours2.dat.bz2 Lauren")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_compressed_and_uncompressed_data_files_together() {
+        utils::CodeRunner::init()
+            .program(r#"$1 == "Alice" || $1 == "Frank" {print FILENAME;}"#)
+            .cli_options(vec![
+                "./tests/data/hours1.dat.gz",
+                "./tests/data/hours2.dat",
+                "./tests/data/hours1.dat.zst",
+            ])
+            .expect_output(
+                "./tests/data/hours1.dat.gz\n./tests/data/hours2.dat\n./tests/data/hours1.dat.zst",
+            )
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_compressed_data_piped_to_stdin() {
+        utils::CodeRunner::init()
+            .program("{total = total + $3;} END {print total;}")
+            .read_all_stdin()
+            .stdin_file("./tests/data/hours1.dat.gz")
+            .expect_output("100")
+            .assert()
+    }
+}
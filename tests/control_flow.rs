@@ -805,4 +805,42 @@ mod control_flow {
             .expect_output("The value is less than or equal to 2")
             .assert()
     }
+
+    #[test]
+    fn it_breaks_out_of_the_innermost_loop_only() {
+        utils::CodeRunner::init()
+            .program(
+                r#"{
+                    for (i=0; i<2; i=i+1) {
+                        for (j=0; j<3; j=j+1) {
+                            if (j == 1) break;
+                        }
+                        print i, j;
+                    }
+                }"#,
+            )
+            .cli_options(vec!["-q"])
+            .expect_output("0 1\n1 1")
+            .assert()
+    }
+
+    #[test]
+    fn it_continues_the_innermost_loop_only() {
+        utils::CodeRunner::init()
+            .program(
+                r#"{
+                    i = 0;
+                    while (i < 2) {
+                        i = i + 1;
+                        for (j=0; j<2; j=j+1) {
+                            continue;
+                        }
+                        print i, j;
+                    }
+                }"#,
+            )
+            .cli_options(vec!["-q"])
+            .expect_output("1 2\n2 2")
+            .assert()
+    }
 }
@@ -0,0 +1,58 @@
+//! Integration tests for reading data as comma separated values
+
+pub mod utils;
+
+#[cfg(test)]
+mod csv {
+    use crate::utils;
+
+    #[test]
+    fn it_splits_quoted_fields_containing_commas() {
+        utils::CodeRunner::init()
+            .program("{print $2, $1;}")
+            .cli_options(vec!["--csv"])
+            .stdin_data("\"Smith, John\",42")
+            .expect_output("42 Smith, John")
+            .assert()
+    }
+
+    #[test]
+    fn it_unescapes_doubled_quotes() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["--csv"])
+            .stdin_data("\"say \"\"hi\"\"\"")
+            .expect_output("say \"hi\"")
+            .assert()
+    }
+
+    #[test]
+    fn it_ignores_the_field_separator() {
+        utils::CodeRunner::init()
+            .program("{print $2;}")
+            .cli_options(vec!["--csv", "-F;"])
+            .stdin_data("a;b,c")
+            .expect_output("c")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_a_quoted_field_spanning_lines_as_one_record() {
+        utils::CodeRunner::init()
+            .program("{print $1 \"-\" $3;}")
+            .read_all_stdin()
+            .cli_options(vec!["--csv"])
+            .stdin_data("a,b,\"1 Main St\nApt 2\"\nc,d,e\n")
+            .expect_output("a-1 Main St\nApt 2\nc-e")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_a_data_file_with_crlf_line_endings() {
+        utils::CodeRunner::init()
+            .program("{print $1 \"-\" $2 \"-\" $3;}")
+            .cli_options(vec!["--csv", "./tests/data/people.csv"])
+            .expect_output("name-age-address\nSmith, John-42-1 Main St\nApt 2\nO\"Brien, Pat-37-")
+            .assert()
+    }
+}
@@ -0,0 +1,367 @@
+//! Integration tests for embedding r-awk as a library
+
+#[cfg(test)]
+mod embedding {
+    use rawk::{Error, ErrorKind, Interpreter, NativeFunctions, Program, Value};
+    use std::io;
+    use std::io::{Cursor, Write};
+    use std::thread;
+
+    /// Compiles and runs a program against the provided input, returning the exit status and output
+    fn run(source: &str, input: &str) -> (i32, String) {
+        let program = Program::compile(source).expect("the program should compile");
+        let mut interpreter = Interpreter::new(program);
+        let mut output = Vec::new();
+        let status = interpreter
+            .run(Cursor::new(input), &mut output)
+            .expect("the program should run");
+        (status, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn it_writes_output_to_the_provided_writer() {
+        let (status, output) = run(
+            r#"BEGIN {print "start";} {print $2;} END {print "end";}"#,
+            "a 1\nb 2\n",
+        );
+
+        assert_eq!(status, 0);
+        assert_eq!(output, "start\n1\n2\nend\n");
+    }
+
+    #[test]
+    fn it_does_not_read_input_for_only_begin_actions() {
+        let program = Program::compile(r#"BEGIN {print "only";}"#).unwrap();
+        let mut interpreter = Interpreter::new(program);
+        let mut input = Cursor::new("unread\n");
+        let mut output = Vec::new();
+
+        interpreter.run(&mut input, &mut output).unwrap();
+
+        assert_eq!(input.position(), 0);
+        assert_eq!(output, b"only\n");
+    }
+
+    #[test]
+    fn it_splits_records_with_the_provided_field_separator() {
+        let program = Program::compile("{print $2;}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_field_separator(",");
+        let mut output = Vec::new();
+
+        interpreter
+            .run(Cursor::new("a,b\nc,d"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"b\nd\n");
+    }
+
+    #[test]
+    fn it_splits_records_as_comma_separated_values() {
+        let program = Program::compile("{print $2;}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_csv();
+        let mut output = Vec::new();
+
+        interpreter
+            .run(
+                Cursor::new("1,\"a, \"\"b\"\"\"\n2,\"multi\nline\"\n"),
+                &mut output,
+            )
+            .unwrap();
+
+        assert_eq!(output, b"a, \"b\"\nmulti\nline\n");
+    }
+
+    #[test]
+    fn it_names_fields_after_a_header() {
+        let program = Program::compile(r#"{print $"name", FIELD["age"];}"#).unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_header();
+        let mut output = Vec::new();
+
+        interpreter
+            .run(Cursor::new("age name\n40 alice\n"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"alice 1\n");
+    }
+
+    #[test]
+    fn it_reads_json_lines() {
+        let program = Program::compile(r#"{print J["user.name"];}"#).unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_jsonl();
+        let mut output = Vec::new();
+
+        interpreter
+            .run(
+                Cursor::new("{\"user\": {\"name\": \"alice\"}}\n"),
+                &mut output,
+            )
+            .unwrap();
+
+        assert_eq!(output, b"alice\n");
+    }
+
+    #[test]
+    fn it_counts_bytes_as_characters() {
+        let program = Program::compile("{print length($1), substr($1, 1, 1);}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_bytes();
+        let mut output = Vec::new();
+
+        interpreter.run(Cursor::new("été\n"), &mut output).unwrap();
+
+        // the first byte of `é` is written out on its own
+        assert_eq!(output, b"5 \xc3\n");
+    }
+
+    #[test]
+    fn it_counts_bytes_of_input_that_is_not_valid_utf8_as_they_were_read() {
+        let program = Program::compile(r#"{print length($0), index($0, "b");}"#).unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_bytes();
+        let mut output = Vec::new();
+
+        interpreter
+            .run(Cursor::new(b"a\xffb\xc3\xa9\n"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"5 3\n");
+    }
+
+    #[test]
+    fn it_writes_back_input_that_is_not_valid_utf8_as_it_was_read() {
+        let program = Program::compile("{print $2, $1;}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_record_separator("\0");
+        let mut output = Vec::new();
+
+        interpreter
+            .run(Cursor::new(b"\xff\xfe \x80ok\0"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"\x80ok \xff\xfe\n");
+    }
+
+    #[test]
+    fn it_prints_values_as_comma_separated_values() {
+        let program = Program::compile("{print $2, $1 + 1;}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_ocsv();
+        let mut output = Vec::new();
+
+        interpreter
+            .run(Cursor::new("41 a,b\n"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"\"a,b\",42\n");
+    }
+
+    #[test]
+    fn it_presets_and_reads_back_global_variables() {
+        let program = Program::compile("{count = count + $1;} END {print label count;}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_global("count", "10");
+        interpreter.set_global("label", "count=");
+        let mut output = Vec::new();
+
+        interpreter.run(Cursor::new("1\n2\n"), &mut output).unwrap();
+
+        assert_eq!(output, b"count=13\n");
+        assert_eq!(interpreter.get_global("count"), "13");
+        assert_eq!(interpreter.get_global("label"), "count=");
+        assert_eq!(interpreter.get_global("never_assigned"), "");
+    }
+
+    #[test]
+    fn it_runs_an_interpreter_moved_to_another_thread() {
+        let program = Program::compile("{ total = total + $2; }").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        interpreter.set_field_separator(",");
+        interpreter.set_header();
+
+        let interpreter = thread::spawn(move || {
+            interpreter
+                .run(Cursor::new("name,count\na,1\nb,2\n"), &mut Vec::new())
+                .unwrap();
+            interpreter
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(interpreter.get_global("total"), "3");
+    }
+
+    #[test]
+    fn it_keeps_global_variables_between_runs() {
+        let program = Program::compile("{seen = seen + 1;}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+
+        interpreter
+            .run(Cursor::new("a\nb\n"), &mut Vec::new())
+            .unwrap();
+        interpreter
+            .run(Cursor::new("c\n"), &mut Vec::new())
+            .unwrap();
+
+        assert_eq!(interpreter.get_global("seen"), "3");
+    }
+
+    #[test]
+    fn it_returns_compile_errors_as_values() {
+        let error = Program::compile("{print 1;")
+            .err()
+            .expect("the program should not compile");
+
+        assert_eq!(
+            error.errors(),
+            [Error::new(
+                ErrorKind::Syntax,
+                "syntax error at end: Expect '}' after block."
+            )
+            .with_line(1)
+            .with_column(10)]
+        );
+        assert_eq!(
+            error.to_string(),
+            "cmd. line:1:10: syntax error at end: Expect '}' after block."
+        );
+    }
+
+    #[test]
+    fn it_returns_runtime_errors_as_values() {
+        let program = Program::compile("{print $1;} {print $(-1);}").unwrap();
+        let mut interpreter = Interpreter::new(program);
+        let mut output = Vec::new();
+
+        let error = interpreter
+            .run(Cursor::new("a\nb\n"), &mut output)
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Runtime);
+        assert_eq!(error.message(), "trying to access out of range field -1");
+        assert_eq!(error.line(), Some(1));
+        assert_eq!(error.filename(), Some("-"));
+        assert_eq!(error.fnr(), Some(1));
+        // the program halts at the first runtime error
+        assert_eq!(output, b"a\n");
+    }
+
+    /// An output whose reader has stopped reading, as `head` does once it has read enough
+    struct ClosedOutput;
+
+    impl Write for ClosedOutput {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_stops_at_a_broken_pipe() {
+        let program = Program::compile("{print $1;}").unwrap();
+
+        let error = Interpreter::new(program)
+            .run(Cursor::new("a\nb\n"), &mut ClosedOutput)
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+        assert_eq!(error.fnr(), Some(1));
+    }
+
+    /// Creates native functions for tests to call
+    fn natives() -> NativeFunctions {
+        let mut natives = NativeFunctions::new();
+        natives
+            .register("join", 2, |args| {
+                Value::String(format!("{}-{}", args[0], args[1]))
+            })
+            .unwrap();
+        natives
+            .register("half", 1, |args| Value::Number(args[0].num_value() / 2.0))
+            .unwrap();
+        natives
+    }
+
+    #[test]
+    fn it_calls_native_functions() {
+        let program = Program::compile_with_natives(
+            r#"{ print join($1, half($2)); } $2 > half(10) { print "big"; }"#,
+            &natives(),
+        )
+        .unwrap();
+        let mut output = Vec::new();
+
+        Interpreter::new(program)
+            .run(Cursor::new("a 4\nb 12\n"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"a-2\nb-6\nbig\n");
+    }
+
+    #[test]
+    fn it_passes_folded_constants_to_native_functions() {
+        let program = Program::compile_with_natives(
+            "BEGIN { if (half(1 + 1) == 1) print join(\"x\", 1 2); }",
+            &natives(),
+        )
+        .unwrap();
+        let mut output = Vec::new();
+
+        Interpreter::new(program)
+            .run(Cursor::new(""), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"x-12\n");
+    }
+
+    #[test]
+    fn it_runs_a_program_compiled_with_native_functions_on_another_thread() {
+        let program = Program::compile_with_natives("{ print half($1); }", &natives()).unwrap();
+
+        let output = thread::spawn(move || {
+            let mut output = Vec::new();
+            Interpreter::new(program)
+                .run(Cursor::new("8\n"), &mut output)
+                .unwrap();
+            output
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(output, b"4\n");
+    }
+
+    #[test]
+    fn it_runs_a_program_compiled_without_optimizing() {
+        let program = Program::compile_unoptimized(
+            "BEGIN { print half(2 * 3) + 1; } 1 { print join($1, 2 3); }",
+            &natives(),
+        )
+        .unwrap();
+        let mut output = Vec::new();
+
+        Interpreter::new(program)
+            .run(Cursor::new("a\n"), &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"4\na-23\n");
+    }
+
+    #[test]
+    fn it_rejects_a_native_call_with_the_wrong_number_of_arguments() {
+        let error = Program::compile_with_natives("{ print join($1); }", &natives())
+            .err()
+            .expect("the program should not compile");
+
+        assert_eq!(
+            error.to_string(),
+            "cmd. line:1:9: syntax error at 'join': Expect 2 arguments but got 1."
+        );
+    }
+}
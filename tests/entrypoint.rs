@@ -62,14 +62,40 @@ mod entrypoint {
     }
 
     #[test]
-    fn panics_for_a_non_existent_file() {
+    fn runs_an_awk_program_from_stdin() {
+        utils::CodeRunner::init()
+            .cli_options(vec!["-f", "-", "./tests/data/hours2.dat"])
+            .stdin_data("{print $1;}")
+            .expect_output("Frank\nGerry\nHannah\nIgor\nLauren")
+            .assert()
+    }
+
+    #[test]
+    fn runs_an_awk_program_from_stdin_and_a_file() {
+        utils::CodeRunner::init()
+            .cli_options(vec![
+                "-f",
+                "./awk_examples/field_variables/it_prints_line_parts.awk",
+                "-f",
+                "-",
+                "./tests/data/hours2.dat",
+            ])
+            .stdin_data("$1 == \"Igor\" {print \"found\";}")
+            .expect_output("15.00 11\n1.75 19\n25.50 40\n0 10\nfound\n22.00 32")
+            .assert()
+    }
+
+    #[test]
+    fn fails_for_a_non_existent_file() {
         utils::CodeRunner::init()
             .cli_options(vec!["-f", "./does_not_exist.awk"])
-            .assert_fail();
+            .assert_error(
+                "can't open file ./does_not_exist.awk: No such file or directory (os error 2)",
+            );
     }
 
     #[test]
-    fn panics_for_a_non_existent_file_many_given() {
+    fn fails_for_a_non_existent_file_many_given() {
         utils::CodeRunner::init()
             .cli_options(vec![
                 "-f",
@@ -201,6 +227,69 @@ Erin 660"#,
             .assert();
     }
 
+    #[test]
+    fn reads_stdin_for_a_dash_data_file() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["-"])
+            .stdin_data("Zed 1 2\nYvonne 3 4")
+            .expect_output("Zed\nYvonne")
+            .assert();
+    }
+
+    #[test]
+    fn reads_stdin_for_a_dash_data_file_between_data_files() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec![
+                "./tests/data/hours1.dat",
+                "-",
+                "./tests/data/hours2.dat",
+            ])
+            .stdin_data("Zed 1 2")
+            .expect_output(
+                r#"Alice
+Bob
+Charlie
+Dan
+Erin
+Zed
+Frank
+Gerry
+Hannah
+Igor
+Lauren"#,
+            )
+            .assert();
+    }
+
+    #[test]
+    fn reads_stdin_for_a_dev_stdin_data_file() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["/dev/stdin", "./tests/data/hours2.dat"])
+            .stdin_data("Zed 1 2")
+            .expect_output("Zed\nFrank\nGerry\nHannah\nIgor\nLauren")
+            .assert();
+    }
+
+    #[test]
+    fn stops_parsing_options_after_double_dash() {
+        utils::CodeRunner::init()
+            .cli_options(vec!["-q", "--", r#"-1 {print "negative one is truthy";}"#])
+            .expect_output("negative one is truthy")
+            .assert();
+    }
+
+    #[test]
+    fn treats_arguments_after_double_dash_as_operands() {
+        utils::CodeRunner::init()
+            .cli_options(vec!["--", "{print $1;}", "-", "./tests/data/hours2.dat"])
+            .stdin_data("-v 1 2")
+            .expect_output("-v\nFrank\nGerry\nHannah\nIgor\nLauren")
+            .assert();
+    }
+
     #[test]
     fn processes_records_for_multiple_files_and_two_truthy_actions_correctly() {
         utils::CodeRunner::init()
@@ -249,14 +338,14 @@ Lauren needs to fill out a tax form"#)
     }
 
     #[test]
-    fn panics_only_a_data_file_is_provided() {
+    fn fails_only_a_data_file_is_provided() {
         utils::CodeRunner::init()
             .cli_options(vec!["./tests/data/hours1.dat"])
             .assert_fail();
     }
 
     #[test]
-    fn panics_when_awk_file_and_program_literal_are_provided() {
+    fn fails_when_awk_file_and_program_literal_are_provided() {
         utils::CodeRunner::init()
             .cli_options(vec![
                 "-f",
@@ -265,4 +354,81 @@ Lauren needs to fill out a tax form"#)
             ])
             .assert_fail();
     }
+
+    #[test]
+    fn fails_for_a_non_existent_data_file() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["./does_not_exist.dat"])
+            .assert_error(
+                "can't open file ./does_not_exist.dat: No such file or directory (os error 2)",
+            );
+    }
+
+    #[test]
+    fn fails_for_an_unterminated_string() {
+        utils::CodeRunner::init()
+            .program(r#"{print "Hello;}"#)
+            .assert_error(
+                "cmd. line:1:8: syntax error: Unterminated string.\n{print \"Hello;}\n       ^",
+            );
+    }
+
+    #[test]
+    fn fails_with_each_syntax_error_in_a_program() {
+        utils::CodeRunner::init()
+            .program("{x = ;}\nEND {print 1 +;}")
+            .assert_error(concat!(
+                "cmd. line:1:6: syntax error at ';': Expect expression.\n",
+                "{x = ;}\n",
+                "     ^\n",
+                "rawk: cmd. line:2:15: syntax error at ';': Expect expression.\n",
+                "END {print 1 +;}\n",
+                "              ^",
+            ));
+    }
+
+    #[test]
+    fn fails_for_a_syntax_error_in_a_program_file() {
+        utils::CodeRunner::init()
+            .cli_options(vec![
+                "-f",
+                "./awk_examples/control_flow/it_suppports_for_loop_no_init.awk",
+                "-f",
+                "./tests/data/unterminated_block.awk",
+            ])
+            .assert_error("./tests/data/unterminated_block.awk:3:1: syntax error at end: Expect '}' after block.");
+    }
+
+    #[test]
+    fn fails_for_a_runtime_error() {
+        utils::CodeRunner::init()
+            .program("{print $(0 - 1);}")
+            .stdin_data("data")
+            .assert_error(concat!(
+                "cmd. line:1: (FILENAME=- FNR=1) trying to access out of range field -1\n",
+                "{print $(0 - 1);}",
+            ));
+    }
+
+    #[test]
+    fn fails_for_a_runtime_error_with_the_record_of_a_data_file() {
+        utils::CodeRunner::init()
+            .program("$1 == \"Gerry\" {\n    print $(0 - $3);\n}")
+            .cli_options(vec!["./tests/data/hours1.dat", "./tests/data/hours2.dat"])
+            .assert_error(concat!(
+                "cmd. line:2: (FILENAME=./tests/data/hours2.dat FNR=2) trying to access out of range field -19\n",
+                "    print $(0 - $3);",
+            ));
+    }
+
+    #[test]
+    fn fails_for_a_runtime_error_in_a_begin_action() {
+        utils::CodeRunner::init()
+            .program("BEGIN {print $(0.5);}")
+            .assert_error(concat!(
+                "cmd. line:1: trying to access non integer index 0.5\n",
+                "BEGIN {print $(0.5);}",
+            ));
+    }
 }
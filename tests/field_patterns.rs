@@ -0,0 +1,67 @@
+//! Integration tests for splitting records into fields by their contents, with `FPAT`
+
+pub mod utils;
+
+#[cfg(test)]
+mod field_patterns {
+    use crate::utils;
+
+    #[test]
+    fn it_splits_records_into_the_matches_of_fpat() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FPAT = "[a-z]+=[0-9]+";} {print $2, $1;}"#)
+            .stdin_data("12:00 user=42 took ms=17")
+            .expect_output("ms=17 user=42")
+            .assert()
+    }
+
+    #[test]
+    fn it_ignores_the_field_separator_while_fpat_is_set() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["-F,", "-v", "FPAT=[0-9]+"])
+            .stdin_data("a,12,b,34")
+            .expect_output("12")
+            .assert()
+    }
+
+    #[test]
+    fn it_returns_to_the_field_separator_once_fpat_is_cleared() {
+        utils::CodeRunner::init()
+            .program(r#"{print $1; FPAT = "";}"#)
+            .read_all_stdin()
+            .cli_options(vec!["-v", "FPAT=[0-9]+"])
+            .stdin_data("a 12 b\nc 34 d\n")
+            .expect_output("12\nc")
+            .assert()
+    }
+
+    #[test]
+    fn it_returns_to_the_field_separator_assigned_by_the_program() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FS = ","; FPAT = "[0-9]+";} {print $1; FPAT = "";}"#)
+            .read_all_stdin()
+            .stdin_data("a,12,b\nc,34,d\n")
+            .expect_output("12\nc")
+            .assert()
+    }
+
+    #[test]
+    fn it_splits_by_the_field_separator_assigned_after_fpat() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FS = ",";} {print PROCINFO["FS"], $1;}"#)
+            .cli_options(vec!["-v", "FPAT=[0-9]+"])
+            .stdin_data("a,12,b")
+            .expect_output("FS a")
+            .assert()
+    }
+
+    #[test]
+    fn it_fails_for_an_invalid_fpat() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["-v", "FPAT=[0-9"])
+            .stdin_data("a")
+            .assert_fail()
+    }
+}
@@ -112,6 +112,44 @@ mod field_variables {
             .assert()
     }
 
+    #[test]
+    fn it_splits_data_by_fs_assigned_in_begin() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FS=",";} {print $2;}"#)
+            .stdin_data("a,b")
+            .expect_output("b")
+            .assert()
+    }
+
+    #[test]
+    fn it_splits_data_by_fs_assigned_on_the_command_line() {
+        utils::CodeRunner::init()
+            .program("{print $2;}")
+            .cli_options(vec!["-v", "FS=,"])
+            .stdin_data("a,b")
+            .expect_output("b")
+            .assert()
+    }
+
+    #[test]
+    fn it_splits_data_by_a_regex_when_fs_is_more_than_one_character() {
+        utils::CodeRunner::init()
+            .program("{print $2 \"-\" $3;}")
+            .cli_options(vec!["-F", "[,;] *"])
+            .stdin_data("a, b;c")
+            .expect_output("b-c")
+            .assert()
+    }
+
+    #[test]
+    fn it_fails_for_an_fs_that_is_not_a_valid_regex() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["-F", "[,"])
+            .stdin_data("a")
+            .assert_fail()
+    }
+
     #[test]
     fn it_splits_data_entirely_when_fs_matches_test_data_single_char() {
         utils::CodeRunner::init()
@@ -168,4 +206,14 @@ mod field_variables {
             .expect_output("0")
             .assert()
     }
+
+    #[test]
+    fn it_treats_a_field_of_zero_surrounded_by_blanks_as_false() {
+        utils::CodeRunner::init()
+            .program(r#"$2 {print "true";} $3 {print $3;}"#)
+            .cli_options(vec!["-F,"])
+            .stdin_data("a, 0 , 1 ")
+            .expect_output(" 1 ")
+            .assert()
+    }
 }
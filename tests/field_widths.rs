@@ -0,0 +1,86 @@
+//! Integration tests for splitting records into fields at fixed positions, with `FIELDWIDTHS`
+
+pub mod utils;
+
+#[cfg(test)]
+mod field_widths {
+    use crate::utils;
+
+    #[test]
+    fn it_splits_records_at_fixed_positions() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FIELDWIDTHS = "5 2:4 *";} {print $3, $2, $1;}"#)
+            .stdin_data("ALICE  0042the rest")
+            .expect_output("the rest 0042 ALICE")
+            .assert()
+    }
+
+    #[test]
+    fn it_reports_the_active_splitter_in_procinfo() {
+        utils::CodeRunner::init()
+            .program(r#"{print PROCINFO["FS"], $1; FIELDWIDTHS = "2";}"#)
+            .read_all_stdin()
+            .stdin_data("abc def\nabc def\n")
+            .expect_output("FS abc\nFIELDWIDTHS ab")
+            .assert()
+    }
+
+    #[test]
+    fn it_uses_the_most_recently_assigned_splitter() {
+        utils::CodeRunner::init()
+            .program(concat!(
+                r#"BEGIN {seen = 0;} {print PROCINFO["FS"], $1;} "#,
+                r#"seen == 0 {FPAT = "[0-9]+";} "#,
+                r#"seen == 1 {FIELDWIDTHS = "1";} "#,
+                r#"seen == 2 {FIELDWIDTHS = "";} "#,
+                r#"{seen = seen + 1;}"#,
+            ))
+            .read_all_stdin()
+            .stdin_data("ab 12\nab 12\nab 12\nab 12\n")
+            .expect_output("FS ab\nFPAT 12\nFIELDWIDTHS a\nFS ab")
+            .assert()
+    }
+
+    #[test]
+    fn it_uses_fpat_assigned_after_fieldwidths() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FIELDWIDTHS = "2 2"; FPAT = "[a-c]+";} {print PROCINFO["FS"], $1;}"#)
+            .stdin_data("xabcx")
+            .expect_output("FPAT abc")
+            .assert()
+    }
+
+    #[test]
+    fn it_switches_back_to_fpat_when_it_is_assigned_its_own_value() {
+        utils::CodeRunner::init()
+            .program(concat!(
+                r#"BEGIN {FPAT = "[a-c]+"; seen = 0;} {print PROCINFO["FS"], $1;} "#,
+                r#"seen == 0 {FIELDWIDTHS = "2 2";} "#,
+                r#"seen == 1 {FPAT = "[a-c]+";} "#,
+                r#"{seen = seen + 1;}"#,
+            ))
+            .read_all_stdin()
+            .stdin_data("xabcx\nxabcx\nxabcx\n")
+            .expect_output("FPAT abc\nFIELDWIDTHS xa\nFPAT abc")
+            .assert()
+    }
+
+    #[test]
+    fn it_switches_to_the_field_separator_when_it_is_assigned_its_own_value() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {FIELDWIDTHS = "1 1";} {print PROCINFO["FS"], $1; FS = " ";}"#)
+            .read_all_stdin()
+            .stdin_data("ab cd\nab cd\n")
+            .expect_output("FIELDWIDTHS a\nFS ab")
+            .assert()
+    }
+
+    #[test]
+    fn it_fails_for_invalid_field_widths() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["-v", "FIELDWIDTHS=5 abc"])
+            .stdin_data("a")
+            .assert_error("(FILENAME=- FNR=1) invalid FIELDWIDTHS value: 5 abc")
+    }
+}
@@ -0,0 +1,73 @@
+//! Integration tests for naming fields after the header of each data file
+
+pub mod utils;
+
+#[cfg(test)]
+mod header {
+    use crate::utils;
+
+    #[test]
+    fn it_references_fields_by_name() {
+        utils::CodeRunner::init()
+            .program(r#"{print $"id", $"status";}"#)
+            .cli_options(vec!["--header", "-F,", "./tests/data/orders1.csv"])
+            .expect_output("1 shipped\n2 pending")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_the_header_of_each_data_file() {
+        utils::CodeRunner::init()
+            .program(r#"{total = total + $"total";} END {print total, $"id";}"#)
+            .cli_options(vec![
+                "--header",
+                "-F,",
+                "./tests/data/orders1.csv",
+                "./tests/data/orders2.csv",
+            ])
+            .expect_output("42 3")
+            .assert()
+    }
+
+    #[test]
+    fn it_holds_the_index_of_each_named_field_in_field() {
+        utils::CodeRunner::init()
+            .program(r#"{print FIELD["total"], $FIELD["status"];}"#)
+            .cli_options(vec!["--header", "-F,", "./tests/data/orders2.csv"])
+            .expect_output("1 shipped")
+            .assert()
+    }
+
+    #[test]
+    fn it_references_a_field_by_number_with_a_numeric_string() {
+        utils::CodeRunner::init()
+            .program(r#"{print $"2";}"#)
+            .cli_options(vec!["--header", "-F,", "./tests/data/orders2.csv"])
+            .expect_output("3")
+            .assert()
+    }
+
+    #[test]
+    fn it_reads_the_header_of_piped_data() {
+        utils::CodeRunner::init()
+            .program(r#"{print $"b";}"#)
+            .read_all_stdin()
+            .cli_options(vec!["--header", "--csv"])
+            .stdin_data("a,b\n1,\"x, y\"\n")
+            .expect_output("x, y")
+            .assert()
+    }
+
+    #[test]
+    fn fails_for_an_unknown_field_name() {
+        utils::CodeRunner::init()
+            .program(r#"{print $"nope";}"#)
+            .cli_options(vec!["--header", "-F,", "./tests/data/orders1.csv"])
+            .assert_error(concat!(
+                "cmd. line:1: (FILENAME=./tests/data/orders1.csv FNR=2) ",
+                r#"trying to access unknown field "nope""#,
+                "\n",
+                r#"{print $"nope";}"#,
+            ))
+    }
+}
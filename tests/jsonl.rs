@@ -0,0 +1,78 @@
+//! Integration tests for reading JSON Lines input
+
+pub mod utils;
+
+#[cfg(test)]
+mod jsonl {
+    use crate::utils;
+
+    #[test]
+    fn it_holds_the_values_of_each_record_by_path() {
+        utils::CodeRunner::init()
+            .program(r#"{print J["req.method"], J["status"] + 1, J["tags.1"];}"#)
+            .cli_options(vec!["--jsonl"])
+            .stdin_data(r#"{"status": 200, "req": {"method": "GET"}, "tags": ["a", "b"]}"#)
+            .expect_output("GET 201 b")
+            .assert()
+    }
+
+    #[test]
+    fn it_keeps_the_digits_of_numbers_while_comparing_them_as_numbers() {
+        utils::CodeRunner::init()
+            .program(r#"{print J["id"], J["ts"];} J["count"] > 9 {print "many";}"#)
+            .cli_options(vec!["--jsonl"])
+            .stdin_data(r#"{"id": 123456789, "ts": 1700000000123, "count": 10}"#)
+            .expect_output("123456789 1700000000123\nmany")
+            .assert()
+    }
+
+    #[test]
+    fn it_keeps_the_record_as_a_whole() {
+        utils::CodeRunner::init()
+            .program("{print;}")
+            .cli_options(vec!["--jsonl"])
+            .stdin_data(r#"{"a": 1}"#)
+            .expect_output(r#"\{"a": 1\}"#)
+            .assert()
+    }
+
+    #[test]
+    fn it_aggregates_over_records() {
+        utils::CodeRunner::init()
+            .program(r#"J["req.method"] == "GET" {total = total + J["bytes"];} END {print total;}"#)
+            .read_all_stdin()
+            .cli_options(vec!["--jsonl"])
+            .stdin_data(concat!(
+                r#"{"req": {"method": "GET"}, "bytes": 10}"#,
+                "\n",
+                r#"{"req": {"method": "POST"}, "bytes": 99}"#,
+                "\n",
+                r#"{"req": {"method": "GET"}, "bytes": 5}"#,
+                "\n",
+            ))
+            .expect_output("15")
+            .assert()
+    }
+
+    #[test]
+    fn it_clears_values_between_records() {
+        utils::CodeRunner::init()
+            .program(r#"{print "[" J["only_first"] "]";}"#)
+            .read_all_stdin()
+            .cli_options(vec!["--jsonl"])
+            .stdin_data("{\"only_first\": \"x\"}\n{}\n")
+            .expect_output("\\[x\\]\n\\[\\]")
+            .assert()
+    }
+
+    #[test]
+    fn fails_for_an_invalid_record() {
+        utils::CodeRunner::init()
+            .program("{print;}")
+            .cli_options(vec!["--jsonl"])
+            .stdin_data("{oops}")
+            .assert_error(
+                "(FILENAME=- FNR=1) invalid JSON record: key must be a string at line 1 column 2",
+            )
+    }
+}
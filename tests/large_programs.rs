@@ -0,0 +1,103 @@
+//! Integration tests for programs whose jumps and loops span a large amount of bytecode
+
+pub mod utils;
+
+#[cfg(test)]
+mod large_programs {
+    use crate::utils;
+
+    /// Builds a sequence of statements that each add one to `c`
+    ///
+    /// # Arguments
+    /// - `statements` the number of statements to build
+    fn increments(statements: usize) -> String {
+        "c=c+1;".repeat(statements)
+    }
+
+    #[test]
+    fn it_loops_over_a_body_longer_than_a_byte_can_address() {
+        let program = format!(
+            "BEGIN {{ while (i < 3) {{ {} i = i + 1; }} print c; }}",
+            increments(40)
+        );
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .expect_output("120")
+            .assert()
+    }
+
+    #[test]
+    fn it_loops_over_a_body_longer_than_two_bytes_can_address() {
+        let program = format!(
+            "BEGIN {{ while (i < 3) {{ {} i = i + 1; }} print c; }}",
+            increments(7000)
+        );
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .expect_output("21000")
+            .assert()
+    }
+
+    #[test]
+    fn it_breaks_out_of_a_large_for_loop() {
+        let program = format!(
+            "BEGIN {{ for (i = 0; ; i = i + 1) {{ if (i == 2) break; {} if (i > 5) continue; }} print c; }}",
+            increments(7000)
+        );
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .expect_output("14000")
+            .assert()
+    }
+
+    #[test]
+    fn it_jumps_over_a_large_then_branch() {
+        let program = format!(
+            "BEGIN {{ if (0) {{ {} }} else {{ c = 5; }} print c; }}",
+            increments(7000)
+        );
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .expect_output("5")
+            .assert()
+    }
+
+    #[test]
+    fn it_jumps_over_a_large_else_branch() {
+        let program = format!(
+            "BEGIN {{ if (1) {{ c = 5; }} else {{ {} }} print c; }}",
+            increments(7000)
+        );
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .expect_output("5")
+            .assert()
+    }
+
+    #[test]
+    fn it_skips_a_large_action_when_its_pattern_is_false() {
+        let program = format!("$1 > 5 {{ {} }} {{ print c + 0; }}", increments(7000));
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .stdin_data("1")
+            .expect_output("0")
+            .assert()
+    }
+
+    #[test]
+    fn it_runs_many_rules() {
+        let program = format!("{} END {{ print c; }}", "{ c = c + 1; } ".repeat(5000));
+
+        utils::CodeRunner::init()
+            .program(&program)
+            .cli_options(vec!["-q"])
+            .expect_output("5000")
+            .assert()
+    }
+}
@@ -0,0 +1,93 @@
+//! Integration tests for programs that the optimizer rewrites, which are run both with and without optimizations
+
+pub mod utils;
+
+#[cfg(test)]
+mod optimizer {
+    use crate::utils;
+
+    /// Asserts that a program prints the same output whether or not it is optimized
+    ///
+    /// # Arguments
+    /// - `program` the program to run
+    /// - `expected_output` the output that the program should print
+    fn assert_same_output(program: &str, expected_output: &str) {
+        for opts in [vec![], vec!["-s"]] {
+            utils::CodeRunner::init()
+                .program(program)
+                .cli_options(opts)
+                .stdin_data("1 2 3")
+                .expect_output(expected_output)
+                .assert()
+        }
+    }
+
+    #[test]
+    fn it_folds_arithmetic() {
+        assert_same_output("BEGIN { print 2 * 3 + 1; }", "7")
+    }
+
+    #[test]
+    fn it_folds_concatenation() {
+        assert_same_output(r#"BEGIN { print "a" "b" 1 + 2; }"#, "ab3")
+    }
+
+    #[test]
+    fn it_folds_string_comparisons() {
+        assert_same_output(r#"BEGIN { print "10" < 9; }"#, "1")
+    }
+
+    #[test]
+    fn it_folds_unary_operations() {
+        assert_same_output(r#"BEGIN { print -"a", !"", !3; }"#, "0 1 0")
+    }
+
+    #[test]
+    fn it_removes_a_branch_that_is_never_taken() {
+        assert_same_output(
+            r#"BEGIN { if (0) print "never"; else print "always"; }"#,
+            "always",
+        )
+    }
+
+    #[test]
+    fn it_keeps_the_result_of_a_constant_logical_expression() {
+        assert_same_output("BEGIN { print 1 && 0, 0 || 2, 2 || x; }", "0 1 1")
+    }
+
+    #[test]
+    fn it_keeps_the_result_of_a_constant_condition() {
+        assert_same_output(r#"BEGIN { print 1 < 2 ? "yes" : "no"; }"#, "yes")
+    }
+
+    #[test]
+    fn it_runs_a_loop_with_a_fused_condition() {
+        assert_same_output(
+            "BEGIN { while (i < 3) { if (i == 1) { i = i + 1; continue; } sum += i; i = i + 1; } print sum; }",
+            "2",
+        )
+    }
+
+    #[test]
+    fn it_runs_a_loop_whose_condition_is_always_true() {
+        assert_same_output(
+            "BEGIN { while (1) { i = i + 1; if (i >= 4) break; } print i; }",
+            "4",
+        )
+    }
+
+    #[test]
+    fn it_runs_actions_without_a_pattern() {
+        assert_same_output("{ print $2; }", "2")
+    }
+
+    #[test]
+    fn it_runs_actions_whose_pattern_is_always_true() {
+        assert_same_output(r#"1 < 2 { print $3; } "" { print "never"; }"#, "3")
+    }
+
+    #[test]
+    fn it_skips_actions_whose_pattern_is_false() {
+        assert_same_output(r#"$1 > 1 { print "never"; } $1 <= 1"#, "1 2 3")
+    }
+}
@@ -0,0 +1,80 @@
+//! Integration tests for writing the values given to print statements as comma separated values or JSON
+
+pub mod utils;
+
+#[cfg(test)]
+mod output_formats {
+    use crate::utils;
+
+    #[test]
+    fn it_separates_values_with_a_space_by_default() {
+        utils::CodeRunner::init()
+            .program("{print $2, $1 * 2;}")
+            .stdin_data("21 a,b")
+            .expect_output("a,b 42")
+            .assert()
+    }
+
+    #[test]
+    fn it_quotes_values_as_comma_separated_values() {
+        utils::CodeRunner::init()
+            .program(r#"{print $1, "say \"hi\"", $2 + 1;}"#)
+            .cli_options(vec!["--ocsv"])
+            .stdin_data("a,b 41")
+            .expect_output("\"a,b\",\"say \"\"hi\"\"\",42")
+            .assert()
+    }
+
+    #[test]
+    fn it_round_trips_comma_separated_values() {
+        utils::CodeRunner::init()
+            .program("{print $2, $1;}")
+            .cli_options(vec!["--csv", "--ocsv"])
+            .stdin_data("\"Smith, John\",42")
+            .expect_output("42,\"Smith, John\"")
+            .assert()
+    }
+
+    #[test]
+    fn it_prints_the_fields_of_a_record_as_comma_separated_values() {
+        utils::CodeRunner::init()
+            .program("{print;}")
+            .cli_options(vec!["--ocsv"])
+            .stdin_data("alice  40 25")
+            .expect_output("alice,40,25")
+            .assert()
+    }
+
+    #[test]
+    fn it_prints_values_as_a_json_array() {
+        utils::CodeRunner::init()
+            .program("{print $1, $2 * 2;}")
+            .cli_options(vec!["--ojson"])
+            .stdin_data("\"quoted\" 21")
+            // the expected output is a regular expression, so its brackets and backslashes are escaped
+            .expect_output(r#"\["\\"quoted\\"",42\]"#)
+            .assert()
+    }
+
+    #[test]
+    fn it_prints_a_record_with_a_header_as_a_json_object() {
+        utils::CodeRunner::init()
+            .program(r#"$"status" == "shipped" {print;}"#)
+            .cli_options(vec![
+                "--header",
+                "--csv",
+                "--ojson",
+                "./tests/data/orders2.csv",
+            ])
+            .expect_output(r#"\{"total":"7","id":"3","status":"shipped"\}"#)
+            .assert()
+    }
+
+    #[test]
+    fn it_rejects_more_than_one_output_format() {
+        utils::CodeRunner::init()
+            .program("{print;}")
+            .cli_options(vec!["--ocsv", "--ojson"])
+            .assert_fail()
+    }
+}
@@ -0,0 +1,56 @@
+//! Integration tests for reading data that has been piped to r-awk via STDIN
+
+pub mod utils;
+
+#[cfg(test)]
+mod piped_input {
+    use crate::utils;
+
+    #[test]
+    fn it_processes_every_piped_record() {
+        utils::CodeRunner::init()
+            .program("{print $2;}")
+            .read_all_stdin()
+            .stdin_data("a 1\nb 2\nc 3\n")
+            .expect_output("1\n2\n3")
+            .assert()
+    }
+
+    #[test]
+    fn it_processes_a_final_record_without_a_trailing_newline() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .read_all_stdin()
+            .stdin_data("a\nb")
+            .expect_output("a\nb")
+            .assert()
+    }
+
+    #[test]
+    fn it_runs_end_after_all_piped_records_are_read() {
+        utils::CodeRunner::init()
+            .program("{total = total + $2;} END {print total, $1;}")
+            .read_all_stdin()
+            .stdin_data("a 1\nb 2\nc 3\n")
+            .expect_output("6 c")
+            .assert()
+    }
+
+    #[test]
+    fn it_runs_end_when_nothing_is_piped() {
+        utils::CodeRunner::init()
+            .program("{print \"unexpected\";} END {print \"done\";}")
+            .read_all_stdin()
+            .expect_output("done")
+            .assert()
+    }
+
+    #[test]
+    fn it_exits_cleanly_when_nothing_is_piped_without_end() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .read_all_stdin()
+            .expect_empty_output()
+            .assert()
+    }
+}
@@ -0,0 +1,59 @@
+//! Integration tests for separating records by a character other than a newline
+
+pub mod utils;
+
+#[cfg(test)]
+mod record_separator {
+    use crate::utils;
+
+    #[test]
+    fn it_separates_records_by_nul_bytes() {
+        utils::CodeRunner::init()
+            .program("{print $2;}")
+            .read_all_stdin()
+            .cli_options(vec!["-0"])
+            .stdin_data("a first\0b second\nline\0")
+            .expect_output("first\nsecond")
+            .assert()
+    }
+
+    #[test]
+    fn it_separates_records_by_an_assigned_record_separator() {
+        utils::CodeRunner::init()
+            .program("{print $0;}")
+            .read_all_stdin()
+            .cli_options(vec!["-v", r"RS=\0"])
+            .stdin_data("./a file\0./another\0")
+            .expect_output("./a file\n./another")
+            .assert()
+    }
+
+    #[test]
+    fn it_separates_records_by_a_record_separator_assigned_in_begin() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {RS = ";";} {print $1;}"#)
+            .read_all_stdin()
+            .stdin_data("a 1;b 2;c 3")
+            .expect_output("a\nb\nc")
+            .assert()
+    }
+
+    #[test]
+    fn it_separates_records_by_blank_lines_when_the_record_separator_is_empty() {
+        utils::CodeRunner::init()
+            .program(r#"BEGIN {RS = "";} {print $1 $3;}"#)
+            .read_all_stdin()
+            .stdin_data("\nAlice 40\nBob 25\n\n\nCarol 30\n\n")
+            .expect_output("AliceBob\nCarol")
+            .assert()
+    }
+
+    #[test]
+    fn it_fails_for_a_record_separator_of_more_than_one_byte() {
+        utils::CodeRunner::init()
+            .program("{print $1;}")
+            .cli_options(vec!["-v", "RS=ab"])
+            .stdin_data("a")
+            .assert_error("invalid RS value: ab")
+    }
+}
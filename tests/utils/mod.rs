@@ -7,9 +7,9 @@ use assert_cmd::Command;
 #[derive(Debug)]
 #[must_use]
 pub struct CodeRunner {
-    program: Option<&'static str>,
+    program: Option<String>,
     opts: Vec<&'static str>,
-    data: &'static str,
+    data: Vec<u8>,
     expected_value: String,
     debug: bool,
 }
@@ -21,7 +21,7 @@ impl CodeRunner {
             program: None,
             // init with the 'eval' flag so a run is terminated after one piece of data is received
             opts: vec!["-k"],
-            data: "",
+            data: vec![],
             expected_value: String::from(""),
             debug: false,
         }
@@ -36,14 +36,23 @@ impl CodeRunner {
         self
     }
 
+    /// Disable the 'eval' flag, so that all of the data provided via STDIN is read
+    ///
+    /// Since the data provided via [`stdin_data`] is piped to r-awk, the run terminates once all
+    /// of it has been read.
+    pub fn read_all_stdin(mut self) -> Self {
+        self.opts.retain(|opt| *opt != "-k");
+        self
+    }
+
     /// Sets the awk program to run.
     ///
     /// Multiple invocations of this function will override previous calls.
     ///
     /// # Arguments
     /// - `program` the program that would have been received by the user as a positional argument
-    pub fn program(mut self, program: &'static str) -> Self {
-        self.program = Some(program);
+    pub fn program(mut self, program: &str) -> Self {
+        self.program = Some(String::from(program));
         self
     }
 
@@ -64,9 +73,20 @@ impl CodeRunner {
     ///
     /// # Arguments
     /// - `data` the data that would have been received from the user that the program should run
-    /// against
+    ///   against
     pub fn stdin_data(mut self, data: &'static str) -> Self {
-        self.data = data;
+        self.data = data.as_bytes().to_vec();
+        self
+    }
+
+    /// Sets the data to be provided to r-awk via STDIN to the contents of a file, which need not be text
+    ///
+    /// Multiple invocations of this function, or of [`stdin_data`], will override previous calls.
+    ///
+    /// # Arguments
+    /// - `path` the path of the file whose contents should be piped to r-awk
+    pub fn stdin_file(mut self, path: &str) -> Self {
+        self.data = std::fs::read(path).unwrap();
         self
     }
 
@@ -110,6 +130,17 @@ impl CodeRunner {
         self.build_assert().failure();
     }
 
+    /// Runs r-awk, and asserts that the command failed with the exit status used for errors, printing the provided
+    /// diagnostic to STDERR
+    ///
+    /// # Arguments
+    /// - `expected_error` the diagnostic expected to be printed, without the leading `rawk: `
+    pub fn assert_error(mut self, expected_error: &str) {
+        self.build_assert()
+            .code(2)
+            .stderr(format!("rawk: {}\n", expected_error));
+    }
+
     /// Helper method for creating an [`Assert`] object from the current state of `Self`.
     ///
     /// This method invokes r-awk, which has two important ramifications:
@@ -121,9 +152,9 @@ impl CodeRunner {
     fn build_assert(&mut self) -> Assert {
         let test_assert = Command::cargo_bin("rawk")
             .unwrap()
-            .args(self.program)
+            .args(&self.program)
             .args(&self.opts)
-            .write_stdin(self.data)
+            .write_stdin(self.data.clone())
             .assert();
 
         self.print_debug(&test_assert);